@@ -6,7 +6,13 @@
 //! - Providing sensible defaults when arguments are omitted
 //! - Converting raw arguments into structured configuration for the application
 
-use clap::{Parser, ValueEnum};
+use clap::{Parser, Subcommand, ValueEnum};
+use pyline_libs::cache::DEFAULT_CACHE_DIR;
+use pyline_libs::metrics::duplicates::DEFAULT_MIN_DUPLICATE_LINES;
+use pyline_libs::metrics::gates::Gates;
+use pyline_libs::metrics::line_length::DEFAULT_TAB_WIDTH;
+use pyline_libs::metrics::tasks::DEFAULT_TASK_TAGS;
+use pyline_libs::metrics::{ReportKind, ReportOptions};
 use pyline_libs::py::base::{
     EXCLUDE_DIRS, EXCLUDE_DOT_DIRS, EXCLUDE_FILENAMES, MARKER_FILE, VALID_EXTENSIONS,
 };
@@ -14,6 +20,7 @@ use pyline_libs::rust::base::{
     RUST_EXCLUDE_DIRS, RUST_EXCLUDE_DOT_DIRS, RUST_EXCLUDE_FILENAMES, RUST_MARKER_FILE,
     RUST_VALID_EXTENSIONS,
 };
+use pyline_libs::utils::parse_file_size;
 use std::env;
 use std::fmt::Display;
 use std::path::PathBuf;
@@ -23,7 +30,31 @@ use std::process::exit;
 #[clap(about = "A high-performance CLI tool for analyzing codebases with \
     intelligent filtering and detailed statistics collection.")]
 #[clap(author, version, long_about = None)]
+#[clap(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Args {
+    #[clap(flatten)]
+    scan: Option<ScanArgs>,
+
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Keep running and re-analyze files as they change, printing updated
+    /// totals and keyword deltas after each change.
+    Watch {
+        #[clap(flatten)]
+        scan: ScanArgs,
+
+        /// Detect changes by polling instead of native notifications.
+        #[clap(long, default_value = "false")]
+        poll: bool,
+    },
+}
+
+#[derive(clap::Args, Debug)]
+struct ScanArgs {
     /// Selects the programming language for parsing from predefined options.
     #[clap(short, long, required = true)]
     lang: CodeLang,
@@ -40,9 +71,27 @@ struct Args {
     auto_config: bool,
 
     /// Path to the directory with files to parse. If not specified,
-    /// the current directory is analyzed.
+    /// the current directory is analyzed. Can be specified multiple times;
+    /// overlapping directories are scanned once.
     #[clap(short, long, value_name = "PATH")]
-    path: Option<PathBuf>,
+    path: Vec<PathBuf>,
+
+    /// Read the list of files to analyze from FILE (`-` for stdin), one
+    /// path per line, instead of walking directories. Cannot be combined
+    /// with `--path`; directory exclusions still apply to the listed files.
+    ///
+    /// For example: `git diff --name-only | pyline -l py --files-from -`.
+    #[clap(long, value_name = "FILE", conflicts_with = "path")]
+    files_from: Option<String>,
+
+    /// Collect files at most DEPTH levels below each path (files directly
+    /// in the path have depth 1).
+    #[clap(long, value_name = "DEPTH")]
+    max_depth: Option<usize>,
+
+    /// Collect files at least DEPTH levels below each path.
+    #[clap(long, value_name = "DEPTH")]
+    min_depth: Option<usize>,
 
     /// Directories to exclude from collection.
     #[clap[short='x', long, value_name = "DIRECTORIES"]]
@@ -89,11 +138,200 @@ struct Args {
     #[clap(short = 'E', long = "gather-errors", default_value = "false")]
     no_skip_gather_errors: bool,
 
+    /// Skip files larger than SIZE (bytes, or with a K/M/G suffix).
+    #[clap(long, value_name = "SIZE", value_parser = parse_size_arg)]
+    max_file_size: Option<u64>,
+
+    /// Skip files smaller than SIZE (bytes, or with a K/M/G suffix).
+    #[clap(long, value_name = "SIZE", value_parser = parse_size_arg)]
+    min_file_size: Option<u64>,
+
+    /// Do not sniff file contents. By default, binary and minified files
+    /// are skipped, and generated files are analyzed and tagged as
+    /// generated by their header.
+    #[clap(long, default_value = "false")]
+    no_sniff: bool,
+
+    /// Count identical files (same content, hard links, bind mounts) only
+    /// once.
+    #[clap(long, default_value = "false")]
+    dedupe: bool,
+
+    /// Replace bytes that are invalid in the encoding of a file with
+    /// U+FFFD and count the file, instead of failing it.
+    #[clap(long, default_value = "false")]
+    lossy: bool,
+
+    /// Fail (exit code 1) if any file cannot be read, decoded or parsed,
+    /// instead of counting it as invalid.
+    #[clap(long, default_value = "false")]
+    strict: bool,
+
+    /// Do not use the incremental analysis cache.
+    #[clap(long, default_value = "false")]
+    no_cache: bool,
+
+    /// Directory of the incremental analysis cache. Defaults to
+    /// `.pyline-cache` in the first scanned path.
+    #[clap(long, value_name = "DIR")]
+    cache_dir: Option<PathBuf>,
+
+    /// Output format. With `json` and `sarif`, only the report is printed
+    /// to stdout (one JSON line per change in watch mode, where `sarif` is
+    /// not available); `dot` and `mermaid` print the module dependency
+    /// graph only.
+    #[clap(long, value_name = "FORMAT", default_value = "text")]
+    format: OutputFormat,
+
+    /// Additional reports to print after the statistics. Can be specified
+    /// multiple times.
+    #[clap(short, long, value_name = "REPORT")]
+    report: Vec<ReportArg>,
+
+    /// Number of entries in the "top N" lists of the reports.
+    #[clap(long, value_name = "N", default_value = "10")]
+    top: usize,
+
+    /// Fail (exit code 1) if any function has a cyclomatic complexity
+    /// above N.
+    #[clap(long, value_name = "N")]
+    max_complexity: Option<usize>,
+
+    /// Tags of the task markers to report (comma-separated). Defaults to
+    /// TODO, FIXME, HACK and XXX.
+    #[clap(long, value_name = "TAGS", value_delimiter = ',')]
+    task_tags: Vec<String>,
+
+    /// Fail (exit code 1) if there are more than N open FIXME markers.
+    #[clap(long, value_name = "N")]
+    max_fixmes: Option<usize>,
+
+    /// Fail (exit code 1) if there are more than N unsafe sites in Rust
+    /// code (e.g. the count of the last release).
+    #[clap(long, value_name = "N")]
+    max_unsafe: Option<usize>,
+
+    /// Fail (exit code 1) if less than PCT percent of the parameters and
+    /// return types of Python functions are annotated.
+    #[clap(long, value_name = "PCT")]
+    min_type_coverage: Option<f64>,
+
+    /// Fail (exit code 1) if there are fewer than RATIO comment lines per
+    /// code line (e.g. 0.1).
+    #[clap(long, value_name = "RATIO")]
+    min_comment_ratio: Option<f64>,
+
+    /// Count indexing with `[]` as a panic path in the panics report
+    /// (heuristic, may report indexing that cannot panic).
+    #[clap(long, default_value = "false")]
+    panic_index: bool,
+
+    /// Code lines of the smallest clone in the duplicates report.
+    #[clap(long, value_name = "N", default_value_t = DEFAULT_MIN_DUPLICATE_LINES)]
+    min_dup_lines: usize,
+
+    /// Replace identifiers and literals with placeholders when looking for
+    /// duplicated code, to find renamed copies too.
+    #[clap(long, default_value = "false")]
+    dup_placeholders: bool,
+
+    /// Columns of a tab in the line-length report.
+    #[clap(long, value_name = "N", default_value_t = DEFAULT_TAB_WIDTH)]
+    tab_width: usize,
+
+    /// Count the lines longer than N columns in the line-length report.
+    #[clap(long, value_name = "N")]
+    max_line_length: Option<usize>,
+
     /// Enable verbose output with detailed logging information.
     #[clap(short, long)]
     verbose: bool,
 }
 
+#[derive(Clone, Copy, ValueEnum, Debug, PartialEq, Eq)]
+enum ReportArg {
+    /// Functions, methods, classes and lambdas with their sizes.
+    Symbols,
+    /// Cyclomatic complexity of functions.
+    Complexity,
+    /// Nesting depth per file and function, mixed tab/space indentation.
+    Nesting,
+    /// Task markers (TODO, FIXME, ...) in comments and docstrings.
+    Tasks,
+    /// Unsafe blocks, functions, impls, traits and extern blocks (Rust).
+    Unsafe,
+    /// Panic-prone call sites (unwrap, expect, panic!, ...) and robustness
+    /// per crate (Rust).
+    Panics,
+    /// Macro invocations, attributes, derived traits and `#[allow]` lint
+    /// suppressions (Rust).
+    Macros,
+    /// Decorators, imported modules (stdlib, third-party, local) and dunder
+    /// methods, with star and relative imports (Python).
+    Usage,
+    /// Module dependency graph: import cycles, fan-in and fan-out.
+    Graph,
+    /// Type-hint coverage per file and package, `Any`, `cast()` and
+    /// `# type: ignore` counts (Python).
+    Types,
+    /// Duplicated code blocks (clone groups) and duplicated-line share.
+    Duplicates,
+    /// Halstead volume, difficulty and effort with the maintainability
+    /// index per file and function.
+    Halstead,
+    /// Comment-to-code and doc-to-code ratios, documented public items per
+    /// file and directory.
+    Comments,
+    /// Histogram of line lengths in display columns, longest lines and
+    /// lines over --max-line-length.
+    LineLength,
+    /// Trailing whitespace, mixed line endings, missing final newline, BOM
+    /// and tabs in Python code, per file.
+    Whitespace,
+    /// Encodings of the files (BOM, coding declaration, heuristics) and
+    /// files decoded lossily.
+    Encodings,
+}
+
+impl From<ReportArg> for ReportKind {
+    fn from(arg: ReportArg) -> Self {
+        match arg {
+            ReportArg::Symbols => ReportKind::Symbols,
+            ReportArg::Complexity => ReportKind::Complexity,
+            ReportArg::Nesting => ReportKind::Nesting,
+            ReportArg::Tasks => ReportKind::Tasks,
+            ReportArg::Unsafe => ReportKind::Unsafe,
+            ReportArg::Panics => ReportKind::Panics,
+            ReportArg::Macros => ReportKind::Macros,
+            ReportArg::Usage => ReportKind::Usage,
+            ReportArg::Graph => ReportKind::Graph,
+            ReportArg::Types => ReportKind::TypeHints,
+            ReportArg::Duplicates => ReportKind::Duplicates,
+            ReportArg::Halstead => ReportKind::Halstead,
+            ReportArg::Comments => ReportKind::Comments,
+            ReportArg::LineLength => ReportKind::LineLength,
+            ReportArg::Whitespace => ReportKind::Whitespace,
+            ReportArg::Encodings => ReportKind::Encodings,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human-readable report.
+    #[default]
+    Text,
+    /// Machine-readable JSON.
+    Json,
+    /// SARIF 2.1.0 log of the findings (task markers, undocumented unsafe
+    /// blocks), for code scanning tools.
+    Sarif,
+    /// Module dependency graph as Graphviz DOT.
+    Dot,
+    /// Module dependency graph as a Mermaid flowchart.
+    Mermaid,
+}
+
 #[derive(Clone, ValueEnum, Debug, Default)]
 pub enum CodeLang {
     /// alias `py`.
@@ -115,7 +353,10 @@ impl Display for CodeLang {
 
 #[derive(Default, Clone)]
 pub struct ArgsResult {
-    pub path: PathBuf,
+    pub paths: Vec<PathBuf>,
+    pub files_from: Option<Vec<PathBuf>>,
+    pub max_depth: Option<usize>,
+    pub min_depth: Option<usize>,
     pub dirs: Vec<String>,
     pub marker_files: Vec<String>,
     pub extension: Vec<String>,
@@ -125,6 +366,24 @@ pub struct ArgsResult {
     pub ignore_dot_dirs: bool,
     auto_config: bool,
     pub skip_gather_errors: bool,
+    pub max_file_size: Option<u64>,
+    pub min_file_size: Option<u64>,
+    pub sniff_content: bool,
+    pub dedupe: bool,
+    pub lossy: bool,
+    pub strict: bool,
+    pub cache_dir: Option<PathBuf>,
+    pub format: OutputFormat,
+    pub reports: Vec<ReportKind>,
+    pub report_options: ReportOptions,
+    pub gates: Gates,
+    pub watch: Option<WatchOptions>,
+}
+
+/// Options of the `watch` subcommand.
+#[derive(Default, Clone, Copy)]
+pub struct WatchOptions {
+    pub poll: bool,
 }
 
 impl ArgsResult {
@@ -275,6 +534,8 @@ impl ArgsResult {
         format!(
             "Arguments:\n\
              ├─ Path: {}\n\
+             ├─ Files from list: {}\n\
+             ├─ Depth: {}\n\
              ├─ Exclude Directories: {}\n\
              ├─ Marker_files: {}\n\
              ├─ Ignore dot dirs: {}\n\
@@ -282,8 +543,22 @@ impl ArgsResult {
              ├─ Exclude Filenames: {}\n\
              ├─ Language: {:?}\n\
              ├─ Skip gather errors: {}\n\
+             ├─ File size limits: {}\n\
+             ├─ Sniff content: {}\n\
+             ├─ Dedupe files: {}\n\
+             ├─ Lossy decoding: {}\n\
+             ├─ Strict parsing: {}\n\
+             ├─ Cache: {}\n\
+             ├─ Output format: {:?}\n\
+             ├─ Reports: {}\n\
+             ├─ Gates: {:?}\n\
              └─ Verbose: {}",
-            self.path.display(),
+            self.paths_display(),
+            self.files_from
+                .as_ref()
+                .map(|files| format!("{} files", files.len()))
+                .unwrap_or("not set".to_string()),
+            Self::range_display(self.min_depth, self.max_depth),
             dirs,
             marker_files,
             self.ignore_dot_dirs,
@@ -291,10 +566,50 @@ impl ArgsResult {
             filenames,
             self.lang,
             self.skip_gather_errors,
+            Self::range_display(self.min_file_size, self.max_file_size),
+            self.sniff_content,
+            self.dedupe,
+            self.lossy,
+            self.strict,
+            self.cache_dir
+                .as_ref()
+                .map(|dir| dir.display().to_string())
+                .unwrap_or("disabled".to_string()),
+            self.format,
+            Self::join_or_wildcard(
+                &self.reports.iter().map(|r| r.name()).collect::<Vec<_>>(),
+                ", "
+            ),
+            self.gates,
             self.verbose
         )
     }
 
+    /// Returns `true` if the human-readable report was requested.
+    pub fn is_text(&self) -> bool {
+        self.format == OutputFormat::Text
+    }
+
+    /// Returns the scanned paths joined with commas.
+    pub fn paths_display(&self) -> String {
+        self.paths
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn range_display<T: Display>(min: Option<T>, max: Option<T>) -> String {
+        match (min, max) {
+            (None, None) => "not set".to_string(),
+            (min, max) => format!(
+                "{}..{}",
+                min.map(|n| n.to_string()).unwrap_or_default(),
+                max.map(|n| n.to_string()).unwrap_or_default()
+            ),
+        }
+    }
+
     fn join_or_wildcard<T: AsRef<str>>(items: &[T], separator: &str) -> String {
         if items.is_empty() {
             "not set".to_string()
@@ -312,12 +627,57 @@ impl ArgsResult {
 ///
 /// Control is not returned until valid data is received from the user.
 pub fn read_cmd_args() -> ArgsResult {
-    let args = Args::parse();
-
-    let path = parse_path(args.path);
+    let (args, watch) = match Args::parse() {
+        Args {
+            command: Some(Command::Watch { scan, poll }),
+            ..
+        } => (scan, Some(WatchOptions { poll })),
+        Args {
+            scan: Some(scan), ..
+        } => (scan, None),
+        Args { scan: None, .. } => exit_err("No arguments given. Try `--help`."),
+    };
+
+    let mut paths = parse_paths(args.path);
+    if watch.is_some() {
+        // Change notifications carry absolute paths.
+        paths = paths.into_iter().map(canonicalize_path).collect();
+    }
+    let mut files_from = args.files_from.map(|source| read_files_list(&source));
+    if watch.is_some() {
+        // Watched files are tracked by the absolute paths of the changes.
+        files_from = files_from.map(|files| {
+            files
+                .into_iter()
+                .map(|file| file.canonicalize().unwrap_or(file))
+                .collect()
+        });
+    }
+    if watch.is_some() && !matches!(args.format, OutputFormat::Text | OutputFormat::Json) {
+        exit_err("SARIF, DOT and Mermaid output are not available in watch mode.");
+    }
+    let task_tags = if args.task_tags.is_empty() {
+        DEFAULT_TASK_TAGS.iter().map(|t| t.to_string()).collect()
+    } else {
+        args.task_tags
+            .iter()
+            .map(|t| t.trim().to_uppercase())
+            .collect()
+    };
+    let mut reports: Vec<ReportKind> = args.report.into_iter().map(ReportKind::from).collect();
+    reports.sort();
+    reports.dedup();
+    let cache_dir = match (args.no_cache, args.cache_dir) {
+        (true, _) => None,
+        (false, Some(dir)) => Some(dir),
+        (false, None) => Some(paths[0].join(DEFAULT_CACHE_DIR)),
+    };
 
     ArgsResult {
-        path,
+        paths,
+        files_from,
+        max_depth: args.max_depth,
+        min_depth: args.min_depth,
         auto_config: args.auto_config,
         dirs: args.exclude_dirs,
         marker_files: args.marker_files,
@@ -326,18 +686,77 @@ pub fn read_cmd_args() -> ArgsResult {
         filenames: args.exclude_files,
         lang: args.lang,
         skip_gather_errors: !args.no_skip_gather_errors,
+        max_file_size: args.max_file_size,
+        min_file_size: args.min_file_size,
+        sniff_content: !args.no_sniff,
+        dedupe: args.dedupe,
+        lossy: args.lossy,
+        strict: args.strict,
+        cache_dir,
+        format: args.format,
+        reports,
+        report_options: ReportOptions {
+            top: args.top,
+            task_tags,
+            panic_index: args.panic_index,
+            min_duplicate_lines: args.min_dup_lines,
+            duplicate_placeholders: args.dup_placeholders,
+            tab_width: args.tab_width,
+            max_line_length: args.max_line_length,
+        },
+        gates: Gates {
+            max_complexity: args.max_complexity,
+            max_fixmes: args.max_fixmes,
+            max_unsafe: args.max_unsafe,
+            min_type_coverage: args.min_type_coverage,
+            min_comment_ratio: args.min_comment_ratio,
+        },
+        watch,
         verbose: args.verbose,
     }
 }
 
-/// Parses and validates the input path argument.
+/// Value parser for file size arguments (`512`, `64K`, `2M`).
+fn parse_size_arg(size: &str) -> Result<u64, String> {
+    parse_file_size(size).map_err(|err| err.to_string())
+}
+
+/// Parses and validates the input path arguments.
 ///
-/// If a path is provided, validates it as an existing directory.
+/// If paths are provided, validates each as an existing directory.
 /// If no path is provided, returns the current working directory.
-fn parse_path(args_path: Option<PathBuf>) -> PathBuf {
-    match args_path {
-        Some(path) => validate_directory_path(path),
-        None => get_current_dir(),
+fn parse_paths(args_paths: Vec<PathBuf>) -> Vec<PathBuf> {
+    if args_paths.is_empty() {
+        return vec![get_current_dir()];
+    }
+
+    args_paths
+        .into_iter()
+        .map(validate_directory_path)
+        .collect()
+}
+
+/// Reads a list of files, one per line, from a file or from stdin (`-`).
+///
+/// Blank lines are ignored.
+fn read_files_list(source: &str) -> Vec<PathBuf> {
+    let content = if source == "-" {
+        std::io::read_to_string(std::io::stdin())
+    } else {
+        std::fs::read_to_string(source)
+    };
+
+    match content {
+        Ok(content) => content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(PathBuf::from)
+            .collect(),
+        Err(err) => exit_err(format!(
+            "Failed to read the list of files {}: {}",
+            source, err
+        )),
     }
 }
 
@@ -363,6 +782,12 @@ fn validate_directory_path(path: PathBuf) -> PathBuf {
     path
 }
 
+/// Returns the canonical, absolute form of an existing path.
+fn canonicalize_path(path: PathBuf) -> PathBuf {
+    path.canonicalize()
+        .unwrap_or_else(|err| exit_err(format!("{}: {}", path.display(), err)))
+}
+
 /// Get path to the current directory.
 fn get_current_dir() -> PathBuf {
     env::current_dir().expect("ERROR: Current directory could not be determined.")
@@ -6,16 +6,28 @@
 //! Shindler7, 2025.
 #![warn(missing_docs)]
 
-use pyline_libs::traits::{CodeParsers, FileDataExt};
+use pyline_libs::traits::FileDataExt;
 mod cli;
 mod config;
 mod tools;
+mod watch;
 
-use crate::cli::{ArgsResult, CodeLang};
-use crate::tools::show_dot;
+use crate::cli::{ArgsResult, CodeLang, OutputFormat};
+use crate::tools::{error_chain, print_info, show_dot};
+use pyline_libs::cache::{AnalysisCache, Cacheable};
 use pyline_libs::collector::{Collector, CollectorResult, FileData};
-use pyline_libs::errors::PyLineError;
+use pyline_libs::errors::{ErrorKind, PyLineError};
+use pyline_libs::metrics::Report;
+use pyline_libs::metrics::gates::GateViolation;
+use pyline_libs::metrics::graph::DependencyGraph;
+use pyline_libs::metrics::sarif::SarifLog;
+use pyline_libs::metrics::tasks::TaskReport;
+use pyline_libs::metrics::unsafety::UnsafeReport;
 use pyline_libs::parser::{Python, Rust};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
 use std::process::exit;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -23,7 +35,7 @@ use std::sync::atomic::{AtomicBool, Ordering};
 #[tokio::main]
 async fn main() {
     if let Err(e) = run().await {
-        eprintln!("Error: {}", e);
+        eprintln!("Error: {}", error_chain(&e));
         exit(1);
     }
 }
@@ -32,26 +44,69 @@ async fn run() -> Result<(), PyLineError> {
     let cli_result = cli::read_cmd_args().normalize_by_lang();
 
     if cli_result.verbose {
-        println!("{}", cli_result.verbose_display());
-    } else {
+        print_info(cli_result.is_text(), cli_result.verbose_display());
+    } else if cli_result.is_text() {
         println!("\nSelected language: {}\n", cli_result.lang);
-        println!(
-            "The files in the directory are being examined: {}",
-            cli_result.path.display()
-        );
+        match &cli_result.files_from {
+            Some(files) => println!("The listed files are being examined: {}", files.len()),
+            None => println!(
+                "The files in the directory are being examined: {}",
+                cli_result.paths_display()
+            ),
+        }
     }
 
-    let files = collect_files(&cli_result).await?;
+    let collector = build_collector(&cli_result);
+    let files = collect_files(&cli_result, &collector).await?;
 
     // About errors and verbose.
     if files.has_errors() {
-        println!(
-            "\nWARNINGS! During the gathering process, {} errors occurred.",
-            files.num_errors()
+        print_info(
+            cli_result.is_text(),
+            format!(
+                "\nWARNINGS! During the gathering process, {} errors occurred ({}).",
+                files.num_errors(),
+                files
+                    .error_summary()
+                    .iter()
+                    .map(|(kind, count)| format!("{} {}", count, kind))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
         );
         if cli_result.verbose {
             for err in files.errors() {
-                eprintln!("\n{}", err);
+                eprintln!("\n{}", error_chain(err));
+            }
+        }
+    }
+
+    if files.has_skipped() {
+        print_info(
+            cli_result.is_text(),
+            format!(
+                "\n{} files were skipped by size limits or content sniffing.",
+                files.num_skipped()
+            ),
+        );
+        if cli_result.verbose {
+            for skipped in files.skipped() {
+                print_info(cli_result.is_text(), format!("  {}", skipped));
+            }
+        }
+    }
+
+    if files.has_duplicates() {
+        print_info(
+            cli_result.is_text(),
+            format!(
+                "\n{} duplicate files were counted once.",
+                files.num_duplicates()
+            ),
+        );
+        if cli_result.verbose {
+            for group in files.duplicates() {
+                print_info(cli_result.is_text(), format!("  {}", group));
             }
         }
     }
@@ -60,18 +115,70 @@ async fn run() -> Result<(), PyLineError> {
         return Ok(());
     }
 
-    println!(" Successfully gathered {} files.", files.num_files());
+    if cli_result.is_text() {
+        println!(" Successfully gathered {} files.", files.num_files());
+    }
 
     if cli_result.verbose {
-        println!("\n{}", files.files().join_verbose(""));
+        print_info(
+            cli_result.is_text(),
+            format!("\n{}", files.files().join_verbose("")),
+        );
     }
 
-    analyze_files(&cli_result, files.files()).await?;
+    match (&cli_result.lang, cli_result.watch) {
+        (CodeLang::Python, None) => analyze_files::<Python>(&cli_result, files.files()).await,
+        (CodeLang::Rust, None) => analyze_files::<Rust>(&cli_result, files.files()).await,
+        (CodeLang::Python, Some(options)) => {
+            watch::watch::<Python>(&cli_result, options, &collector, files.files()).await
+        }
+        (CodeLang::Rust, Some(options)) => {
+            watch::watch::<Rust>(&cli_result, options, &collector, files.files()).await
+        }
+    }
+}
 
-    Ok(())
+/// Configures the file collector from the command-line arguments.
+fn build_collector(cli_result: &ArgsResult) -> Collector {
+    let mut collector = Collector::new(&cli_result.paths[0])
+        .add_roots(&cli_result.paths[1..])
+        .ignore_dot_dirs(cli_result.ignore_dot_dirs)
+        .extensions(&cli_result.extension)
+        .exclude_dirs(&cli_result.dirs)
+        .with_marker_files(&cli_result.marker_files)
+        .exclude_files(&cli_result.filenames)
+        .skip_errors(cli_result.skip_gather_errors)
+        .sniff_content(cli_result.sniff_content)
+        .dedupe(cli_result.dedupe)
+        .lossy(cli_result.lossy);
+
+    if let Some(files) = &cli_result.files_from {
+        collector = collector.files_from(files);
+    }
+    if let Some(depth) = cli_result.max_depth {
+        collector = collector.max_depth(depth);
+    }
+    if let Some(depth) = cli_result.min_depth {
+        collector = collector.min_depth(depth);
+    }
+    if let Some(max) = cli_result.max_file_size {
+        collector = collector.max_file_size(max);
+    }
+    if let Some(min) = cli_result.min_file_size {
+        collector = collector.min_file_size(min);
+    }
+
+    collector
 }
 
-async fn collect_files(cli_result: &ArgsResult) -> Result<CollectorResult, PyLineError> {
+async fn collect_files(
+    cli_result: &ArgsResult,
+    collector: &Collector,
+) -> Result<CollectorResult, PyLineError> {
+    if !cli_result.is_text() {
+        return collector.complete().await;
+    }
+
     let running = Arc::new(AtomicBool::new(true));
     let spinner_handle = {
         let running = running.clone();
@@ -80,15 +187,7 @@ async fn collect_files(cli_result: &ArgsResult) -> Result<CollectorResult, PyLin
 
     print!("\nGathering files for analysis... ");
 
-    let files = Collector::new(&cli_result.path)
-        .ignore_dot_dirs(cli_result.ignore_dot_dirs)
-        .extensions(&cli_result.extension)
-        .exclude_dirs(&cli_result.dirs)
-        .with_marker_files(&cli_result.marker_files)
-        .exclude_files(&cli_result.filenames)
-        .skip_errors(cli_result.skip_gather_errors)
-        .complete()
-        .await?;
+    let files = collector.complete().await?;
 
     // Spinner stop.
     running.store(false, Ordering::Relaxed);
@@ -105,25 +204,190 @@ async fn collect_files(cli_result: &ArgsResult) -> Result<CollectorResult, PyLin
     Ok(files)
 }
 
-async fn analyze_files(cli_result: &ArgsResult, files: &[FileData]) -> Result<(), PyLineError> {
-    print!("\nGathering code stats... ");
+async fn analyze_files<C: Cacheable + Display>(
+    cli_result: &ArgsResult,
+    files: &[FileData],
+) -> Result<(), PyLineError> {
+    if cli_result.is_text() {
+        print!("\nGathering code stats... ");
+    }
+
+    let stats: C = parse_files(cli_result, files).await?;
+    let failures = file_failures(&stats);
+    if cli_result.verbose || cli_result.strict {
+        print_failures(&failures);
+    }
+    if cli_result.strict && !failures.is_empty() {
+        return Err(PyLineError::counter_error(format!(
+            "{} file(s) failed to parse (--strict).",
+            failures.len()
+        )));
+    }
 
-    match cli_result.lang {
-        CodeLang::Python => {
-            let mut python_stats = Python::new();
-            python_stats.parse(files).await?;
+    let reports: BTreeMap<_, _> = cli_result
+        .reports
+        .iter()
+        .map(|kind| {
+            let report = stats.metrics().report(*kind, &cli_result.report_options);
+            (kind.name(), report)
+        })
+        .collect();
 
-            print!("OK.");
-            println!("\n{}\n", python_stats);
-        }
-        CodeLang::Rust => {
-            let mut rust_stats = Rust::new();
-            rust_stats.parse(files).await?;
+    let violations = cli_result.gates.check(stats.metrics());
 
+    match cli_result.format {
+        OutputFormat::Text => {
             print!("OK.");
-            println!("\n{}\n", rust_stats);
+            println!("\n{}\n", stats);
+            for report in reports.values() {
+                println!("{}\n", report);
+            }
+            if !violations.is_empty() {
+                println!("Gate violations:");
+                for violation in &violations {
+                    println!("  {}", violation);
+                }
+                println!();
+            }
+        }
+        OutputFormat::Json => println!(
+            "{}",
+            to_json(&AnalysisOutput {
+                stats: &stats,
+                reports,
+                gate_violations: &violations,
+                failures: &failures,
+            })?
+        ),
+        OutputFormat::Sarif => {
+            let metrics = stats.metrics();
+            let tasks = TaskReport::new(&metrics.tasks, &cli_result.report_options.task_tags);
+            let mut results = tasks.sarif_results();
+            results.extend(UnsafeReport::new(&metrics.unsafe_sites).sarif_results());
+            println!("{}", to_json(&SarifLog::new(&results))?);
         }
+        OutputFormat::Dot | OutputFormat::Mermaid => {
+            let metrics = stats.metrics();
+            let graph = DependencyGraph::new(
+                &metrics.imports,
+                &metrics.files,
+                cli_result.report_options.top,
+            );
+            match cli_result.format {
+                OutputFormat::Dot => println!("{}", graph.to_dot()),
+                _ => println!("{}", graph.to_mermaid()),
+            }
+        }
+    }
+
+    if !violations.is_empty() {
+        return Err(PyLineError::gate_error(format!(
+            "{} quality gate violation(s).",
+            violations.len()
+        )));
     }
 
     Ok(())
 }
+
+/// JSON output of an analysis: the statistics with the requested reports.
+#[derive(Serialize)]
+struct AnalysisOutput<'a, C> {
+    #[serde(flatten)]
+    stats: &'a C,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    reports: BTreeMap<&'static str, Report>,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    gate_violations: &'a [GateViolation],
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    failures: &'a [FileFailure<'a>],
+}
+
+/// A file that failed to parse, in the output.
+#[derive(Serialize)]
+struct FileFailure<'a> {
+    path: &'a Path,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    line: Option<usize>,
+    kind: ErrorKind,
+    error: String,
+}
+
+impl<'a> FileFailure<'a> {
+    fn new(path: &'a Path, error: &PyLineError) -> Self {
+        Self {
+            path,
+            line: error.line(),
+            kind: error.kind(),
+            error: error.message(),
+        }
+    }
+}
+
+/// Returns the files of `stats` that failed to parse, for the output.
+fn file_failures<C: Cacheable>(stats: &C) -> Vec<FileFailure<'_>> {
+    stats
+        .failures()
+        .iter()
+        .map(|(path, error)| FileFailure::new(path, error))
+        .collect()
+}
+
+/// Prints the files that failed to parse to stderr.
+fn print_failures(failures: &[FileFailure]) {
+    if failures.is_empty() {
+        return;
+    }
+    eprintln!("\n{} files failed to parse:", failures.len());
+    for failure in failures {
+        eprintln!("  {}", failure);
+    }
+}
+
+impl Display for FileFailure<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}: {}", self.path.display(), line, self.error),
+            None => write!(f, "{}: {}", self.path.display(), self.error),
+        }
+    }
+}
+
+/// Serializes a report to a single JSON line.
+fn to_json(report: &impl Serialize) -> Result<String, PyLineError> {
+    serde_json::to_string(report)
+        .map_err(|err| PyLineError::counter_error(format!("JSON encoding: {}", err)))
+}
+
+/// Parses the files, through the incremental cache unless it is disabled.
+///
+/// A cache that cannot be written is reported but does not fail the run.
+async fn parse_files<C: Cacheable>(
+    cli_result: &ArgsResult,
+    files: &[FileData],
+) -> Result<C, PyLineError> {
+    let mut stats = C::new();
+
+    let Some(cache_dir) = &cli_result.cache_dir else {
+        stats.parse(files).await?;
+        return Ok(stats);
+    };
+
+    let mut cache = AnalysisCache::<C>::load(cache_dir).await;
+    cache.parse(&mut stats, files).await?;
+
+    if let Err(err) = cache.save().await {
+        eprintln!(
+            "\nWARNING! Failed to save the analysis cache: {}",
+            error_chain(&err)
+        );
+    }
+    if cli_result.verbose {
+        let stat = cache.stat();
+        if cli_result.is_text() {
+            print!("(cache: {} hits, {} parsed) ", stat.hits, stat.misses);
+        }
+    }
+
+    Ok(stats)
+}
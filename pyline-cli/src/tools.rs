@@ -1,5 +1,7 @@
 //! Tools and utils for pyline.
 
+use std::error::Error;
+use std::fmt::Display;
 use std::io::Write;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -38,3 +40,27 @@ pub async fn show_dot(running: Arc<AtomicBool>) {
 
     Write::flush(&mut std::io::stdout()).unwrap();
 }
+
+/// Prints an informational message.
+///
+/// Goes to stdout in the text mode and to stderr otherwise, so that
+/// machine-readable output on stdout stays clean.
+pub fn print_info(to_stdout: bool, message: impl Display) {
+    if to_stdout {
+        println!("{}", message);
+    } else {
+        eprintln!("{}", message);
+    }
+}
+
+/// Formats an error followed by its chain of sources, separated by `: `.
+pub fn error_chain(error: &dyn Error) -> String {
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(error) = source {
+        message.push_str(": ");
+        message.push_str(&error.to_string());
+        source = error.source();
+    }
+    message
+}
@@ -0,0 +1,87 @@
+//! `watch` subcommand: live-updating statistics.
+
+use crate::cli::{ArgsResult, OutputFormat, WatchOptions};
+use crate::{FileFailure, file_failures, print_failures};
+use pyline_libs::cache::Cacheable;
+use pyline_libs::collector::{Collector, FileData};
+use pyline_libs::errors::PyLineError;
+use pyline_libs::watch::{DEBOUNCE, FileWatcher, POLL_INTERVAL, WatchState, WatchUpdate};
+use serde::Serialize;
+use std::fmt::Display;
+
+/// One line of the JSON output of watch mode.
+#[derive(Serialize)]
+struct WatchEvent<'a, C> {
+    #[serde(flatten)]
+    update: &'a WatchUpdate,
+    totals: &'a C,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    failures: &'a [FileFailure<'a>],
+}
+
+/// Analyzes `files`, then keeps re-analyzing the files that change until
+/// the process is interrupted.
+///
+/// The same `collector` that gathered `files` decides whether a changed
+/// path is part of the analysis.
+pub async fn watch<C: Cacheable + Display>(
+    cli_result: &ArgsResult,
+    options: WatchOptions,
+    collector: &Collector,
+    files: &[FileData],
+) -> Result<(), PyLineError> {
+    let mut state = WatchState::<C>::new(files).await;
+
+    let roots = collector.roots();
+    let mut watcher = if options.poll {
+        FileWatcher::polling(roots, POLL_INTERVAL)?
+    } else {
+        FileWatcher::new(roots)?
+    };
+
+    print_update(cli_result, &WatchUpdate::default(), state.totals())?;
+    if cli_result.is_text() {
+        println!(
+            "\nWatching for changes ({}). Press Ctrl+C to stop.",
+            watcher.mode()
+        );
+    }
+
+    while let Some(changed) = watcher.next_batch(DEBOUNCE).await {
+        let update = state.update(collector, &changed).await?;
+        if !update.is_empty() {
+            print_update(cli_result, &update, state.totals())?;
+        }
+    }
+
+    Ok(())
+}
+
+fn print_update<C: Cacheable + Display>(
+    cli_result: &ArgsResult,
+    update: &WatchUpdate,
+    totals: &C,
+) -> Result<(), PyLineError> {
+    let failures = file_failures(totals);
+    match cli_result.format {
+        OutputFormat::Text => {
+            if !update.is_empty() {
+                println!("\n{}", update);
+            }
+            println!("\n{}", totals);
+            if cli_result.verbose || cli_result.strict {
+                print_failures(&failures);
+            }
+        }
+        OutputFormat::Json | OutputFormat::Sarif | OutputFormat::Dot | OutputFormat::Mermaid => {
+            let event = WatchEvent {
+                update,
+                totals,
+                failures: &failures,
+            };
+            println!("{}", crate::to_json(&event)?);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,377 @@
+//! Incremental analysis cache.
+//!
+//! Stores per-file parsing results on disk, keyed by path, size, modification
+//! time and a fast content hash, so that repeated runs over an unchanged tree
+//! only re-parse the files that actually changed. A result is also re-parsed
+//! when the [`Origin`] of its file changed. Cached and fresh results are
+//! combined through [`CodeParsers::merge_ref`]. The per-line data that is not
+//! serialized with the results (the code line hashes and line widths) is
+//! stored next to them. Entries of files that were not analyzed since loading
+//! (e.g. deleted or renamed ones) are dropped when the cache is saved.
+//!
+//! The cache is a single compact JSON file per language inside the cache
+//! directory. It is discarded as a whole when the library version or the
+//! language definition ([`LangDefinition::fingerprint`]) changes, or when it
+//! cannot be read back.
+
+use crate::collector::FileData;
+use crate::errors::{Operation, PyLineError};
+use crate::metrics::duplicates::CodeLine;
+use crate::metrics::line_length::LineWidth;
+use crate::origin::Origin;
+use crate::traits::{CodeParsers, LangDefinition};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tokio::fs;
+use xxhash_rust::xxh3::xxh3_64;
+
+/// Default name of the cache directory.
+pub const DEFAULT_CACHE_DIR: &str = ".pyline-cache";
+
+/// Version of the on-disk format. Bumped on incompatible layout changes.
+const CACHE_FORMAT: u32 = 4;
+
+/// A cached result of a single file.
+#[derive(Serialize, Deserialize)]
+struct CacheEntry<C> {
+    /// File size in bytes.
+    size: u64,
+    /// Modification time in nanoseconds since the UNIX epoch.
+    mtime: u64,
+    /// xxh3 hash of the file content.
+    hash: u64,
+    /// Origin of the file when it was parsed.
+    origin: Origin,
+    /// Parsing result of the file.
+    result: C,
+    /// Per-line data of the files in `result`, only filled while the entry
+    /// is written or read.
+    #[serde(default)]
+    lines: Vec<FileLines>,
+}
+
+impl<C: Cacheable> CacheEntry<C> {
+    /// Moves the per-line data out of the result, to be written.
+    fn detach_lines(&mut self) {
+        self.lines = self
+            .result
+            .metrics_mut()
+            .files
+            .iter_mut()
+            .map(|file| FileLines {
+                code_lines: std::mem::take(&mut file.code_lines),
+                line_widths: std::mem::take(&mut file.line_widths),
+            })
+            .collect();
+    }
+
+    /// Moves the per-line data back into the result.
+    fn attach_lines(&mut self) {
+        let files = self.result.metrics_mut().files.iter_mut();
+        for (file, lines) in files.zip(self.lines.drain(..)) {
+            file.code_lines = lines.code_lines;
+            file.line_widths = lines.line_widths;
+        }
+    }
+}
+
+/// Per-line data of a cached file that is not serialized with the result.
+#[derive(Serialize, Deserialize)]
+struct FileLines {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    code_lines: Vec<CodeLine>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    line_widths: Vec<LineWidth>,
+}
+
+/// On-disk representation of the cache, generic over owned or borrowed
+/// entries.
+#[derive(Serialize, Deserialize)]
+struct CacheFile<E> {
+    format: u32,
+    version: String,
+    fingerprint: u64,
+    entries: E,
+}
+
+/// Statistics of a cached parsing run.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CacheStat {
+    /// Files whose result was taken from the cache.
+    pub hits: usize,
+    /// Files that were parsed again.
+    pub misses: usize,
+}
+
+/// Language parsers whose per-file results can be cached.
+///
+/// Implemented automatically for every parser generated by
+/// [`crate::impl_lang_parser!`] that also implements [`LangDefinition`].
+pub trait Cacheable:
+    CodeParsers<Code = Self> + LangDefinition + Serialize + DeserializeOwned
+{
+}
+
+impl<C> Cacheable for C where
+    C: CodeParsers<Code = C> + LangDefinition + Serialize + DeserializeOwned
+{
+}
+
+/// Persistent cache of per-file parsing results for the language `C`.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::path::Path;
+/// use pyline_libs::cache::AnalysisCache;
+/// use pyline_libs::collector::FileData;
+/// use pyline_libs::errors::PyLineError;
+/// use pyline_libs::parser::Python;
+/// use pyline_libs::traits::CodeParsers;
+///
+/// # async fn example(files: &[FileData]) -> Result<(), PyLineError> {
+/// let mut cache = AnalysisCache::<Python>::load(Path::new(".pyline-cache")).await;
+/// let mut stats = Python::new();
+/// cache.parse(&mut stats, files).await?;
+/// cache.save().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct AnalysisCache<C> {
+    /// File the cache is read from and written to.
+    file: PathBuf,
+    /// Cached results by file path.
+    entries: HashMap<PathBuf, CacheEntry<C>>,
+    /// Files analyzed since loading; the others are pruned on save.
+    seen: HashSet<PathBuf>,
+    /// Hits and misses of the last run.
+    stat: CacheStat,
+}
+
+impl<C: Cacheable> AnalysisCache<C> {
+    /// Loads the cache for the language `C` from `dir`.
+    ///
+    /// A missing, unreadable or outdated cache yields an empty one; it is
+    /// never an error.
+    pub async fn load(dir: &Path) -> Self {
+        let file = dir.join(format!("{}.json", C::NAME));
+
+        let mut entries = match fs::read(&file).await {
+            Ok(bytes) => {
+                serde_json::from_slice::<CacheFile<HashMap<PathBuf, CacheEntry<C>>>>(&bytes)
+                    .ok()
+                    .filter(|cache| {
+                        cache.format == CACHE_FORMAT
+                            && cache.version == env!("CARGO_PKG_VERSION")
+                            && cache.fingerprint == C::fingerprint()
+                    })
+                    .map(|cache| cache.entries)
+                    .unwrap_or_default()
+            }
+            Err(_) => HashMap::new(),
+        };
+        entries.values_mut().for_each(CacheEntry::attach_lines);
+
+        Self {
+            file,
+            entries,
+            seen: HashSet::new(),
+            stat: CacheStat::default(),
+        }
+    }
+
+    /// Parses `files` into `target`, re-using cached results of unchanged
+    /// files and storing the results of the parsed ones.
+    ///
+    /// A file is considered unchanged when its size and modification time
+    /// match the cache entry, or, failing that, when its content hash does.
+    /// Results are only re-used for files of the same origin, and results of
+    /// lossily decoded files only for files that are decoded lossily again.
+    /// Changed files are read once, to hash and to parse them.
+    pub async fn parse(&mut self, target: &mut C, files: &[FileData]) -> Result<(), PyLineError> {
+        if files.is_empty() {
+            return Err(PyLineError::NoFilesForParse);
+        }
+
+        let mut stale = Vec::new();
+        for file in files {
+            self.seen.insert(file.path.clone());
+            match self.lookup(file).await {
+                Ok(result) => {
+                    target.merge_ref(result);
+                    self.stat.hits += 1;
+                }
+                Err(file) => stale.push(file),
+            }
+        }
+        self.stat.misses += stale.len();
+
+        let tasks: Vec<_> = stale.into_iter().map(Stale::parse::<C>).collect();
+        let results = futures::future::join_all(tasks).await;
+
+        for (file, result) in results {
+            match result {
+                Ok((result, stamp, hash)) => {
+                    target.merge_ref(&result);
+                    self.store(file, stamp, hash, result);
+                }
+                Err(error) => target.record_failure(file.path.clone(), error),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes the cache to disk, dropping the entries of files that were not
+    /// analyzed since loading.
+    ///
+    /// The file is written next to its final location and renamed, so an
+    /// interrupted run never leaves a truncated cache behind.
+    pub async fn save(&mut self) -> Result<(), PyLineError> {
+        self.entries.retain(|path, _| self.seen.contains(path));
+
+        if let Some(dir) = self.file.parent() {
+            fs::create_dir_all(dir)
+                .await
+                .map_err(|err| PyLineError::io_error(Operation::Write, dir, err))?;
+        }
+
+        self.entries.values_mut().for_each(CacheEntry::detach_lines);
+        let cache = CacheFile {
+            format: CACHE_FORMAT,
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            fingerprint: C::fingerprint(),
+            entries: &self.entries,
+        };
+        let bytes = serde_json::to_vec(&cache);
+        self.entries.values_mut().for_each(CacheEntry::attach_lines);
+        let bytes =
+            bytes.map_err(|err| PyLineError::counter_error(format!("cache encoding: {}", err)))?;
+
+        let tmp = self.file.with_extension("json.tmp");
+        fs::write(&tmp, bytes)
+            .await
+            .map_err(|err| PyLineError::io_error(Operation::Write, &tmp, err))?;
+        fs::rename(&tmp, &self.file)
+            .await
+            .map_err(|err| PyLineError::io_error(Operation::Write, &self.file, err))?;
+
+        Ok(())
+    }
+
+    /// Returns hits and misses of the runs since loading.
+    pub fn stat(&self) -> CacheStat {
+        self.stat
+    }
+
+    /// Returns the cached result of `file` if the file is unchanged, or
+    /// the file to parse again, with its content if it was read.
+    async fn lookup<'a>(&mut self, file: &'a FileData) -> Result<&C, Stale<'a>> {
+        let mut stale = Stale {
+            file,
+            stamp: file_stamp(&file.path).await,
+            content: None,
+        };
+        let Some((size, mtime)) = stale.stamp else {
+            return Err(stale);
+        };
+        let Some(entry) = self.entries.get_mut(&file.path) else {
+            return Err(stale);
+        };
+        if entry.origin != file.origin()
+            || !file.lossy()
+                && entry
+                    .result
+                    .metrics()
+                    .files
+                    .iter()
+                    .any(|f| f.encoding.lossy)
+        {
+            return Err(stale);
+        }
+
+        if entry.size != size || entry.mtime != mtime {
+            let Ok(content) = fs::read(&file.path).await else {
+                return Err(stale);
+            };
+            if content.len() as u64 != size || xxh3_64(&content) != entry.hash {
+                stale.content = Some(content);
+                return Err(stale);
+            }
+            // Touched but not modified: refresh the stamp.
+            entry.size = size;
+            entry.mtime = mtime;
+        }
+
+        Ok(&entry.result)
+    }
+
+    /// Stores the result of a freshly parsed file.
+    fn store(&mut self, file: &FileData, stamp: Option<(u64, u64)>, hash: u64, result: C) {
+        let Some((size, mtime)) = stamp else {
+            return;
+        };
+
+        self.entries.insert(
+            file.path.clone(),
+            CacheEntry {
+                size,
+                mtime,
+                hash,
+                origin: file.origin(),
+                result,
+                lines: Vec::new(),
+            },
+        );
+    }
+}
+
+/// A file whose cached result cannot be re-used.
+struct Stale<'a> {
+    file: &'a FileData,
+    /// Size and modification time, taken before the content is read.
+    stamp: Option<(u64, u64)>,
+    /// Content of the file, if it was already read to compare hashes.
+    content: Option<Vec<u8>>,
+}
+
+/// Result of a stale file with its stamp and content hash.
+type Parsed<C> = Result<(C, Option<(u64, u64)>, u64), PyLineError>;
+
+impl<'a> Stale<'a> {
+    /// Reads the file unless its content is known, and parses it.
+    async fn parse<C: Cacheable>(self) -> (&'a FileData, Parsed<C>) {
+        let content = match self.content {
+            Some(content) => content,
+            None => match fs::read(&self.file.path).await {
+                Ok(content) => content,
+                Err(err) => {
+                    let err = PyLineError::io_error(Operation::Read, &self.file.path, err);
+                    return (self.file, Err(err));
+                }
+            },
+        };
+        let hash = xxh3_64(&content);
+        let result = C::parse_bytes(self.file, &content)
+            .await
+            .map(|result| (result, self.stamp, hash));
+        (self.file, result)
+    }
+}
+
+/// Returns the size and the modification time (nanoseconds since the UNIX
+/// epoch) of a file.
+async fn file_stamp(path: &Path) -> Option<(u64, u64)> {
+    let metadata = fs::metadata(path).await.ok()?;
+    let mtime = metadata
+        .modified()
+        .ok()?
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .ok()?
+        .as_nanos() as u64;
+
+    Some((metadata.len(), mtime))
+}
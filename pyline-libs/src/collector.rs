@@ -1,9 +1,13 @@
 //! Module for selecting code files for subsequent analysis.
 
-use crate::errors::PyLineError;
+use crate::dedupe::{DuplicateGroup, find_duplicates};
+use crate::errors::{ErrorKind, Operation, PyLineError};
+use crate::origin::{Origin, classify};
+use crate::sniff::{SkipReason, read_head, sniff_bytes};
 use crate::traits::FileDataExt;
 use crate::utils::format_file_size;
 use async_recursion::async_recursion;
+use std::collections::{BTreeMap, HashSet};
 use std::fmt::{Debug, Display, Formatter};
 use std::path::{Path, PathBuf};
 use tokio::fs;
@@ -19,12 +23,39 @@ pub struct FileData {
 
     /// File size in bytes.
     bytes: u64,
+
+    /// Where the code comes from (authored, generated, vendored).
+    origin: Origin,
+
+    /// Whether bytes that are invalid in the encoding of the file are
+    /// replaced instead of failing the file.
+    lossy: bool,
 }
 
 impl FileData {
     /// Creates a new `FileData` instance with the given path and size.
+    ///
+    /// The file is treated as [`Origin::Authored`] until told otherwise
+    /// with [`FileData::with_origin`].
     pub fn new(path: PathBuf, bytes: u64) -> Self {
-        Self { path, bytes }
+        Self {
+            path,
+            bytes,
+            origin: Origin::Authored,
+            lossy: false,
+        }
+    }
+
+    /// Sets the origin of the file.
+    pub fn with_origin(mut self, origin: Origin) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    /// Sets whether the file is decoded lossily (see [`crate::source`]).
+    pub fn with_lossy(mut self, lossy: bool) -> Self {
+        self.lossy = lossy;
+        self
     }
 
     /// Returns a detailed string representation suitable for verbose output.
@@ -35,13 +66,15 @@ impl FileData {
     /// ```bash
     ///  File: src/main.py
     ///  size: 2048 bytes (2.0 KB)
+    ///  origin: authored
     /// ```
     pub fn verbose_display(&self) -> String {
         format!(
-            "File: {}\n  size: {} bytes ({})\n",
+            "File: {}\n  size: {} bytes ({})\n  origin: {}\n",
             self.path.display(),
             self.bytes,
-            format_file_size(self.bytes).unwrap_or("n/a".to_string())
+            format_file_size(self.bytes).unwrap_or("n/a".to_string()),
+            self.origin
         )
     }
 
@@ -49,6 +82,16 @@ impl FileData {
     pub fn size(&self) -> u64 {
         self.bytes
     }
+
+    /// Returns the origin of the file.
+    pub fn origin(&self) -> Origin {
+        self.origin
+    }
+
+    /// Returns `true` if the file is decoded lossily.
+    pub fn lossy(&self) -> bool {
+        self.lossy
+    }
 }
 
 impl Display for FileData {
@@ -62,6 +105,28 @@ impl Display for FileData {
     }
 }
 
+/// A file that matched the collection filters but was left out.
+///
+/// Kept in [`CollectorResult`] so that verbose output can explain why
+/// a file is missing from the statistics.
+#[derive(Debug)]
+pub struct SkippedFile {
+    /// Full path to the skipped file.
+    pub path: PathBuf,
+
+    /// File size in bytes.
+    pub bytes: u64,
+
+    /// Why the file was skipped.
+    pub reason: SkipReason,
+}
+
+impl Display for SkippedFile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Skipped: {} ({})", self.path.display(), self.reason)
+    }
+}
+
 impl FileDataExt for Vec<FileData> {
     fn join_verbose(&self, sep: &str) -> String {
         self.iter()
@@ -78,8 +143,27 @@ impl FileDataExt for Vec<FileData> {
 /// defaults.
 #[derive(Default)]
 pub struct Collector {
-    /// Root directory path from which to start file collection.
-    path: PathBuf,
+    /// Root directory paths from which to start file collection.
+    ///
+    /// Overlapping roots are de-duplicated by [`Collector::complete`].
+    roots: Vec<PathBuf>,
+
+    /// Canonical forms of `roots`, resolved once when the roots are set.
+    canonical_roots: Vec<PathBuf>,
+
+    /// Explicit list of files to analyze. When set, directory walking is
+    /// skipped entirely.
+    files_from: Option<Vec<PathBuf>>,
+
+    /// Canonical forms of the listed files.
+    listed: HashSet<PathBuf>,
+
+    /// Maximum depth of collected files below a root (files directly in
+    /// the root have depth `1`).
+    max_depth: Option<usize>,
+
+    /// Minimum depth of collected files below a root.
+    min_depth: Option<usize>,
 
     /// List of file names that, when found, cause their parent directories
     /// to be excluded.
@@ -110,6 +194,24 @@ pub struct Collector {
     ///
     /// Default: `true`.
     skip_errors: bool,
+
+    /// Files larger than this number of bytes are skipped.
+    max_file_size: Option<u64>,
+
+    /// Files smaller than this number of bytes are skipped.
+    min_file_size: Option<u64>,
+
+    /// Whether to read the head of each file, skip binary and minified files
+    /// and look for generated-code markers.
+    ///
+    /// Default: `true`.
+    sniff_content: bool,
+
+    /// Whether identical files are collected only once.
+    dedupe: bool,
+
+    /// Whether the collected files are decoded lossily.
+    lossy: bool,
 }
 
 impl Collector {
@@ -137,11 +239,15 @@ impl Collector {
     ///
     /// By default, the `ignore_dot_dirs` is enabled (set to true),
     /// meaning all directories starting with a dot (`.`) are ignored.
+    ///
+    /// Further roots can be added with [`Collector::add_roots`].
     pub fn new(path: &Path) -> Self {
         Self {
-            path: path.to_path_buf(),
+            roots: vec![path.to_path_buf()],
+            canonical_roots: vec![canonical_path(path)],
             ignore_dot_dirs: true,
             skip_errors: true,
+            sniff_content: true,
             ..Default::default()
         }
     }
@@ -306,6 +412,129 @@ impl Collector {
         self.skip_errors = skip;
         self
     }
+
+    /// Adds more root directories to collect files from in the same run.
+    ///
+    /// Roots nested inside other roots are walked only once.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// use std::path::PathBuf;
+    /// use pyline_libs::collector::Collector;
+    ///
+    /// let path = PathBuf::from("src");
+    ///
+    /// Collector::new(&path)
+    ///     .add_roots(["tools", "scripts"])
+    ///     .complete();
+    /// ```
+    pub fn add_roots<I, P>(mut self, roots: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        for root in roots {
+            let root = root.into();
+            self.canonical_roots.push(canonical_path(&root));
+            self.roots.push(root);
+        }
+        self
+    }
+
+    /// Analyzes exactly the given files instead of walking the roots.
+    ///
+    /// The roots are not walked. Extension, file name, size and content
+    /// filters still apply, and so do the directory exclusions (excluded
+    /// names, dot directories, marker files) for the directories between
+    /// a root and each file; files outside the roots are only filtered by
+    /// name and content. Useful for piping `git diff --name-only` output
+    /// into the collector.
+    pub fn files_from<I, P>(mut self, files: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        let files: Vec<PathBuf> = files.into_iter().map(|p| p.into()).collect();
+        self.listed = files.iter().map(|file| canonical_path(file)).collect();
+        self.files_from = Some(files);
+        self
+    }
+
+    /// Limits how deep below a root files are collected.
+    ///
+    /// Files directly in a root have depth `1`, so `max_depth(1)` disables
+    /// recursion.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Collects only files at least `depth` levels below a root.
+    pub fn min_depth(mut self, depth: usize) -> Self {
+        self.min_depth = Some(depth);
+        self
+    }
+
+    /// Skips files larger than `bytes`.
+    ///
+    /// Large vendored blobs and generated sources tend to skew the numbers
+    /// and slow down scans.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// use std::path::PathBuf;
+    /// use pyline_libs::collector::Collector;
+    ///
+    /// let path = PathBuf::from("/path");
+    ///
+    /// Collector::new(&path)
+    ///     .max_file_size(1024 * 1024)
+    ///     .complete();
+    /// ```
+    pub fn max_file_size(mut self, bytes: u64) -> Self {
+        self.max_file_size = Some(bytes);
+        self
+    }
+
+    /// Skips files smaller than `bytes`.
+    pub fn min_file_size(mut self, bytes: u64) -> Self {
+        self.min_file_size = Some(bytes);
+        self
+    }
+
+    /// Controls whether file contents are sniffed before collection.
+    ///
+    /// When `true` (default), the head of each file is read: binary and
+    /// minified files are skipped (see [`crate::sniff`]) and generated-code
+    /// markers are used to classify the file (see [`crate::origin`]).
+    /// Without sniffing, files are classified by path only.
+    pub fn sniff_content(mut self, sniff: bool) -> Self {
+        self.sniff_content = sniff;
+        self
+    }
+
+    /// Controls whether identical files are collected only once.
+    ///
+    /// When `true`, hard links and bind mounts of the same file are
+    /// recognised by inode, and other files by a hash of their content.
+    /// Only the copy with the smallest path is kept; the groups are listed
+    /// in [`CollectorResult::duplicates`] (see [`crate::dedupe`]).
+    pub fn dedupe(mut self, dedupe: bool) -> Self {
+        self.dedupe = dedupe;
+        self
+    }
+
+    /// Controls whether the collected files are decoded lossily.
+    ///
+    /// When `true`, bytes that are invalid in the encoding of a file are
+    /// replaced with U+FFFD and the file is counted; otherwise the file
+    /// fails (see [`crate::source`]).
+    pub fn lossy(mut self, lossy: bool) -> Self {
+        self.lossy = lossy;
+        self
+    }
 }
 
 /// Result of a file collection operation with error tracking.
@@ -320,6 +549,12 @@ pub struct CollectorResult {
 
     /// Errors encountered during file collection.
     errors: Vec<PyLineError>,
+
+    /// Files left out by size limits or content sniffing.
+    skipped: Vec<SkippedFile>,
+
+    /// Identical files collected only once.
+    duplicates: Vec<DuplicateGroup>,
 }
 
 impl CollectorResult {
@@ -358,6 +593,46 @@ impl CollectorResult {
         self.errors.len()
     }
 
+    /// Returns the number of errors per kind (permission denied, not
+    /// found, ...).
+    pub fn error_summary(&self) -> BTreeMap<ErrorKind, usize> {
+        let mut summary = BTreeMap::new();
+        for err in &self.errors {
+            *summary.entry(err.kind()).or_insert(0) += 1;
+        }
+        summary
+    }
+
+    /// Returns a reference to the skipped files.
+    pub fn skipped(&self) -> &Vec<SkippedFile> {
+        &self.skipped
+    }
+
+    /// Returns `true` if any files were skipped.
+    pub fn has_skipped(&self) -> bool {
+        !self.skipped.is_empty()
+    }
+
+    /// Returns the number of skipped files.
+    pub fn num_skipped(&self) -> usize {
+        self.skipped.len()
+    }
+
+    /// Returns a reference to the groups of identical files.
+    pub fn duplicates(&self) -> &Vec<DuplicateGroup> {
+        &self.duplicates
+    }
+
+    /// Returns `true` if identical files were left out.
+    pub fn has_duplicates(&self) -> bool {
+        !self.duplicates.is_empty()
+    }
+
+    /// Returns the number of files left out as copies of another file.
+    pub fn num_duplicates(&self) -> usize {
+        self.duplicates.iter().map(|group| group.copies.len()).sum()
+    }
+
     /// Adds a successfully collected file to the result.
     pub fn add_file(&mut self, item: FileData) {
         self.result.push(item);
@@ -368,6 +643,11 @@ impl CollectorResult {
         self.errors.push(err);
     }
 
+    /// Adds a file left out of the collection.
+    pub fn add_skipped(&mut self, item: SkippedFile) {
+        self.skipped.push(item);
+    }
+
     /// Extends the collection with multiple successfully collected files.
     pub fn extend_results(&mut self, items: Vec<FileData>) {
         self.result.extend(items);
@@ -384,6 +664,8 @@ impl CollectorResult {
     pub fn absorb(&mut self, other: Self) {
         self.result.extend(other.result);
         self.errors.extend(other.errors);
+        self.skipped.extend(other.skipped);
+        self.duplicates.extend(other.duplicates);
     }
 }
 
@@ -444,11 +726,192 @@ impl Collector {
     /// - The operation respects all filters configured via builder methods
     /// - By default, dot-directories (starting with `.`) are excluded
     /// - File collection is recursive unless filtered by `exclude_dirs`
+    ///   or limited by `max_depth`
     /// - Symbolic links are followed according to platform behavior
     /// - The method has internal parallelism optimizations for large scans
     pub async fn complete(&self) -> Result<CollectorResult, PyLineError> {
-        // Parsing...
-        self.mapping_files(&self.path).await
+        let mut collector_result = match &self.files_from {
+            Some(files) => self.collect_listed(files).await?,
+            None => {
+                let mut collector_result = CollectorResult::new();
+                for root in self.unique_roots().await {
+                    collector_result.absorb(self.mapping_files(&root, &root, 1).await?);
+                }
+                collector_result
+            }
+        };
+
+        if self.dedupe {
+            self.remove_duplicates(&mut collector_result).await?;
+        }
+
+        Ok(collector_result)
+    }
+
+    /// Returns the configured root directories.
+    pub fn roots(&self) -> &[PathBuf] {
+        &self.roots
+    }
+
+    /// Applies all collection filters to a single file, as if it had been
+    /// found while walking the roots.
+    ///
+    /// Returns `Ok(None)` if the file is not collected: it lies outside the
+    /// roots, is filtered out, skipped, or no longer exists. Used to re-check
+    /// changed files without walking the whole tree (see [`crate::watch`]).
+    pub async fn check_file(&self, file: &Path) -> Result<Option<FileData>, PyLineError> {
+        // Listed paths and roots may be relative or go through symlinks,
+        // change notifications are absolute: compare the canonical forms.
+        let canonical = canonical_path(file);
+        let (root, inspected) = if self.files_from.is_some() {
+            if !self.listed.contains(&canonical) || self.is_in_excluded_dir(&canonical) {
+                return Ok(None);
+            }
+            (Path::new(""), file)
+        } else {
+            match self.root_of(&canonical) {
+                Some((root, relative)) if self.is_within_limits(root, relative) => {
+                    (root, canonical.as_path())
+                }
+                _ => return Ok(None),
+            }
+        };
+
+        if !self.is_valid_file(file) {
+            return Ok(None);
+        }
+
+        let Ok(metadata) = fs::metadata(file).await else {
+            return Ok(None);
+        };
+
+        match self.inspect_file(root, inspected, metadata.len()).await? {
+            Inspection::Collect(origin) => Ok(Some(
+                FileData::new(file.to_path_buf(), metadata.len())
+                    .with_origin(origin)
+                    .with_lossy(self.lossy),
+            )),
+            Inspection::Skip(_) => Ok(None),
+        }
+    }
+
+    /// Checks depth limits and directory exclusions for a path relative to
+    /// its root.
+    fn is_within_limits(&self, root: &Path, relative: &Path) -> bool {
+        let depth = relative.components().count();
+        if self.max_depth.is_some_and(|max| depth > max)
+            || self.min_depth.is_some_and(|min| depth < min)
+        {
+            return false;
+        }
+
+        !self.is_below_excluded_dir(root, relative)
+    }
+
+    /// Returns `true` if a directory between `root` and the file at
+    /// `relative` below it is excluded. The root itself is not checked.
+    fn is_below_excluded_dir(&self, root: &Path, relative: &Path) -> bool {
+        let mut dir = root.to_path_buf();
+        relative
+            .parent()
+            .into_iter()
+            .flat_map(|p| p.components())
+            .any(|component| {
+                dir.push(component);
+                self.is_dir_excluded(&dir)
+            })
+    }
+
+    /// Returns the outermost canonical root containing the canonical path
+    /// `file`, with the path relative to it.
+    fn root_of<'a>(&'a self, file: &'a Path) -> Option<(&'a Path, &'a Path)> {
+        self.canonical_roots
+            .iter()
+            .filter_map(|root| Some((root.as_path(), file.strip_prefix(root).ok()?)))
+            .min_by_key(|(root, _)| root.components().count())
+    }
+
+    /// Drops roots that repeat or lie inside another root.
+    ///
+    /// Roots are compared by their canonical form but walked by the path
+    /// given by the user, so reported paths stay as the user typed them.
+    async fn unique_roots(&self) -> Vec<PathBuf> {
+        let mut roots = Vec::with_capacity(self.roots.len());
+        for root in &self.roots {
+            let canonical = fs::canonicalize(root)
+                .await
+                .unwrap_or_else(|_| root.clone());
+            roots.push((canonical, root));
+        }
+        roots.sort();
+
+        let mut unique: Vec<(PathBuf, &PathBuf)> = Vec::with_capacity(roots.len());
+        for (canonical, root) in roots {
+            if !unique.iter().any(|(kept, _)| canonical.starts_with(kept)) {
+                unique.push((canonical, root));
+            }
+        }
+
+        unique.into_iter().map(|(_, root)| root.clone()).collect()
+    }
+
+    /// Drops the copies of identical files from the result and records them
+    /// as duplicate groups.
+    async fn remove_duplicates(
+        &self,
+        collector_result: &mut CollectorResult,
+    ) -> Result<(), PyLineError> {
+        let deduplication = find_duplicates(&collector_result.result).await;
+        for err in deduplication.errors {
+            if !self.skip_errors {
+                return Err(err);
+            }
+            collector_result.add_err(err);
+        }
+
+        let dropped: std::collections::HashSet<&PathBuf> = deduplication
+            .groups
+            .iter()
+            .flat_map(|group| group.copies.iter().map(|(path, _)| path))
+            .collect();
+        collector_result
+            .result
+            .retain(|file| !dropped.contains(&file.path));
+        collector_result.duplicates.extend(deduplication.groups);
+
+        Ok(())
+    }
+
+    /// Collects an explicit list of files without walking directories.
+    async fn collect_listed(&self, files: &[PathBuf]) -> Result<CollectorResult, PyLineError> {
+        let mut collector_result = CollectorResult::new();
+        let mut seen = std::collections::HashSet::new();
+
+        for file in files {
+            if !seen.insert(file)
+                || !self.is_valid_file(file)
+                || self.is_in_excluded_dir(&canonical_path(file))
+            {
+                continue;
+            }
+
+            let bytes = match fs::metadata(file).await {
+                Ok(metadata) => metadata.len(),
+                Err(err) => {
+                    let err = PyLineError::io_error(Operation::Metadata, file, err);
+                    if !self.skip_errors {
+                        return Err(err);
+                    }
+                    collector_result.add_err(err);
+                    continue;
+                }
+            };
+
+            self.collect_file(Path::new(""), file.clone(), bytes, &mut collector_result)
+                .await?;
+        }
+
+        Ok(collector_result)
     }
 
     /// Recursively collects files matching the configured criteria.
@@ -456,8 +919,15 @@ impl Collector {
     /// Traverses directories depth-first, applying all configured filters and exclusions.
     /// Returns a [`CollectorResult`] containing both successfully collected files
     /// and any encountered errors (depending on the `skip_errors` setting).
+    ///
+    /// `depth` is the depth of the entries of `path` below `root`.
     #[async_recursion]
-    async fn mapping_files(&self, path: &Path) -> Result<CollectorResult, PyLineError> {
+    async fn mapping_files(
+        &self,
+        root: &Path,
+        path: &Path,
+        depth: usize,
+    ) -> Result<CollectorResult, PyLineError> {
         // let mut files: Vec<FileData> = Vec::new();
         let mut collector_result = CollectorResult::new();
 
@@ -465,11 +935,12 @@ impl Collector {
         let mut dir_entries = match fs::read_dir(path).await {
             Ok(entries) => entries,
             Err(err) => {
+                let err = PyLineError::io_error(Operation::ReadDir, path, err);
                 return if self.skip_errors {
-                    collector_result.add_err(err.into());
+                    collector_result.add_err(err);
                     Ok(collector_result)
                 } else {
-                    Err(err.into())
+                    Err(err)
                 };
             }
         };
@@ -477,20 +948,28 @@ impl Collector {
         'collect: while let Some(entry_res) = match dir_entries.next_entry().await {
             Ok(entry) => entry,
             Err(err) => {
+                let err = PyLineError::io_error(Operation::ReadDir, path, err);
                 if self.skip_errors {
-                    collector_result.add_err(err.into());
+                    collector_result.add_err(err);
                     continue 'collect;
                 } else {
-                    return Err(err.into());
+                    return Err(err);
                 }
             }
         } {
             let elem = entry_res.path();
-            let metadata = entry_res.metadata().await?;
+            let metadata = entry_res
+                .metadata()
+                .await
+                .map_err(|err| PyLineError::io_error(Operation::Metadata, &elem, err))?;
 
             if self.is_valid_dir(&elem) {
+                if self.max_depth.is_some_and(|max| depth >= max) {
+                    continue;
+                }
+
                 // Subfolders
-                match self.mapping_files(&elem).await {
+                match self.mapping_files(root, &elem, depth + 1).await {
                     Ok(sub_dirs) => collector_result.absorb(sub_dirs),
                     Err(err) => {
                         if self.skip_errors {
@@ -500,19 +979,96 @@ impl Collector {
                         }
                     }
                 }
-            } else if self.is_valid_file(&elem) {
-                let file_data = FileData::new(elem, metadata.len());
-                collector_result.add_file(file_data);
+            } else if self.is_valid_file(&elem) && self.min_depth.is_none_or(|min| depth >= min) {
+                self.collect_file(root, elem, metadata.len(), &mut collector_result)
+                    .await?;
             }
         }
 
         Ok(collector_result)
     }
 
+    /// Inspects a file that passed the name filters and adds it to the result
+    /// as collected, skipped or failed.
+    async fn collect_file(
+        &self,
+        root: &Path,
+        file: PathBuf,
+        bytes: u64,
+        collector_result: &mut CollectorResult,
+    ) -> Result<(), PyLineError> {
+        match self.inspect_file(root, &file, bytes).await {
+            Ok(Inspection::Skip(reason)) => collector_result.add_skipped(SkippedFile {
+                path: file,
+                bytes,
+                reason,
+            }),
+            Ok(Inspection::Collect(origin)) => collector_result.add_file(
+                FileData::new(file, bytes)
+                    .with_origin(origin)
+                    .with_lossy(self.lossy),
+            ),
+            Err(err) => {
+                if self.skip_errors {
+                    collector_result.add_err(err);
+                } else {
+                    return Err(err);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Applies size limits and content sniffing to a matching file and
+    /// classifies its origin.
+    ///
+    /// The origin is classified by the path relative to the collection root,
+    /// so a root that itself lives under `vendor/` is not marked vendored.
+    async fn inspect_file(
+        &self,
+        root: &Path,
+        file: &Path,
+        bytes: u64,
+    ) -> Result<Inspection, PyLineError> {
+        if let Some(limit) = self.max_file_size
+            && bytes > limit
+        {
+            return Ok(Inspection::Skip(SkipReason::TooLarge { limit }));
+        }
+
+        if let Some(limit) = self.min_file_size
+            && bytes < limit
+        {
+            return Ok(Inspection::Skip(SkipReason::TooSmall { limit }));
+        }
+
+        let head = if self.sniff_content {
+            read_head(file).await?
+        } else {
+            Vec::new()
+        };
+
+        if let Some(reason) = sniff_bytes(&head) {
+            return Ok(Inspection::Skip(reason));
+        }
+
+        let relative = file.strip_prefix(root).unwrap_or(file);
+        Ok(Inspection::Collect(classify(relative, &head)))
+    }
+
     fn is_valid_dir(&self, path: &Path) -> bool {
         path.is_dir() && !self.is_dir_excluded(path)
     }
 
+    /// Returns `true` if a directory between a root and the listed file at
+    /// the canonical path `file` is excluded, so the file would have been
+    /// skipped by walking. Directories above the roots are not checked.
+    fn is_in_excluded_dir(&self, file: &Path) -> bool {
+        self.root_of(file)
+            .is_some_and(|(root, relative)| self.is_below_excluded_dir(root, relative))
+    }
+
     fn is_dir_excluded(&self, path: &Path) -> bool {
         let dir_name = match path.file_name().and_then(|s| s.to_str()) {
             Some(name) => name,
@@ -583,6 +1139,37 @@ impl Collector {
     }
 }
 
+/// Returns the canonical form of a path that may no longer exist: a
+/// deleted file is resolved through its parent directory.
+fn canonical_path(path: &Path) -> PathBuf {
+    if let Ok(canonical) = std::fs::canonicalize(path) {
+        return canonical;
+    }
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) => {
+            let parent = if parent.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                parent
+            };
+            match std::fs::canonicalize(parent) {
+                Ok(parent) => parent.join(name),
+                Err(_) => path.to_path_buf(),
+            }
+        }
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Outcome of [`Collector::inspect_file`].
+enum Inspection {
+    /// The file is collected with the given origin.
+    Collect(Origin),
+
+    /// The file is left out for the given reason.
+    Skip(SkipReason),
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,201 @@
+//! Detection of duplicate files among the collected ones.
+//!
+//! Files are first grouped by device and inode, which catches hard links and
+//! bind mounts without reading them. The remaining files of equal size are
+//! then compared by a hash of their whole content, and files with equal
+//! hashes by their size and bytes. Each group keeps the file with the
+//! smallest path; the others are dropped from the collection.
+
+use crate::collector::FileData;
+use crate::errors::{Operation, PyLineError};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use tokio::fs;
+use xxhash_rust::xxh3::xxh3_64;
+
+/// How a duplicate relates to the kept file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateKind {
+    /// Same device and inode: a hard link or the same file seen through
+    /// a bind mount.
+    SameFile,
+
+    /// Different file with identical content.
+    SameContent,
+}
+
+impl Display for DuplicateKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::SameFile => write!(f, "same file"),
+            Self::SameContent => write!(f, "same content"),
+        }
+    }
+}
+
+/// A file found more than once, counted only once.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicateGroup {
+    /// File size in bytes.
+    pub bytes: u64,
+
+    /// Path of the copy that stays in the collection.
+    pub kept: PathBuf,
+
+    /// Paths of the dropped copies.
+    pub copies: Vec<(PathBuf, DuplicateKind)>,
+}
+
+impl Display for DuplicateGroup {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Duplicate: {} ({} bytes, {} copies)",
+            self.kept.display(),
+            self.bytes,
+            self.copies.len()
+        )?;
+        for (path, kind) in &self.copies {
+            write!(f, "\n    {} ({})", path.display(), kind)?;
+        }
+        Ok(())
+    }
+}
+
+/// Result of [`find_duplicates`].
+#[derive(Debug, Default)]
+pub(crate) struct Deduplication {
+    /// Groups of identical files, by path of the kept file.
+    pub groups: Vec<DuplicateGroup>,
+
+    /// Files whose metadata or content could not be read; they are kept.
+    pub errors: Vec<PyLineError>,
+}
+
+/// Groups identical files.
+pub(crate) async fn find_duplicates(files: &[FileData]) -> Deduplication {
+    let mut result = Deduplication::default();
+    let mut paths: Vec<&FileData> = files.iter().collect();
+    paths.sort_by(|a, b| a.path.cmp(&b.path));
+
+    // Kept file of every group, with its dropped copies.
+    let mut groups: Vec<DuplicateGroup> = Vec::new();
+
+    let mut by_inode: HashMap<(u64, u64), usize> = HashMap::new();
+    let mut by_size: HashMap<u64, Vec<usize>> = HashMap::new();
+    for file in paths {
+        match file_id(&file.path).await {
+            Ok(Some(id)) => {
+                if let Some(&group) = by_inode.get(&id) {
+                    groups[group]
+                        .copies
+                        .push((file.path.clone(), DuplicateKind::SameFile));
+                    continue;
+                }
+                by_inode.insert(id, groups.len());
+            }
+            Ok(None) => {}
+            Err(err) => {
+                result
+                    .errors
+                    .push(PyLineError::io_error(Operation::Metadata, &file.path, err));
+                continue;
+            }
+        }
+        by_size.entry(file.size()).or_default().push(groups.len());
+        groups.push(DuplicateGroup {
+            bytes: file.size(),
+            kept: file.path.clone(),
+            copies: Vec::new(),
+        });
+    }
+
+    let mut merged: Vec<Option<DuplicateGroup>> = groups.into_iter().map(Some).collect();
+    let mut candidates: Vec<Vec<usize>> = by_size
+        .into_values()
+        .filter(|same_size| same_size.len() > 1)
+        .collect();
+    candidates.sort_unstable();
+
+    for same_size in candidates {
+        // First file of every distinct content, by hash.
+        let mut by_hash: HashMap<u64, Vec<usize>> = HashMap::new();
+        for idx in same_size {
+            let Some(path) = merged[idx].as_ref().map(|group| group.kept.clone()) else {
+                continue;
+            };
+            let content = match fs::read(&path).await {
+                Ok(content) => content,
+                Err(err) => {
+                    result
+                        .errors
+                        .push(PyLineError::io_error(Operation::Read, &path, err));
+                    continue;
+                }
+            };
+            let firsts = by_hash.entry(xxh3_64(&content)).or_default();
+            let mut same = None;
+            for &first in firsts.iter() {
+                let Some(kept) = merged[first].as_ref() else {
+                    continue;
+                };
+                if has_content(&kept.kept, &content).await {
+                    same = Some(first);
+                    break;
+                }
+            }
+            let Some(first) = same else {
+                firsts.push(idx);
+                continue;
+            };
+            let Some(dropped) = merged[idx].take() else {
+                continue;
+            };
+            if let Some(kept) = merged[first].as_mut() {
+                kept.copies.push((dropped.kept, DuplicateKind::SameContent));
+                kept.copies.extend(dropped.copies);
+            }
+        }
+    }
+
+    result.groups = merged
+        .into_iter()
+        .flatten()
+        .filter(|group| !group.copies.is_empty())
+        .map(|mut group| {
+            group.copies.sort_by(|a, b| a.0.cmp(&b.0));
+            group
+        })
+        .collect();
+
+    result
+}
+
+/// Returns `true` if the file at `path` holds exactly `content`. A hash
+/// match alone is not proof: the file is read again and compared byte by
+/// byte. A file that cannot be read is treated as different.
+async fn has_content(path: &Path, content: &[u8]) -> bool {
+    match fs::metadata(path).await {
+        Ok(metadata) if metadata.len() == content.len() as u64 => {}
+        _ => return false,
+    }
+    fs::read(path).await.is_ok_and(|kept| kept == content)
+}
+
+/// Returns the device and inode of a file, where the platform has them.
+async fn file_id(path: &Path) -> std::io::Result<Option<(u64, u64)>> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+
+        let metadata = fs::metadata(path).await?;
+        Ok(Some((metadata.dev(), metadata.ino())))
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = path;
+        Ok(None)
+    }
+}
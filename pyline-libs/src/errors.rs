@@ -1,22 +1,34 @@
 //! Custom error types module.
 
+use crate::source::DecodeError;
+use serde::Serialize;
 use std::error::Error;
 use std::fmt::{Display, Formatter};
 use std::io::Error as IoError;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 /// Error types for the PyLine code analysis tool.
 ///
 /// Categorizes different failure scenarios that can occur during
-/// file scanning, parsing, and analysis operations.
-#[derive(Debug)]
+/// file scanning, parsing, and analysis operations. Use
+/// [`PyLineError::kind`] to match on the category without inspecting
+/// messages.
+///
+/// `Display` describes the failed operation only; the underlying I/O or
+/// decoding error is returned by [`Error::source`].
+#[derive(Debug, Clone)]
 pub enum PyLineError {
     /// I/O error that occurred during file operations (reading,
     /// writing, etc.).
     IOError {
-        /// The underlying I/O error from the standard library.
-        error: IoError,
-        /// Human-readable context about what operation failed.
-        description: String,
+        /// The underlying I/O error from the standard library, shared so
+        /// that the error can be cloned with its source chain.
+        error: Arc<IoError>,
+        /// Operation that failed, if known.
+        operation: Option<Operation>,
+        /// Path the operation failed on, if known.
+        path: Option<PathBuf>,
     },
 
     /// Error building the file tree for parsing.
@@ -34,29 +46,147 @@ pub enum PyLineError {
         description: String,
     },
 
+    /// A file could not be decoded or parsed.
+    ParseError {
+        /// Path of the file.
+        path: PathBuf,
+        /// Line where decoding or parsing failed, if known.
+        line: Option<usize>,
+        /// Operation that failed: [`Operation::Decode`] or
+        /// [`Operation::Parse`].
+        operation: Operation,
+        /// Description of the failure.
+        description: String,
+        /// The decoding error, for files that are not valid in their
+        /// encoding.
+        source: Option<DecodeError>,
+    },
+
     /// No files available for code parsing.
     NoFilesForParse,
+
+    /// The analyzed code failed a quality gate (e.g. `--max-complexity`).
+    GateError {
+        /// Description of the failed gates.
+        description: String,
+    },
+}
+
+/// Operation on a path that failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operation {
+    /// Listing a directory.
+    ReadDir,
+    /// Reading the metadata of a file or directory.
+    Metadata,
+    /// Opening a file.
+    Open,
+    /// Reading a file.
+    Read,
+    /// Writing a file.
+    Write,
+    /// Decoding the content of a file.
+    Decode,
+    /// Parsing the decoded content of a file.
+    Parse,
+}
+
+impl Display for Operation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::ReadDir => "read_dir",
+            Self::Metadata => "metadata",
+            Self::Open => "open",
+            Self::Read => "read",
+            Self::Write => "write",
+            Self::Decode => "decode",
+            Self::Parse => "parse",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Category of a [`PyLineError`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    /// A file or directory does not exist.
+    NotFound,
+    /// Access to a file or directory was denied.
+    PermissionDenied,
+    /// Any other I/O error.
+    Io,
+    /// A file is not valid in its encoding.
+    Decode,
+    /// A file could not be parsed.
+    Parse,
+    /// The file tree could not be built.
+    Scanner,
+    /// The statistics could not be counted or encoded.
+    Counter,
+    /// There were no files to parse.
+    NoFiles,
+    /// A quality gate failed.
+    Gate,
+}
+
+impl Display for ErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::NotFound => "not found",
+            Self::PermissionDenied => "permission denied",
+            Self::Io => "I/O error",
+            Self::Decode => "decoding error",
+            Self::Parse => "parse error",
+            Self::Scanner => "scanner error",
+            Self::Counter => "counter error",
+            Self::NoFiles => "no files",
+            Self::Gate => "quality gate",
+        };
+        write!(f, "{}", name)
+    }
 }
 
 impl From<IoError> for PyLineError {
-    /// Converts an [`IoError`] into a [`PyLineError::IOError`] variant,
-    /// preserving the original error and its string description.
+    /// Converts an [`IoError`] into a [`PyLineError::IOError`] variant
+    /// without path context (see [`PyLineError::io_error`]).
     fn from(error: IoError) -> Self {
-        let err_msg = error.to_string();
         PyLineError::IOError {
-            error,
-            description: err_msg,
+            error: Arc::new(error),
+            operation: None,
+            path: None,
         }
     }
 }
 
-impl Error for PyLineError {}
+impl Error for PyLineError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::IOError { error, .. } => Some(error.as_ref()),
+            Self::ParseError {
+                source: Some(source),
+                ..
+            } => Some(source),
+            _ => None,
+        }
+    }
+}
 
 impl Display for PyLineError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::IOError { error, description } => {
-                write!(f, "IO error: {}\n{}", error, description)
+            Self::IOError {
+                operation, path, ..
+            } => {
+                write!(f, "IO error")?;
+                match (operation, path) {
+                    (Some(operation), Some(path)) => {
+                        write!(f, ": {} {}", operation, path.display())
+                    }
+                    (Some(operation), None) => write!(f, ": {}", operation),
+                    (None, Some(path)) => write!(f, ": {}", path.display()),
+                    (None, None) => Ok(()),
+                }
             }
             Self::ScannerError { description } => {
                 write!(f, "ScannerError: {}", description)
@@ -64,14 +194,58 @@ impl Display for PyLineError {
             Self::CounterError { description } => {
                 write!(f, "CounterError: {}", description)
             }
+            Self::ParseError {
+                path,
+                line,
+                operation,
+                description,
+                source,
+            } => {
+                write!(f, "ParseError: {} {}", operation, path.display())?;
+                if let Some(line) = line {
+                    write!(f, ":{}", line)?;
+                }
+                match source {
+                    // The decoding error is the source.
+                    Some(_) => Ok(()),
+                    None => write!(f, ": {}", description),
+                }
+            }
             Self::NoFilesForParse => {
                 write!(f, "No files available for code parsing.")
             }
+            Self::GateError { description } => {
+                write!(f, "GateError: {}", description)
+            }
         }
     }
 }
 
 impl PyLineError {
+    /// Creates a new I/O error for an operation that failed on a path.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use pyline_libs::errors::{ErrorKind, Operation, PyLineError};
+    /// use std::io;
+    ///
+    /// let error = PyLineError::io_error(
+    ///     Operation::ReadDir,
+    ///     "src/secret",
+    ///     io::Error::from(io::ErrorKind::PermissionDenied),
+    /// );
+    /// assert_eq!(error.kind(), ErrorKind::PermissionDenied);
+    /// assert_eq!(error.operation(), Some(Operation::ReadDir));
+    /// ```
+    pub fn io_error(operation: Operation, path: impl AsRef<Path>, error: IoError) -> PyLineError {
+        Self::IOError {
+            error: Arc::new(error),
+            operation: Some(operation),
+            path: Some(path.as_ref().to_path_buf()),
+        }
+    }
+
     /// Creates a new scanner error (File tree analysis error) with the
     /// given description.
     ///
@@ -103,4 +277,134 @@ impl PyLineError {
             description: description.into(),
         }
     }
+
+    /// Creates a new parse error (file that could not be parsed) at the
+    /// given line, if known.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use pyline_libs::errors::PyLineError;
+    ///
+    /// let error = PyLineError::parse_error("src/main.py", Some(3), "unexpected indent");
+    /// assert_eq!(error.line(), Some(3));
+    /// ```
+    pub fn parse_error(
+        path: impl AsRef<Path>,
+        line: Option<usize>,
+        description: impl Into<String>,
+    ) -> PyLineError {
+        Self::ParseError {
+            path: path.as_ref().to_path_buf(),
+            line,
+            operation: Operation::Parse,
+            description: description.into(),
+            source: None,
+        }
+    }
+
+    /// Creates a new parse error for a file that is not valid in its
+    /// encoding. `bytes` is the data that failed to decode, used to find
+    /// the line of the first invalid byte.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use pyline_libs::errors::{ErrorKind, PyLineError};
+    /// use pyline_libs::source::SourceText;
+    ///
+    /// let bytes = b"x = 1\ny = '\xd0\x9f\xff'\n";
+    /// let err = SourceText::decode(bytes, false).unwrap_err();
+    /// let error = PyLineError::decode_error("src/main.py", bytes, err);
+    /// assert_eq!(error.kind(), ErrorKind::Decode);
+    /// assert_eq!(error.line(), Some(2));
+    /// ```
+    pub fn decode_error(path: impl AsRef<Path>, bytes: &[u8], error: DecodeError) -> PyLineError {
+        Self::ParseError {
+            path: path.as_ref().to_path_buf(),
+            line: error.line(bytes),
+            operation: Operation::Decode,
+            description: error.to_string(),
+            source: Some(error),
+        }
+    }
+
+    /// Creates a new gate error (failed quality gate) with the given
+    /// description.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use pyline_libs::errors::PyLineError;
+    ///
+    /// let error = PyLineError::gate_error("2 functions exceed complexity 10");
+    /// ```
+    pub fn gate_error(description: impl Into<String>) -> PyLineError {
+        Self::GateError {
+            description: description.into(),
+        }
+    }
+
+    /// Returns the category of the error.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::IOError { error, .. } => match error.kind() {
+                std::io::ErrorKind::NotFound => ErrorKind::NotFound,
+                std::io::ErrorKind::PermissionDenied => ErrorKind::PermissionDenied,
+                _ => ErrorKind::Io,
+            },
+            Self::ScannerError { .. } => ErrorKind::Scanner,
+            Self::CounterError { .. } => ErrorKind::Counter,
+            Self::ParseError {
+                operation: Operation::Decode,
+                ..
+            } => ErrorKind::Decode,
+            Self::ParseError { .. } => ErrorKind::Parse,
+            Self::NoFilesForParse => ErrorKind::NoFiles,
+            Self::GateError { .. } => ErrorKind::Gate,
+        }
+    }
+
+    /// Returns the path the error occurred on, if known.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Self::IOError { path, .. } => path.as_deref(),
+            Self::ParseError { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Returns the operation that failed, if known.
+    pub fn operation(&self) -> Option<Operation> {
+        match self {
+            Self::IOError { operation, .. } => *operation,
+            Self::ParseError { operation, .. } => Some(*operation),
+            _ => None,
+        }
+    }
+
+    /// Returns the line where decoding or parsing failed, if known.
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            Self::ParseError { line, .. } => *line,
+            _ => None,
+        }
+    }
+
+    /// Returns the description of the error, without its path and line.
+    pub fn message(&self) -> String {
+        match self {
+            Self::IOError {
+                error,
+                operation: Some(operation),
+                ..
+            } => format!("{} failed: {}", operation, error),
+            Self::IOError { error, .. } => error.to_string(),
+            Self::ScannerError { description }
+            | Self::CounterError { description }
+            | Self::ParseError { description, .. }
+            | Self::GateError { description } => description.clone(),
+            Self::NoFilesForParse => self.to_string(),
+        }
+    }
 }
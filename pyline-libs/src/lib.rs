@@ -7,12 +7,19 @@
 //!
 //! Custom error types defined in `errors.rs`.
 #![warn(missing_docs)]
+pub mod cache;
 pub mod collector;
+pub mod dedupe;
 pub mod errors;
 #[macro_use]
 pub mod parser;
 pub mod macros;
+pub mod metrics;
+pub mod origin;
 pub mod py;
 pub mod rust;
+pub mod sniff;
+pub mod source;
 pub mod traits;
 pub mod utils;
+pub mod watch;
@@ -82,12 +82,18 @@ macro_rules! display_for_lang {
 macro_rules! define_lang_struct {
     ($name:ident) => {
         /// Structure for parsing Python files.
-        #[derive(Debug, Default, Clone)]
+        #[derive(Debug, Default, Clone, serde::Serialize, serde::Deserialize)]
         pub struct $name {
             /// File statistics (lines, files, code lines).
             pub stats: CodeFilesStat,
             /// Keyword frequency counts.
             pub keywords: std::collections::HashMap<String, usize>,
+            /// Symbols and other per-file metrics.
+            #[serde(default)]
+            pub metrics: $crate::metrics::CodeMetrics,
+            /// Files that failed to parse, with their errors.
+            #[serde(skip)]
+            pub failures: Vec<(std::path::PathBuf, $crate::errors::PyLineError)>,
         }
 
         display_for_lang!($name);
@@ -112,6 +118,9 @@ macro_rules! define_lang_struct {
 /// - Have the following fields:
 ///   - `stats: CodeStats` - for statistical tracking
 ///   - `keywords: HashMap<LangKeyword, usize>` - for keyword frequency counting
+///   - `metrics: CodeMetrics` - for symbols and other per-file metrics
+///   - `failures: Vec<(PathBuf, PyLineError)>` - for the files that failed
+///     to parse
 ///
 /// # Required Manual Implementations
 /// After using this macro, you MUST implement these methods on `$Lang`:
@@ -121,8 +130,9 @@ macro_rules! define_lang_struct {
 ///     /// Core parsing logic that processes individual lines of code.
 ///     /// This is where language-specific syntax analysis happens.
 ///     async fn parse_code_lines(
-///         reader: tokio::io::BufReader<tokio::fs::File>,
+///         source: &pyline_libs::source::SourceText,
 ///         stats: &mut Self,
+///         file: &FileData,
 ///     ) -> Result<(), PyLineError> { /* ... */ }
 ///
 ///     /// Determines if a line should be counted as code (not comment/empty).
@@ -143,27 +153,30 @@ macro_rules! define_lang_struct {
 ///    - `parse()` - Asynchronously processes multiple files
 ///    - Counting methods for files and lines
 ///
-/// 2. A private `parse_file()` method that:
-///    - Opens and reads files asynchronously
-///    - Delegates line-by-line parsing to `parse_code_lines()`
-///    - Handles file I/O errors gracefully
+/// 2. Private `parse_file()` and `parse_content()` methods that:
+///    - Read files asynchronously and decode them (see [`crate::source`])
+///    - Delegate line-by-line parsing to `parse_code_lines()`
+///    - Handle file I/O errors gracefully
 ///
 /// # Example Usage
 /// ```no_run
 /// use std::collections::HashMap;
+/// use std::path::PathBuf;
 /// use pyline_libs::collector::FileData;
 /// use pyline_libs::traits::CodeParsers;
 /// use pyline_libs::parser::CodeFilesStat;
 /// use pyline_libs::errors::PyLineError;
 /// use pyline_libs::impl_lang_parser;
-/// use tokio::fs::File;
-/// use tokio::io::BufReader;
+/// use pyline_libs::metrics::CodeMetrics;
+/// use pyline_libs::source::SourceText;
 ///
 ///
 /// #[derive(Default, Clone)]
 /// struct PythonParser {
 ///     stats: CodeFilesStat,
 ///     keywords: HashMap<String, usize>,
+///     metrics: CodeMetrics,
+///     failures: Vec<(PathBuf, PyLineError)>,
 /// }
 ///
 /// // Generate the boilerplate implementation
@@ -171,7 +184,11 @@ macro_rules! define_lang_struct {
 ///
 /// // Then implement the language-specific methods
 /// impl PythonParser {
-///     async fn parse_code_lines(reader: BufReader<File>, stats: &mut Self) -> Result<(), PyLineError> {
+///     async fn parse_code_lines(
+///         source: &SourceText,
+///         stats: &mut Self,
+///         file: &FileData,
+///     ) -> Result<(), PyLineError> {
 ///         // Python-specific line parsing
 ///         Ok(())
 ///     }
@@ -190,8 +207,10 @@ macro_rules! define_lang_struct {
 /// # Error Handling
 /// The generated code handles:
 /// - File not found errors (returns `PyLineError`)
-/// - Invalid file errors (counts them in statistics)
+/// - Invalid file errors (counts them in statistics and keeps the error
+///   with the path of the file, see `failures()`)
 /// - I/O errors during file reading
+/// - Files that are not valid in their encoding (unless decoded lossily)
 ///
 /// # Performance Characteristics
 /// - Uses asynchronous I/O for parallel file processing
@@ -201,7 +220,7 @@ macro_rules! define_lang_struct {
 /// # Dependencies
 /// Requires the following in scope:
 /// - `futures::future::join_all` for parallel processing
-/// - `tokio::fs::File` and `tokio::io::BufReader` for async I/O
+/// - `tokio::fs` for async I/O
 /// - `$crate::errors::PyLineError` for error types
 /// - `CodeParsers` trait definition
 ///
@@ -228,6 +247,8 @@ macro_rules! impl_lang_parser {
                 for (keyword, count) in other.keywords {
                     *self.keywords.entry(keyword).or_insert(0) += count;
                 }
+                self.metrics.merge(other.metrics);
+                self.failures.extend(other.failures);
             }
 
             fn merge_ref(&mut self, other: &Self) {
@@ -235,6 +256,8 @@ macro_rules! impl_lang_parser {
                 for (keyword, count) in &other.keywords {
                     *self.keywords.entry(keyword.clone()).or_insert(0) += count;
                 }
+                self.metrics.merge_ref(&other.metrics);
+                self.failures.extend(other.failures.iter().cloned());
             }
 
             async fn parse(
@@ -245,19 +268,59 @@ macro_rules! impl_lang_parser {
                     return Err($crate::errors::PyLineError::NoFilesForParse);
                 }
 
-                let tasks: Vec<_> = files.iter().map(Self::parse_file).collect();
+                let tasks: Vec<_> = files.iter().map(Self::parse_one).collect();
                 let results = futures::future::join_all(tasks).await;
 
-                for result in results {
+                for (file, result) in files.iter().zip(results) {
                     match result {
                         Ok(result) => self.merge(result),
-                        Err(_) => self.count_invalid_file(),
+                        Err(error) => self.record_failure(file.path.clone(), error),
                     }
                 }
 
                 Ok(())
             }
 
+            async fn parse_one(file: &FileData) -> Result<Self, $crate::errors::PyLineError> {
+                Self::parse_file(file).await
+            }
+
+            async fn parse_bytes(
+                file: &FileData,
+                bytes: &[u8],
+            ) -> Result<Self, $crate::errors::PyLineError> {
+                Self::parse_content(file, bytes).await
+            }
+
+            fn stats(&self) -> &$crate::parser::CodeFilesStat {
+                &self.stats
+            }
+
+            fn keywords(&self) -> &std::collections::HashMap<String, usize> {
+                &self.keywords
+            }
+
+            fn metrics(&self) -> &$crate::metrics::CodeMetrics {
+                &self.metrics
+            }
+
+            fn metrics_mut(&mut self) -> &mut $crate::metrics::CodeMetrics {
+                &mut self.metrics
+            }
+
+            fn failures(&self) -> &[(std::path::PathBuf, $crate::errors::PyLineError)] {
+                &self.failures
+            }
+
+            fn record_failure(
+                &mut self,
+                path: std::path::PathBuf,
+                error: $crate::errors::PyLineError,
+            ) {
+                self.count_invalid_file();
+                self.failures.push((path, error));
+            }
+
             fn count_file(&mut self) {
                 self.stats.num_files_total += 1;
             }
@@ -273,20 +336,45 @@ macro_rules! impl_lang_parser {
             fn count_code_line(&mut self) {
                 self.stats.code_lines += 1;
             }
+
+            fn count_comment_line(&mut self) {
+                self.stats.comment_lines += 1;
+            }
+
+            fn count_doc_line(&mut self) {
+                self.stats.doc_lines += 1;
+            }
         }
 
         impl $Lang {
             /// Asynchronously parses a single Python file and extracts code
             /// statistics.
             ///
-            /// Opens the file, reads it line by line, and analyzes Python code
-            /// patterns.
+            /// Reads the file and passes its content to `parse_content()`.
             async fn parse_file(file: &FileData) -> Result<Self, $crate::errors::PyLineError> {
+                let bytes = tokio::fs::read(&file.path).await.map_err(|err| {
+                    $crate::errors::PyLineError::io_error(
+                        $crate::errors::Operation::Read,
+                        &file.path,
+                        err,
+                    )
+                })?;
+                Self::parse_content(file, &bytes).await
+            }
+
+            /// Decodes the content of a file and analyzes it line by line.
+            async fn parse_content(
+                file: &FileData,
+                bytes: &[u8],
+            ) -> Result<Self, $crate::errors::PyLineError> {
                 let mut code_stats = Self::new_one();
 
-                let code_file = tokio::fs::File::open(&file.path).await?;
-                let cursor = tokio::io::BufReader::new(code_file);
-                Self::parse_code_lines(cursor, &mut code_stats).await?;
+                let source =
+                    $crate::source::SourceText::decode(bytes, file.lossy()).map_err(|err| {
+                        $crate::errors::PyLineError::decode_error(&file.path, bytes, err)
+                    })?;
+                Self::parse_code_lines(&source, &mut code_stats, file).await?;
+                code_stats.stats.record_origin(file.origin());
 
                 Ok(code_stats)
             }
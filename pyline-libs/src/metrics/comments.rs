@@ -0,0 +1,234 @@
+//! Comment density and documentation ratios.
+//!
+//! A comment line is a line with a regular comment, trailing or not; a doc
+//! line is a line of a doc comment (`///`, `//!`, `/** */`) in Rust or of
+//! a triple-quoted docstring in Python: the first statement of a module,
+//! class or function body. Public items are the `pub` items of
+//! Rust (without modules, documented by their inner `//!` comments) and the
+//! functions, methods and classes of Python whose name does not start with
+//! `_`; they are documented by a doc comment before the item, or by
+//! a docstring as the first statement of the body.
+
+use crate::metrics::FileMetrics;
+use crate::metrics::symbols::{Symbol, SymbolKind, Visibility};
+use crate::parser::CodeFilesStat;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::ops::AddAssign;
+use std::path::Path;
+
+/// Comment and documentation counts of a file or a group of files.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Documentation {
+    /// Code lines.
+    pub code_lines: usize,
+    /// Lines with a regular comment.
+    pub comment_lines: usize,
+    /// Lines of doc comments or docstrings.
+    pub doc_lines: usize,
+    /// Public items.
+    pub public_items: usize,
+    /// Public items with a doc comment or docstring.
+    pub documented_items: usize,
+}
+
+impl Documentation {
+    /// Returns `true` if nothing was counted.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Comment lines per code line.
+    pub fn comment_ratio(&self) -> f64 {
+        ratio(self.comment_lines, self.code_lines)
+    }
+
+    /// Doc lines per code line.
+    pub fn doc_ratio(&self) -> f64 {
+        ratio(self.doc_lines, self.code_lines)
+    }
+
+    /// Percentage of public items with documentation; `None` without
+    /// public items.
+    pub fn doc_coverage(&self) -> Option<f64> {
+        doc_coverage(self.documented_items, self.public_items)
+    }
+}
+
+impl AddAssign<&Documentation> for Documentation {
+    fn add_assign(&mut self, other: &Documentation) {
+        self.code_lines += other.code_lines;
+        self.comment_lines += other.comment_lines;
+        self.doc_lines += other.doc_lines;
+        self.public_items += other.public_items;
+        self.documented_items += other.documented_items;
+    }
+}
+
+impl From<&CodeFilesStat> for Documentation {
+    fn from(stats: &CodeFilesStat) -> Self {
+        Self {
+            code_lines: stats.code_lines,
+            comment_lines: stats.comment_lines,
+            doc_lines: stats.doc_lines,
+            public_items: stats.public_items,
+            documented_items: stats.documented_items,
+        }
+    }
+}
+
+/// `part` per unit of `total`; 0 without code.
+pub fn ratio(part: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f64 / total as f64
+    }
+}
+
+/// `documented` of `public` items in percent; `None` without public items.
+pub fn doc_coverage(documented: usize, public: usize) -> Option<f64> {
+    (public > 0).then(|| documented as f64 * 100.0 / public as f64)
+}
+
+/// Formats a documented share, `n/a` without public items.
+fn format_coverage(coverage: Option<f64>) -> String {
+    match coverage {
+        Some(coverage) => format!("{:.1}%", coverage),
+        None => "n/a".to_string(),
+    }
+}
+
+/// Returns `true` if the symbol is a public item, as defined in the module
+/// documentation.
+pub(crate) fn is_public_item(symbol: &Symbol) -> bool {
+    match symbol.visibility {
+        Some(visibility) => {
+            visibility == Visibility::Public
+                && !matches!(symbol.kind, SymbolKind::NestedFunction | SymbolKind::Module)
+        }
+        None => {
+            matches!(
+                symbol.kind,
+                SymbolKind::Function | SymbolKind::Method | SymbolKind::Class
+            ) && !symbol.name.starts_with('_')
+        }
+    }
+}
+
+/// Counts the public items of `symbols` and the documented ones.
+pub(crate) fn count_public_items(symbols: &[Symbol]) -> (usize, usize) {
+    symbols
+        .iter()
+        .filter(|s| is_public_item(s))
+        .fold((0, 0), |(public, documented), s| {
+            (public + 1, documented + usize::from(s.documented))
+        })
+}
+
+/// Comment and documentation ratios overall, per directory and per file.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CommentReport {
+    /// Counts over all files.
+    pub total: Documentation,
+    /// Counts per directory.
+    pub directories: BTreeMap<String, DirectoryDocumentation>,
+    /// Counts per file.
+    pub files: BTreeMap<String, Documentation>,
+    /// Files with code and the lowest comment-to-code ratio, lowest first.
+    pub least_commented: Vec<(String, f64)>,
+}
+
+/// Counts of a directory with the share of its documented public items.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct DirectoryDocumentation {
+    /// Comment and documentation counts.
+    #[serde(flatten)]
+    pub counts: Documentation,
+    /// Percentage of public items with documentation; `None` without
+    /// public items.
+    pub documented_percent: Option<f64>,
+}
+
+impl CommentReport {
+    /// Builds the report, keeping the `top` least commented files.
+    pub fn new(files: &[FileMetrics], top: usize) -> Self {
+        let mut report = Self::default();
+        let mut directories: BTreeMap<String, Documentation> = BTreeMap::new();
+
+        for file in files.iter().filter(|f| !f.documentation.is_empty()) {
+            let dir = file.path.parent().unwrap_or(Path::new(""));
+            report.total += &file.documentation;
+            *directories.entry(dir.display().to_string()).or_default() += &file.documentation;
+            report
+                .files
+                .insert(file.path.display().to_string(), file.documentation);
+        }
+        report.directories = directories
+            .into_iter()
+            .map(|(dir, counts)| {
+                let documented_percent = counts.doc_coverage();
+                (
+                    dir,
+                    DirectoryDocumentation {
+                        counts,
+                        documented_percent,
+                    },
+                )
+            })
+            .collect();
+
+        let mut least: Vec<(String, f64)> = report
+            .files
+            .iter()
+            .filter(|(_, docs)| docs.code_lines > 0)
+            .map(|(path, docs)| (path.clone(), docs.comment_ratio()))
+            .collect();
+        least.sort_by(|a, b| a.1.total_cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+        least.truncate(top);
+        report.least_commented = least;
+
+        report
+    }
+}
+
+impl Display for CommentReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let total = &self.total;
+        write!(
+            f,
+            "Comments: {} comment and {} doc lines for {} code lines\n  comment/code {:.2}, doc/code {:.2}, documented public items {}/{} ({})",
+            total.comment_lines,
+            total.doc_lines,
+            total.code_lines,
+            total.comment_ratio(),
+            total.doc_ratio(),
+            total.documented_items,
+            total.public_items,
+            format_coverage(total.doc_coverage())
+        )?;
+
+        if !self.directories.is_empty() {
+            write!(f, "\nPer directory (comment/code, doc/code, documented):")?;
+            for (dir, docs) in &self.directories {
+                write!(
+                    f,
+                    "\n  {:>5.2}  {:>5.2}  {:>6}  {}",
+                    docs.counts.comment_ratio(),
+                    docs.counts.doc_ratio(),
+                    format_coverage(docs.documented_percent),
+                    dir
+                )?;
+            }
+        }
+        if !self.least_commented.is_empty() {
+            write!(f, "\nLeast commented files:")?;
+            for (path, ratio) in &self.least_commented {
+                write!(f, "\n  {:>5.2}  {}", ratio, path)?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,118 @@
+//! Cyclomatic complexity of functions.
+//!
+//! The complexity of a function is 1 plus the number of its decision
+//! points, counted by the language engines and stored in
+//! [`Symbol::complexity`].
+
+use crate::metrics::symbols::Symbol;
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+
+/// Upper bounds of the histogram buckets (the usual McCabe risk ranges).
+pub const COMPLEXITY_BUCKETS: &[(usize, &str)] = &[
+    (5, "1-5"),
+    (10, "6-10"),
+    (20, "11-20"),
+    (50, "21-50"),
+    (usize::MAX, "51+"),
+];
+
+/// Number of functions in a complexity range.
+#[derive(Debug, Clone, Serialize)]
+pub struct ComplexityBucket {
+    /// Range label, e.g. `6-10`.
+    pub range: &'static str,
+    /// Number of functions in the range.
+    pub functions: usize,
+}
+
+/// Summary of function complexity.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ComplexityReport {
+    /// Number of functions.
+    pub functions: usize,
+    /// Average complexity.
+    pub avg_complexity: f64,
+    /// Highest complexity.
+    pub max_complexity: usize,
+    /// Number of functions per complexity range.
+    pub histogram: Vec<ComplexityBucket>,
+    /// Most complex functions, most complex first.
+    pub most_complex: Vec<Symbol>,
+}
+
+impl ComplexityReport {
+    /// Builds the report from a symbol inventory, keeping the `top` most
+    /// complex functions.
+    pub fn new(symbols: &[Symbol], top: usize) -> Self {
+        let mut functions: Vec<&Symbol> = symbols.iter().filter(|s| s.kind.is_function()).collect();
+        functions.sort_by_key(|s| std::cmp::Reverse(s.complexity));
+
+        let mut histogram: Vec<ComplexityBucket> = COMPLEXITY_BUCKETS
+            .iter()
+            .map(|(_, range)| ComplexityBucket {
+                range,
+                functions: 0,
+            })
+            .collect();
+        for function in &functions {
+            let bucket = COMPLEXITY_BUCKETS
+                .iter()
+                .position(|(max, _)| function.complexity <= *max)
+                .unwrap_or_default();
+            histogram[bucket].functions += 1;
+        }
+
+        let total: usize = functions.iter().map(|s| s.complexity).sum();
+
+        Self {
+            functions: functions.len(),
+            avg_complexity: if functions.is_empty() {
+                0.0
+            } else {
+                total as f64 / functions.len() as f64
+            },
+            max_complexity: functions.first().map(|s| s.complexity).unwrap_or_default(),
+            histogram,
+            most_complex: functions.into_iter().take(top).cloned().collect(),
+        }
+    }
+}
+
+/// Functions whose complexity exceeds `limit`, most complex first.
+pub fn over_limit(symbols: &[Symbol], limit: usize) -> Vec<&Symbol> {
+    let mut functions: Vec<&Symbol> = symbols
+        .iter()
+        .filter(|s| s.kind.is_function() && s.complexity > limit)
+        .collect();
+    functions.sort_by_key(|s| std::cmp::Reverse(s.complexity));
+    functions
+}
+
+impl Display for ComplexityReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Cyclomatic complexity: {} functions, avg {:.1}, max {}",
+            self.functions, self.avg_complexity, self.max_complexity
+        )?;
+        for bucket in &self.histogram {
+            write!(f, "\n  {:>6}: {}", bucket.range, bucket.functions)?;
+        }
+
+        if !self.most_complex.is_empty() {
+            write!(f, "\nMost complex functions:")?;
+            for symbol in &self.most_complex {
+                write!(
+                    f,
+                    "\n  {:>5}  {}  {}",
+                    symbol.complexity,
+                    symbol.location(),
+                    symbol.name
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,298 @@
+//! Duplicate code detection.
+//!
+//! The engines hash every code line twice: with whitespace and comments
+//! stripped, and with identifiers and literals also replaced by
+//! placeholders. The report slides a window of N code lines over every
+//! file with a rolling hash; windows found more than once are grown into
+//! clone groups.
+
+use crate::metrics::FileMetrics;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use xxhash_rust::xxh3::xxh3_64;
+
+/// Default number of code lines of the smallest reported clone.
+pub const DEFAULT_MIN_DUPLICATE_LINES: usize = 6;
+
+/// Multiplier of the rolling hash.
+const ROLLING_BASE: u64 = 0x100_0000_01b3;
+
+/// Hashes of a normalized code line.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CodeLine {
+    /// Line number.
+    pub line: usize,
+    /// Hash of the line without whitespace and comments.
+    pub exact: u64,
+    /// Hash of the line with identifiers and literals replaced by
+    /// placeholders.
+    pub abstracted: u64,
+}
+
+impl CodeLine {
+    /// Hashes a code line. `raw` is the line as written, `comment` its
+    /// trailing comment and `code` the line with string contents removed,
+    /// as given by the line scanners; `is_keyword` tells the words that are
+    /// kept when abstracting. Returns `None` for a line without code.
+    pub fn new(
+        line: usize,
+        raw: &str,
+        comment: Option<&str>,
+        code: &str,
+        is_keyword: impl Fn(&str) -> bool,
+    ) -> Option<Self> {
+        let raw = match comment.and_then(|c| raw.strip_suffix(c)) {
+            Some(code) => code.trim_end_matches(['#', '/']),
+            None => raw,
+        };
+        let exact: String = raw.chars().filter(|c| !c.is_whitespace()).collect();
+        if exact.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            line,
+            exact: xxh3_64(exact.as_bytes()),
+            abstracted: xxh3_64(abstract_code(code, is_keyword).as_bytes()),
+        })
+    }
+}
+
+/// Replaces identifiers with `$` and numbers with `0`, dropping whitespace.
+/// String literals are already reduced to their quotes by the scanners.
+fn abstract_code(code: &str, is_keyword: impl Fn(&str) -> bool) -> String {
+    let mut out = String::with_capacity(code.len());
+    let mut chars = code.char_indices().peekable();
+
+    while let Some((start, ch)) = chars.next() {
+        if ch.is_whitespace() {
+            continue;
+        }
+        if !(ch.is_alphanumeric() || ch == '_') {
+            out.push(ch);
+            continue;
+        }
+
+        let mut end = start + ch.len_utf8();
+        while let Some(&(i, c)) = chars.peek() {
+            if !(c.is_alphanumeric() || c == '_' || (ch.is_ascii_digit() && c == '.')) {
+                break;
+            }
+            end = i + c.len_utf8();
+            chars.next();
+        }
+        let word = &code[start..end];
+        if ch.is_ascii_digit() {
+            out.push('0');
+        } else if is_keyword(word) {
+            out.push_str(word);
+        } else {
+            out.push('$');
+        }
+    }
+
+    out
+}
+
+/// A copy of a clone.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CloneLocation {
+    /// File of the copy.
+    pub path: PathBuf,
+    /// First line.
+    pub start_line: usize,
+    /// Last line.
+    pub end_line: usize,
+}
+
+/// Code found at several places.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CloneGroup {
+    /// Number of code lines of each copy.
+    pub lines: usize,
+    /// Copies, in file and line order.
+    pub locations: Vec<CloneLocation>,
+}
+
+/// Duplicated code over all files.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DuplicateReport {
+    /// Code lines of all files.
+    pub code_lines: usize,
+    /// Code lines that belong to a clone.
+    pub duplicated_lines: usize,
+    /// Share of duplicated code lines, in percent.
+    pub duplicated_percent: f64,
+    /// Clone groups, largest first.
+    pub groups: Vec<CloneGroup>,
+}
+
+impl DuplicateReport {
+    /// Finds the clones of at least `min_lines` code lines, comparing the
+    /// abstracted lines if `placeholders`, and keeps the `top` largest
+    /// groups.
+    pub fn new(files: &[FileMetrics], min_lines: usize, placeholders: bool, top: usize) -> Self {
+        let min_lines = min_lines.max(1);
+        let hashes: Vec<Vec<u64>> = files
+            .iter()
+            .map(|file| {
+                file.code_lines
+                    .iter()
+                    .map(|l| if placeholders { l.abstracted } else { l.exact })
+                    .collect()
+            })
+            .collect();
+
+        let mut windows: HashMap<u64, Vec<(usize, usize)>> = HashMap::new();
+        for (file, lines) in hashes.iter().enumerate() {
+            for (start, hash) in rolling_hashes(lines, min_lines) {
+                windows.entry(hash).or_default().push((file, start));
+            }
+        }
+
+        // Occurrences of each repeated window, without the ones overlapping
+        // a previous occurrence in the same file.
+        let mut repeated: Vec<Vec<(usize, usize)>> = windows
+            .into_values()
+            .filter_map(|mut occurrences| {
+                occurrences.sort_unstable();
+                let first = occurrences[0];
+                let first_lines = &hashes[first.0][first.1..first.1 + min_lines];
+                let mut kept: Vec<(usize, usize)> = Vec::new();
+                for (file, start) in occurrences {
+                    let same = &hashes[file][start..start + min_lines] == first_lines;
+                    let overlaps = kept
+                        .last()
+                        .is_some_and(|&(f, s)| f == file && start < s + min_lines);
+                    if same && !overlaps {
+                        kept.push((file, start));
+                    }
+                }
+                (kept.len() > 1).then_some(kept)
+            })
+            .collect();
+        repeated.sort_unstable();
+
+        let window_of: HashMap<(usize, usize), usize> = repeated
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, occurrences)| occurrences.iter().map(move |&o| (o, idx)))
+            .collect();
+
+        let mut report = Self {
+            code_lines: hashes.iter().map(Vec::len).sum(),
+            ..Default::default()
+        };
+        let mut extended: HashSet<(usize, usize)> = HashSet::new();
+        let mut duplicated: HashSet<(usize, usize)> = HashSet::new();
+
+        for occurrences in &repeated {
+            if extended.contains(&occurrences[0]) {
+                continue;
+            }
+
+            // Grows the clone while the next windows of all copies are
+            // repeated together.
+            let mut len = min_lines;
+            loop {
+                let shift = len - min_lines + 1;
+                let next: Vec<Option<&usize>> = occurrences
+                    .iter()
+                    .map(|&(f, s)| window_of.get(&(f, s + shift)))
+                    .collect();
+                let same_group = next[0].is_some() && next.iter().all(|idx| *idx == next[0]);
+                let no_overlap = occurrences
+                    .windows(2)
+                    .all(|pair| pair[0].0 != pair[1].0 || pair[0].1 + len < pair[1].1);
+                if !same_group || !no_overlap {
+                    break;
+                }
+                for &(f, s) in occurrences {
+                    extended.insert((f, s + shift));
+                }
+                len += 1;
+            }
+
+            let mut locations = Vec::new();
+            for &(file, start) in occurrences {
+                let lines = &files[file].code_lines;
+                locations.push(CloneLocation {
+                    path: files[file].path.clone(),
+                    start_line: lines[start].line,
+                    end_line: lines[start + len - 1].line,
+                });
+                duplicated.extend((start..start + len).map(|i| (file, i)));
+            }
+            report.groups.push(CloneGroup {
+                lines: len,
+                locations,
+            });
+        }
+
+        report.duplicated_lines = duplicated.len();
+        if report.code_lines > 0 {
+            report.duplicated_percent =
+                report.duplicated_lines as f64 * 100.0 / report.code_lines as f64;
+        }
+        report.groups.sort_by(|a, b| {
+            (b.lines * b.locations.len())
+                .cmp(&(a.lines * a.locations.len()))
+                .then_with(|| a.locations[0].path.cmp(&b.locations[0].path))
+                .then_with(|| a.locations[0].start_line.cmp(&b.locations[0].start_line))
+        });
+        report.groups.truncate(top);
+
+        report
+    }
+}
+
+/// Hashes of the windows of `size` lines, with their first line index.
+fn rolling_hashes(lines: &[u64], size: usize) -> Vec<(usize, u64)> {
+    if lines.len() < size {
+        return Vec::new();
+    }
+
+    let top = (1..size).fold(1u64, |power, _| power.wrapping_mul(ROLLING_BASE));
+    let mut hash = lines[..size].iter().fold(0u64, |hash, line| {
+        hash.wrapping_mul(ROLLING_BASE).wrapping_add(*line)
+    });
+    let mut hashes = vec![(0, hash)];
+    for start in 1..=lines.len() - size {
+        hash = hash
+            .wrapping_sub(lines[start - 1].wrapping_mul(top))
+            .wrapping_mul(ROLLING_BASE)
+            .wrapping_add(lines[start + size - 1]);
+        hashes.push((start, hash));
+    }
+    hashes
+}
+
+impl Display for DuplicateReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Duplicated code: {} of {} code lines ({:.1}%)",
+            self.duplicated_lines, self.code_lines, self.duplicated_percent
+        )?;
+        for group in &self.groups {
+            write!(
+                f,
+                "\n  {} lines, {} copies:",
+                group.lines,
+                group.locations.len()
+            )?;
+            for location in &group.locations {
+                write!(
+                    f,
+                    "\n    {}:{}-{}",
+                    location.path.display(),
+                    location.start_line,
+                    location.end_line
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
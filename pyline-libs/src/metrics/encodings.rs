@@ -0,0 +1,92 @@
+//! Encodings of the source files.
+//!
+//! Lists the encodings the files were decoded from (see [`crate::source`]),
+//! the files that are not plain UTF-8 and those decoded lossily.
+
+use crate::metrics::FileMetrics;
+use crate::source::FileEncoding;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+
+/// A file of the [`EncodingReport`].
+#[derive(Debug, Clone, Serialize)]
+pub struct EncodedFile {
+    /// Path of the file.
+    pub path: PathBuf,
+    /// Encoding of the file.
+    #[serde(flatten)]
+    pub encoding: FileEncoding,
+}
+
+/// Encodings seen and files decoded lossily.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct EncodingReport {
+    /// Number of files per encoding.
+    pub encodings: BTreeMap<String, usize>,
+    /// Files that are not UTF-8, or whose encoding was declared or
+    /// detected, sorted by path.
+    pub detected: Vec<EncodedFile>,
+    /// Files in which invalid bytes were replaced, sorted by path.
+    pub lossy: Vec<PathBuf>,
+}
+
+impl EncodingReport {
+    /// Builds the report.
+    pub fn new(files: &[FileMetrics]) -> Self {
+        let mut report = Self::default();
+
+        for file in files {
+            *report
+                .encodings
+                .entry(file.encoding.name.clone())
+                .or_insert(0) += 1;
+            if file.encoding.lossy {
+                report.lossy.push(file.path.clone());
+            }
+            if !file.encoding.is_plain_utf8() {
+                report.detected.push(EncodedFile {
+                    path: file.path.clone(),
+                    encoding: file.encoding.clone(),
+                });
+            }
+        }
+        report.detected.sort_by(|a, b| a.path.cmp(&b.path));
+        report.lossy.sort();
+
+        report
+    }
+}
+
+impl Display for EncodingReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Encodings:")?;
+        for (name, files) in &self.encodings {
+            write!(f, "\n  {:>6}  {}", files, name)?;
+        }
+        if !self.detected.is_empty() {
+            write!(f, "\nDetected encodings:")?;
+            for file in &self.detected {
+                write!(
+                    f,
+                    "\n  {}  {} ({})",
+                    file.path.display(),
+                    file.encoding.name,
+                    file.encoding.detection
+                )?;
+            }
+        }
+        if !self.lossy.is_empty() {
+            write!(
+                f,
+                "\nDecoded lossily, invalid bytes replaced ({} files):",
+                self.lossy.len()
+            )?;
+            for path in &self.lossy {
+                write!(f, "\n  {}", path.display())?;
+            }
+        }
+        Ok(())
+    }
+}
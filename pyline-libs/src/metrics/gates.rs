@@ -0,0 +1,120 @@
+//! Quality gates: limits that make a run fail when exceeded.
+
+use crate::metrics::CodeMetrics;
+use crate::metrics::comments::CommentReport;
+use crate::metrics::complexity::over_limit;
+use crate::metrics::type_hints::TypeHintReport;
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+
+/// Configured quality gates. A gate set to `None` is not checked.
+#[derive(Debug, Clone, Default)]
+pub struct Gates {
+    /// Maximum cyclomatic complexity of a function.
+    pub max_complexity: Option<usize>,
+    /// Maximum number of open `FIXME` markers.
+    pub max_fixmes: Option<usize>,
+    /// Maximum number of `unsafe` sites, e.g. the count of the last
+    /// release, so that unsafe usage cannot grow.
+    pub max_unsafe: Option<usize>,
+    /// Minimum type-hint coverage of Python code, in percent.
+    pub min_type_coverage: Option<f64>,
+    /// Minimum number of comment lines per code line.
+    pub min_comment_ratio: Option<f64>,
+}
+
+/// A single gate violation.
+#[derive(Debug, Clone, Serialize)]
+pub struct GateViolation {
+    /// Name of the violated gate (the CLI option without dashes).
+    pub gate: &'static str,
+    /// `path:line` of the offending code, if it has a location.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    /// Human-readable description.
+    pub message: String,
+}
+
+impl Display for GateViolation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.location {
+            Some(location) => write!(f, "[{}] {}  {}", self.gate, location, self.message),
+            None => write!(f, "[{}] {}", self.gate, self.message),
+        }
+    }
+}
+
+impl Gates {
+    /// Returns `true` if no gate is configured.
+    pub fn is_empty(&self) -> bool {
+        self.max_complexity.is_none()
+            && self.max_fixmes.is_none()
+            && self.max_unsafe.is_none()
+            && self.min_type_coverage.is_none()
+            && self.min_comment_ratio.is_none()
+    }
+
+    /// Checks the metrics against the configured gates.
+    pub fn check(&self, metrics: &CodeMetrics) -> Vec<GateViolation> {
+        let mut violations = Vec::new();
+
+        if let Some(limit) = self.max_complexity {
+            for symbol in over_limit(&metrics.symbols, limit) {
+                violations.push(GateViolation {
+                    gate: "max-complexity",
+                    location: Some(symbol.location()),
+                    message: format!(
+                        "{} has complexity {} (limit {})",
+                        symbol.name, symbol.complexity, limit
+                    ),
+                });
+            }
+        }
+
+        if let Some(limit) = self.max_fixmes {
+            let fixmes = metrics.tasks.iter().filter(|t| t.tag == "FIXME").count();
+            if fixmes > limit {
+                violations.push(GateViolation {
+                    gate: "max-fixmes",
+                    location: None,
+                    message: format!("{} open FIXMEs (limit {})", fixmes, limit),
+                });
+            }
+        }
+
+        if let Some(limit) = self.max_unsafe {
+            let sites = metrics.unsafe_sites.len();
+            if sites > limit {
+                violations.push(GateViolation {
+                    gate: "max-unsafe",
+                    location: None,
+                    message: format!("{} unsafe sites (limit {})", sites, limit),
+                });
+            }
+        }
+
+        if let Some(limit) = self.min_type_coverage {
+            let coverage = TypeHintReport::new(&metrics.files, 0).total.coverage();
+            if coverage < limit {
+                violations.push(GateViolation {
+                    gate: "min-type-coverage",
+                    location: None,
+                    message: format!("type-hint coverage {:.1}% (minimum {}%)", coverage, limit),
+                });
+            }
+        }
+
+        if let Some(limit) = self.min_comment_ratio {
+            let ratio = CommentReport::new(&metrics.files, 0).total.comment_ratio();
+            if ratio < limit {
+                violations.push(GateViolation {
+                    gate: "min-comment-ratio",
+                    location: None,
+                    message: format!("comment-to-code ratio {:.2} (minimum {})", ratio, limit),
+                });
+            }
+        }
+
+        violations
+    }
+}
@@ -0,0 +1,458 @@
+//! Module dependency graph of the scanned files.
+//!
+//! Every scanned file is a module. Python modules are named after their
+//! package (directories with an `__init__.py`), Rust modules after their
+//! crate (directory with `Cargo.toml`) and their path below `src`. Imports
+//! are resolved to the longest module path they name, and the names of a
+//! Python `from X import name` to the module `X.name` when there is one;
+//! imports of other modules (standard library, third-party packages and
+//! crates) are left out.
+
+use crate::metrics::FileMetrics;
+use crate::metrics::symbols::crate_root;
+use crate::metrics::usage::Import;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+
+/// Dependency graph with its cycles and the fan-in and fan-out of the
+/// modules.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DependencyGraph {
+    /// Modules imported by each module (adjacency lists).
+    pub modules: BTreeMap<String, BTreeSet<String>>,
+    /// File of each module.
+    pub files: BTreeMap<String, PathBuf>,
+    /// Groups of modules that import each other (strongly connected
+    /// components with more than one module).
+    pub cycles: Vec<Vec<String>>,
+    /// Modules imported by the most modules, most first.
+    pub fan_in: Vec<(String, usize)>,
+    /// Modules importing the most modules, most first.
+    pub fan_out: Vec<(String, usize)>,
+}
+
+impl DependencyGraph {
+    /// Builds the graph of `files` from their imports, keeping the `top`
+    /// modules with the highest fan-in and fan-out.
+    pub fn new(imports: &[Import], files: &[FileMetrics], top: usize) -> Self {
+        let resolver = Resolver::new(files);
+        let mut graph = Self::default();
+
+        for (name, path) in &resolver.paths {
+            graph.modules.entry(name.clone()).or_default();
+            graph.files.insert(name.clone(), path.clone());
+        }
+        for import in imports {
+            let Some(from) = resolver.names.get(&import.path) else {
+                continue;
+            };
+            for to in resolver.resolve(from, import) {
+                if &to != from {
+                    graph.modules.entry(from.clone()).or_default().insert(to);
+                }
+            }
+        }
+
+        let mut fan_in: BTreeMap<&str, usize> = graph
+            .modules
+            .keys()
+            .map(|name| (name.as_str(), 0))
+            .collect();
+        for imported in graph.modules.values().flatten() {
+            *fan_in.entry(imported).or_insert(0) += 1;
+        }
+        graph.fan_in = ranked(
+            fan_in.into_iter().map(|(name, n)| (name.to_string(), n)),
+            top,
+        );
+        graph.fan_out = ranked(
+            graph
+                .modules
+                .iter()
+                .map(|(name, deps)| (name.clone(), deps.len())),
+            top,
+        );
+        graph.cycles = cycles(&graph.modules);
+
+        graph
+    }
+
+    /// Number of edges.
+    pub fn edge_count(&self) -> usize {
+        self.modules.values().map(BTreeSet::len).sum()
+    }
+
+    /// Graphviz DOT source of the graph.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph dependencies {\n    rankdir=LR;\n");
+        for (name, deps) in &self.modules {
+            if deps.is_empty() {
+                dot.push_str(&format!("    {:?};\n", name));
+            }
+            for dep in deps {
+                dot.push_str(&format!("    {:?} -> {:?};\n", name, dep));
+            }
+        }
+        dot.push('}');
+        dot
+    }
+
+    /// Mermaid flowchart of the graph.
+    pub fn to_mermaid(&self) -> String {
+        let ids: HashMap<&str, usize> = self
+            .modules
+            .keys()
+            .enumerate()
+            .map(|(i, name)| (name.as_str(), i))
+            .collect();
+
+        let mut mermaid = String::from("graph LR");
+        for (name, i) in self.modules.keys().zip(0..) {
+            mermaid.push_str(&format!(
+                "\n    m{}[\"{}\"]",
+                i,
+                name.replace('"', "#quot;")
+            ));
+        }
+        for (name, deps) in &self.modules {
+            for dep in deps {
+                mermaid.push_str(&format!(
+                    "\n    m{} --> m{}",
+                    ids[name.as_str()],
+                    ids[dep.as_str()]
+                ));
+            }
+        }
+        mermaid
+    }
+}
+
+impl Display for DependencyGraph {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Dependency graph: {} modules, {} imports",
+            self.modules.len(),
+            self.edge_count()
+        )?;
+
+        write!(f, "\nImport cycles: {}", self.cycles.len())?;
+        for cycle in &self.cycles {
+            write!(f, "\n  {}", cycle.join(" <-> "))?;
+        }
+
+        for (title, list) in [
+            ("Highest fan-in (imported by):", &self.fan_in),
+            ("Highest fan-out (imports):", &self.fan_out),
+        ] {
+            write!(f, "\n{}", title)?;
+            for (name, count) in list.iter().filter(|(_, count)| *count > 0) {
+                write!(f, "\n  {:>5}  {}", count, name)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Module names of the scanned files.
+struct Resolver {
+    /// Module name of each file.
+    names: HashMap<PathBuf, String>,
+    /// File of each module name.
+    paths: BTreeMap<String, PathBuf>,
+    /// Python files, to tell the two languages apart.
+    python: HashSet<PathBuf>,
+    /// Rust crate names as written in paths (`-` replaced with `_`),
+    /// mapped to their module name.
+    crates: HashMap<String, String>,
+}
+
+impl Resolver {
+    fn new(files: &[FileMetrics]) -> Self {
+        let packages: HashSet<&Path> = files
+            .iter()
+            .filter(|f| f.path.file_name() == Some("__init__.py".as_ref()))
+            .filter_map(|f| f.path.parent())
+            .collect();
+        let mut roots: HashMap<PathBuf, Option<PathBuf>> = HashMap::new();
+
+        let mut resolver = Self {
+            names: HashMap::new(),
+            paths: BTreeMap::new(),
+            python: HashSet::new(),
+            crates: HashMap::new(),
+        };
+        for file in files {
+            let name = match file.path.extension().and_then(|e| e.to_str()) {
+                Some("py") => {
+                    resolver.python.insert(file.path.clone());
+                    python_module(&file.path, &packages)
+                }
+                Some("rs") => {
+                    let dir = file.path.parent().unwrap_or(Path::new("")).to_path_buf();
+                    let root = roots.entry(dir).or_insert_with_key(|dir| crate_root(dir));
+                    let (krate, name) = rust_module(&file.path, root.as_deref());
+                    resolver.crates.insert(krate.replace('-', "_"), krate);
+                    name
+                }
+                _ => continue,
+            };
+            if name.is_empty() {
+                continue;
+            }
+
+            // Two scripts with the same name outside of a package.
+            let name = if resolver.paths.contains_key(&name) {
+                file.path.display().to_string()
+            } else {
+                name
+            };
+            resolver.names.insert(file.path.clone(), name.clone());
+            resolver.paths.insert(name, file.path.clone());
+        }
+
+        resolver
+    }
+
+    /// Returns the modules imported by `import` from the module `from`.
+    ///
+    /// Each name of `from X import name` is resolved to the module `X.name`
+    /// if it is scanned; `X` itself is imported for the other names (e.g.
+    /// classes and functions), for star imports and for plain imports.
+    fn resolve(&self, from: &str, import: &Import) -> Vec<String> {
+        let candidates = if self.python.contains(&import.path) {
+            self.python_candidates(from, import)
+        } else {
+            self.rust_candidates(from, import)
+        };
+
+        let mut modules = Vec::new();
+        let mut imports_module = import.names.is_empty();
+        for name in &import.names {
+            let submodule = candidates.iter().find_map(|segments| {
+                let mut segments = segments.clone();
+                segments.push(name.clone());
+                let module = segments.join(".");
+                self.paths.contains_key(&module).then_some(module)
+            });
+            match submodule {
+                Some(module) => modules.push(module),
+                None => imports_module = true,
+            }
+        }
+        if imports_module {
+            modules.extend(self.longest_module(candidates, &import.path));
+        }
+        modules
+    }
+
+    /// Returns the longest prefix of the candidate paths that is a module.
+    fn longest_module(&self, candidates: Vec<Vec<String>>, path: &Path) -> Option<String> {
+        candidates.into_iter().find_map(|mut segments| {
+            while !segments.is_empty() {
+                let name = segments.join(self.separator(path));
+                if self.paths.contains_key(&name) {
+                    return Some(name);
+                }
+                segments.pop();
+            }
+            None
+        })
+    }
+
+    fn separator(&self, path: &Path) -> &'static str {
+        if self.python.contains(path) {
+            "."
+        } else {
+            "::"
+        }
+    }
+
+    /// Absolute module paths an import may refer to.
+    fn python_candidates(&self, from: &str, import: &Import) -> Vec<Vec<String>> {
+        let dots = import.module.chars().take_while(|c| *c == '.').count();
+        let rest = import.module[dots..]
+            .split('.')
+            .filter(|s| !s.is_empty())
+            .map(String::from);
+        if dots == 0 {
+            return vec![rest.collect()];
+        }
+
+        // `.` is the package of the module: the module itself for an
+        // `__init__.py`, its parent otherwise.
+        let mut base: Vec<String> = from.split('.').map(String::from).collect();
+        let is_package = import.path.file_name() == Some("__init__.py".as_ref());
+        let up = dots - usize::from(is_package);
+        if up > base.len() {
+            return Vec::new();
+        }
+        base.truncate(base.len() - up);
+        base.extend(rest);
+        vec![base]
+    }
+
+    /// Absolute module paths an import may refer to: from the crate root,
+    /// relative to the module, or in another scanned crate.
+    fn rust_candidates(&self, from: &str, import: &Import) -> Vec<Vec<String>> {
+        let module: Vec<String> = from.split("::").map(String::from).collect();
+        let mut segments = import.module.split("::").filter(|s| !s.is_empty());
+        let Some(first) = segments.next() else {
+            return Vec::new();
+        };
+        let rest: Vec<String> = segments.map(String::from).collect();
+
+        // `self` is the module of the file: `a.rs` declares the children
+        // of `a`, as `a/mod.rs` does.
+        let with = |mut base: Vec<String>, extra: &[String]| {
+            base.extend_from_slice(extra);
+            base
+        };
+        match first {
+            "crate" => vec![with(vec![module[0].clone()], &rest)],
+            "self" => vec![with(module, &rest)],
+            "super" => {
+                let supers = 1 + rest.iter().take_while(|s| *s == "super").count();
+                if supers >= module.len() {
+                    return Vec::new();
+                }
+                let base = module[..module.len() - supers].to_vec();
+                vec![with(base, &rest[supers - 1..])]
+            }
+            _ => {
+                let mut candidates = Vec::new();
+                if let Some(krate) = self.crates.get(first) {
+                    candidates.push(with(vec![krate.clone()], &rest));
+                }
+                let mut relative = module;
+                relative.push(first.to_string());
+                candidates.push(with(relative, &rest));
+                candidates
+            }
+        }
+    }
+}
+
+/// Dotted module name of a Python file: its packages and its stem.
+fn python_module(path: &Path, packages: &HashSet<&Path>) -> String {
+    let mut segments: Vec<String> = Vec::new();
+    if path.file_name() != Some("__init__.py".as_ref())
+        && let Some(stem) = path.file_stem()
+    {
+        segments.push(stem.to_string_lossy().into_owned());
+    }
+    let dir = path.parent().unwrap_or(Path::new(""));
+    for package in dir.ancestors().take_while(|dir| packages.contains(dir)) {
+        if let Some(name) = package.file_name() {
+            segments.push(name.to_string_lossy().into_owned());
+        }
+    }
+    segments.reverse();
+    segments.join(".")
+}
+
+/// Crate name and module path of a Rust file (`krate::a::b` for
+/// `src/a/b.rs` or `src/a/b/mod.rs`, `krate` for `src/lib.rs` and
+/// `src/main.rs`). Files outside `src` keep their path in the crate.
+fn rust_module(path: &Path, root: Option<&Path>) -> (String, String) {
+    let root = root.unwrap_or(Path::new(""));
+    let krate = root
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "crate".to_string());
+
+    let relative = path.strip_prefix(root).unwrap_or(path).with_extension("");
+    let relative = relative.strip_prefix("src").unwrap_or(&relative);
+    let mut segments: Vec<String> = vec![krate.clone()];
+    segments.extend(
+        relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned()),
+    );
+    let is_root = segments.len() == 2 && matches!(segments[1].as_str(), "lib" | "main");
+    if is_root || segments.last().is_some_and(|s| s == "mod") {
+        segments.pop();
+    }
+
+    (krate, segments.join("::"))
+}
+
+/// Returns the `top` entries, most first, then by name.
+fn ranked(entries: impl Iterator<Item = (String, usize)>, top: usize) -> Vec<(String, usize)> {
+    let mut entries: Vec<(String, usize)> = entries.collect();
+    entries.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    entries.truncate(top);
+    entries
+}
+
+/// Strongly connected components with more than one module (Tarjan's
+/// algorithm, iterative).
+fn cycles(modules: &BTreeMap<String, BTreeSet<String>>) -> Vec<Vec<String>> {
+    let names: Vec<&str> = modules.keys().map(String::as_str).collect();
+    let index_of: HashMap<&str, usize> = names.iter().enumerate().map(|(i, n)| (*n, i)).collect();
+    let edges: Vec<Vec<usize>> = modules
+        .values()
+        .map(|deps| {
+            deps.iter()
+                .filter_map(|d| index_of.get(d.as_str()).copied())
+                .collect()
+        })
+        .collect();
+
+    let mut index = vec![usize::MAX; names.len()];
+    let mut low = vec![0; names.len()];
+    let mut on_stack = vec![false; names.len()];
+    let mut stack = Vec::new();
+    let mut next = 0;
+    let mut components = Vec::new();
+
+    for start in 0..names.len() {
+        if index[start] != usize::MAX {
+            continue;
+        }
+        // Node and position in its edge list.
+        let mut work = vec![(start, 0)];
+        while let Some((node, edge)) = work.pop() {
+            if edge == 0 {
+                index[node] = next;
+                low[node] = next;
+                next += 1;
+                stack.push(node);
+                on_stack[node] = true;
+            }
+            if let Some(&dep) = edges[node].get(edge) {
+                work.push((node, edge + 1));
+                if index[dep] == usize::MAX {
+                    work.push((dep, 0));
+                } else if on_stack[dep] {
+                    low[node] = low[node].min(index[dep]);
+                }
+                continue;
+            }
+
+            if low[node] == index[node] {
+                let mut component = Vec::new();
+                while let Some(member) = stack.pop() {
+                    on_stack[member] = false;
+                    component.push(names[member].to_string());
+                    if member == node {
+                        break;
+                    }
+                }
+                if component.len() > 1 {
+                    component.sort();
+                    components.push(component);
+                }
+            }
+            if let Some(&(parent, _)) = work.last() {
+                low[parent] = low[parent].min(low[node]);
+            }
+        }
+    }
+
+    components.sort();
+    components
+}
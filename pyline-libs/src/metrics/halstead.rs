@@ -0,0 +1,352 @@
+//! Halstead metrics and maintainability index.
+//!
+//! The engines split every code line into operators (keywords and
+//! punctuation) and operands (identifiers and literals). String literals are
+//! reduced to their quotes by the line scanners, so all strings count as the
+//! same operand; closing brackets are not counted, a pair being one
+//! operator. The counts are kept per file and per function.
+//!
+//! The maintainability index is the normalized variant used by Visual
+//! Studio and radon:
+//! `max(0, (171 - 5.2 ln V - 0.23 G - 16.2 ln LOC) * 100 / 171)`, where `V`
+//! is the Halstead volume, `G` the cyclomatic complexity and `LOC` the
+//! number of lines.
+
+use crate::metrics::FileMetrics;
+use crate::metrics::symbols::Symbol;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+
+/// Operator and operand counts of a file or a function.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Halstead {
+    /// Distinct operators (`n1`).
+    pub distinct_operators: usize,
+    /// Distinct operands (`n2`).
+    pub distinct_operands: usize,
+    /// Total operators (`N1`).
+    pub operators: usize,
+    /// Total operands (`N2`).
+    pub operands: usize,
+}
+
+impl Halstead {
+    /// Returns `true` if nothing was counted.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Vocabulary: `n1 + n2`.
+    pub fn vocabulary(&self) -> usize {
+        self.distinct_operators + self.distinct_operands
+    }
+
+    /// Length: `N1 + N2`.
+    pub fn length(&self) -> usize {
+        self.operators + self.operands
+    }
+
+    /// Volume: `N * log2(n)`.
+    pub fn volume(&self) -> f64 {
+        match self.vocabulary() {
+            0 => 0.0,
+            vocabulary => self.length() as f64 * (vocabulary as f64).log2(),
+        }
+    }
+
+    /// Difficulty: `n1 / 2 * N2 / n2`.
+    pub fn difficulty(&self) -> f64 {
+        match self.distinct_operands {
+            0 => 0.0,
+            n2 => self.distinct_operators as f64 / 2.0 * self.operands as f64 / n2 as f64,
+        }
+    }
+
+    /// Effort: `D * V`.
+    pub fn effort(&self) -> f64 {
+        self.difficulty() * self.volume()
+    }
+}
+
+/// Maintainability index from 0 to 100 of code with Halstead `volume`,
+/// cyclomatic `complexity` and `lines` lines.
+pub fn maintainability_index(volume: f64, complexity: usize, lines: usize) -> f64 {
+    let raw = 171.0
+        - 5.2 * volume.max(1.0).ln()
+        - 0.23 * complexity as f64
+        - 16.2 * (lines.max(1) as f64).ln();
+    (raw * 100.0 / 171.0).clamp(0.0, 100.0)
+}
+
+/// A token of a code line, as classified by the engines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum HalsteadToken<'a> {
+    /// Keyword or punctuation.
+    Operator(&'a str),
+    /// Identifier or literal.
+    Operand(&'a str),
+}
+
+/// Collects the tokens of a file and counts them per file and per function.
+pub(crate) struct HalsteadTracker {
+    /// Tokens of each code line, with its number; `true` for operators.
+    lines: Vec<(usize, Vec<(bool, String)>)>,
+}
+
+impl HalsteadTracker {
+    pub fn new() -> Self {
+        Self { lines: Vec::new() }
+    }
+
+    /// Records the tokens of a line.
+    pub fn line<'a>(
+        &mut self,
+        line_no: usize,
+        tokens: impl IntoIterator<Item = HalsteadToken<'a>>,
+    ) {
+        let tokens: Vec<(bool, String)> = tokens
+            .into_iter()
+            .map(|token| match token {
+                HalsteadToken::Operator(op) => (true, op.to_string()),
+                HalsteadToken::Operand(op) => (false, op.to_string()),
+            })
+            .collect();
+        if !tokens.is_empty() {
+            self.lines.push((line_no, tokens));
+        }
+    }
+
+    /// Sets the counts of the function `symbols` from the lines they span
+    /// and returns the counts of the whole file.
+    pub fn finish(self, symbols: &mut [Symbol]) -> Halstead {
+        for symbol in symbols.iter_mut().filter(|s| s.kind.is_function()) {
+            let first = self
+                .lines
+                .partition_point(|(line, _)| *line < symbol.start_line);
+            let last = self
+                .lines
+                .partition_point(|(line, _)| *line <= symbol.end_line);
+            symbol.halstead = count(&self.lines[first..last]);
+        }
+        count(&self.lines)
+    }
+}
+
+/// Counts the tokens of `lines`.
+fn count(lines: &[(usize, Vec<(bool, String)>)]) -> Halstead {
+    let mut operators = HashSet::new();
+    let mut operands = HashSet::new();
+    let mut counts = Halstead::default();
+
+    for (is_operator, token) in lines.iter().flat_map(|(_, tokens)| tokens) {
+        if *is_operator {
+            counts.operators += 1;
+            operators.insert(token.as_str());
+        } else {
+            counts.operands += 1;
+            operands.insert(token.as_str());
+        }
+    }
+    counts.distinct_operators = operators.len();
+    counts.distinct_operands = operands.len();
+
+    counts
+}
+
+/// Halstead metrics and maintainability index of a file or a function.
+#[derive(Debug, Clone, Serialize)]
+pub struct HalsteadEntry {
+    /// File of the code.
+    pub path: PathBuf,
+    /// Function name; `None` for a whole file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// First line.
+    pub line: usize,
+    /// Operator and operand counts.
+    pub counts: Halstead,
+    /// Vocabulary.
+    pub vocabulary: usize,
+    /// Length.
+    pub length: usize,
+    /// Volume.
+    pub volume: f64,
+    /// Difficulty.
+    pub difficulty: f64,
+    /// Effort.
+    pub effort: f64,
+    /// Cyclomatic complexity.
+    pub complexity: usize,
+    /// Lines of code: code lines of a file, lines spanned by a function.
+    pub lines: usize,
+    /// Maintainability index.
+    pub maintainability: f64,
+}
+
+impl HalsteadEntry {
+    fn new(
+        path: PathBuf,
+        name: Option<String>,
+        line: usize,
+        counts: Halstead,
+        complexity: usize,
+        lines: usize,
+    ) -> Self {
+        let volume = counts.volume();
+        Self {
+            path,
+            name,
+            line,
+            counts,
+            vocabulary: counts.vocabulary(),
+            length: counts.length(),
+            volume,
+            difficulty: counts.difficulty(),
+            effort: counts.effort(),
+            complexity,
+            lines,
+            maintainability: maintainability_index(volume, complexity, lines),
+        }
+    }
+}
+
+/// Halstead metrics and maintainability index per file and per function.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct HalsteadReport {
+    /// Sum of the file volumes.
+    pub total_volume: f64,
+    /// Sum of the file efforts.
+    pub total_effort: f64,
+    /// Average maintainability index of the files.
+    pub avg_file_maintainability: f64,
+    /// Average maintainability index of the functions.
+    pub avg_function_maintainability: f64,
+    /// Metrics per file.
+    pub files: BTreeMap<String, HalsteadEntry>,
+    /// Least maintainable files, lowest index first.
+    pub least_maintainable_files: Vec<HalsteadEntry>,
+    /// Least maintainable functions, lowest index first.
+    pub least_maintainable_functions: Vec<HalsteadEntry>,
+}
+
+impl HalsteadReport {
+    /// Builds the report, keeping the `top` least maintainable files and
+    /// functions.
+    ///
+    /// The complexity of a file is the sum of the complexities of its
+    /// functions (at least 1).
+    pub fn new(files: &[FileMetrics], symbols: &[Symbol], top: usize) -> Self {
+        let mut report = Self::default();
+
+        let mut complexity: HashMap<&PathBuf, usize> = HashMap::new();
+        let mut functions = Vec::new();
+        for symbol in symbols.iter().filter(|s| s.kind.is_function()) {
+            *complexity.entry(&symbol.path).or_insert(0) += symbol.complexity;
+            functions.push(HalsteadEntry::new(
+                symbol.path.clone(),
+                Some(symbol.name.clone()),
+                symbol.start_line,
+                symbol.halstead,
+                symbol.complexity,
+                symbol.line_count(),
+            ));
+        }
+
+        for file in files.iter().filter(|f| !f.halstead.is_empty()) {
+            let entry = HalsteadEntry::new(
+                file.path.clone(),
+                None,
+                1,
+                file.halstead,
+                complexity
+                    .get(&file.path)
+                    .copied()
+                    .unwrap_or_default()
+                    .max(1),
+                file.code_lines.len(),
+            );
+            report.total_volume += entry.volume;
+            report.total_effort += entry.effort;
+            report.files.insert(file.path.display().to_string(), entry);
+        }
+
+        let mut file_entries: Vec<HalsteadEntry> = report.files.values().cloned().collect();
+        report.avg_file_maintainability = average(&file_entries);
+        report.avg_function_maintainability = average(&functions);
+
+        report.least_maintainable_files = least_maintainable(&mut file_entries, top);
+        report.least_maintainable_functions = least_maintainable(&mut functions, top);
+
+        report
+    }
+}
+
+/// Average maintainability index of `entries`.
+fn average(entries: &[HalsteadEntry]) -> f64 {
+    if entries.is_empty() {
+        return 0.0;
+    }
+    entries.iter().map(|e| e.maintainability).sum::<f64>() / entries.len() as f64
+}
+
+/// The `top` entries with the lowest maintainability index.
+fn least_maintainable(entries: &mut Vec<HalsteadEntry>, top: usize) -> Vec<HalsteadEntry> {
+    entries.sort_by(|a, b| {
+        a.maintainability
+            .total_cmp(&b.maintainability)
+            .then_with(|| a.path.cmp(&b.path))
+            .then_with(|| a.line.cmp(&b.line))
+    });
+    entries.truncate(top);
+    std::mem::take(entries)
+}
+
+impl Display for HalsteadReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Halstead: {} files, volume {:.0}, effort {:.0}\nMaintainability index: files avg {:.1}, functions avg {:.1}",
+            self.files.len(),
+            self.total_volume,
+            self.total_effort,
+            self.avg_file_maintainability,
+            self.avg_function_maintainability
+        )?;
+
+        if !self.least_maintainable_files.is_empty() {
+            write!(f, "\nLeast maintainable files (MI, volume, difficulty):")?;
+            for entry in &self.least_maintainable_files {
+                write!(
+                    f,
+                    "\n  {:>5.1}  {:>8.0}  {:>5.1}  {}",
+                    entry.maintainability,
+                    entry.volume,
+                    entry.difficulty,
+                    entry.path.display()
+                )?;
+            }
+        }
+        if !self.least_maintainable_functions.is_empty() {
+            write!(
+                f,
+                "\nLeast maintainable functions (MI, volume, difficulty):"
+            )?;
+            for entry in &self.least_maintainable_functions {
+                write!(
+                    f,
+                    "\n  {:>5.1}  {:>8.0}  {:>5.1}  {}:{}  {}",
+                    entry.maintainability,
+                    entry.volume,
+                    entry.difficulty,
+                    entry.path.display(),
+                    entry.line,
+                    entry.name.as_deref().unwrap_or_default()
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
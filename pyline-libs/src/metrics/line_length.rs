@@ -0,0 +1,180 @@
+//! Line length distribution.
+//!
+//! Lengths are display columns: wide characters (e.g. CJK) count as two
+//! columns, combining marks as none, and tabs advance to the next multiple
+//! of the tab width. The engines keep the widths of the text between the
+//! tabs of every non-empty line, so the tab width and the limit are chosen
+//! when the report is built. Lines excluded from the line-length check of
+//! the formatters and linters are exempt from the limit: `# noqa: E501`
+//! (or a bare `# noqa`) in Python, items and statements marked
+//! `#[rustfmt::skip]` in Rust.
+
+use crate::metrics::FileMetrics;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use unicode_width::UnicodeWidthStr;
+
+/// Default number of columns of a tab.
+pub const DEFAULT_TAB_WIDTH: usize = 4;
+
+/// Columns per bar of the histogram.
+pub const HISTOGRAM_BUCKET: usize = 10;
+
+/// Display width of a physical line.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LineWidth {
+    /// Line number.
+    pub line: usize,
+    /// Display widths of the text before the first tab, between the tabs
+    /// and after the last tab.
+    pub segments: Vec<usize>,
+    /// The line is exempt from the limit.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub exempt: bool,
+}
+
+impl LineWidth {
+    /// Measures a line. Returns `None` for an empty line.
+    pub fn new(line: usize, raw: &str, exempt: bool) -> Option<Self> {
+        if raw.is_empty() {
+            return None;
+        }
+        Some(Self {
+            line,
+            segments: raw.split('\t').map(UnicodeWidthStr::width).collect(),
+            exempt,
+        })
+    }
+
+    /// Width of the line in columns, with tabs of `tab_width` columns.
+    pub fn width(&self, tab_width: usize) -> usize {
+        let tab_width = tab_width.max(1);
+        let mut segments = self.segments.iter();
+        let first = segments.next().copied().unwrap_or(0);
+        segments.fold(first, |column, segment| {
+            (column / tab_width + 1) * tab_width + segment
+        })
+    }
+}
+
+/// A line of the longest-lines list.
+#[derive(Debug, Clone, Serialize)]
+pub struct LongLine {
+    /// File of the line.
+    pub path: PathBuf,
+    /// Line number.
+    pub line: usize,
+    /// Width in columns.
+    pub width: usize,
+}
+
+/// Distribution of the line lengths.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LineLengthReport {
+    /// Columns of a tab.
+    pub tab_width: usize,
+    /// Non-empty lines.
+    pub lines: usize,
+    /// Exempt lines.
+    pub exempt: usize,
+    /// Number of lines per bucket of [`HISTOGRAM_BUCKET`] columns, keyed by
+    /// the first column of the bucket.
+    pub histogram: BTreeMap<usize, usize>,
+    /// Line length limit.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_line_length: Option<usize>,
+    /// Lines longer than the limit, exempt lines excluded.
+    pub over_limit: usize,
+    /// Longest lines, exempt lines excluded, longest first.
+    pub longest: Vec<LongLine>,
+}
+
+impl LineLengthReport {
+    /// Builds the report with tabs of `tab_width` columns, counting the
+    /// lines over `max_line_length` and keeping the `top` longest lines.
+    pub fn new(
+        files: &[FileMetrics],
+        tab_width: usize,
+        max_line_length: Option<usize>,
+        top: usize,
+    ) -> Self {
+        let mut report = Self {
+            tab_width,
+            max_line_length,
+            ..Default::default()
+        };
+        let mut longest = Vec::new();
+
+        for file in files {
+            for line in &file.line_widths {
+                let width = line.width(tab_width);
+                report.lines += 1;
+                *report
+                    .histogram
+                    .entry(width / HISTOGRAM_BUCKET * HISTOGRAM_BUCKET)
+                    .or_insert(0) += 1;
+
+                if line.exempt {
+                    report.exempt += 1;
+                    continue;
+                }
+                if max_line_length.is_some_and(|limit| width > limit) {
+                    report.over_limit += 1;
+                }
+                longest.push(LongLine {
+                    path: file.path.clone(),
+                    line: line.line,
+                    width,
+                });
+            }
+        }
+
+        longest.sort_by(|a, b| {
+            b.width
+                .cmp(&a.width)
+                .then_with(|| a.path.cmp(&b.path))
+                .then_with(|| a.line.cmp(&b.line))
+        });
+        longest.truncate(top);
+        report.longest = longest;
+
+        report
+    }
+}
+
+impl Display for LineLengthReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Line lengths: {} lines ({} exempt), tab width {}",
+            self.lines, self.exempt, self.tab_width
+        )?;
+        if let Some(limit) = self.max_line_length {
+            write!(f, "\n  {} lines over {} columns", self.over_limit, limit)?;
+        }
+        for (start, count) in &self.histogram {
+            write!(
+                f,
+                "\n  {:>4}-{:<4} {:>7}",
+                start,
+                start + HISTOGRAM_BUCKET - 1,
+                count
+            )?;
+        }
+        if !self.longest.is_empty() {
+            write!(f, "\nLongest lines:")?;
+            for line in &self.longest {
+                write!(
+                    f,
+                    "\n  {:>5}  {}:{}",
+                    line.width,
+                    line.path.display(),
+                    line.line
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,129 @@
+//! Macro invocations and attributes in Rust code.
+//!
+//! The engine counts macro calls by name (`println!`, `vec!`), attributes
+//! by path (`derive`, `tokio::main`), the traits listed in `#[derive(...)]`
+//! and the lints silenced with `#[allow(...)]`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+/// Frequency tables of macros and attributes.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MacroCounts {
+    /// Macro invocations per macro name.
+    #[serde(default)]
+    pub macros: BTreeMap<String, usize>,
+    /// Attributes per path, inner and outer alike.
+    #[serde(default)]
+    pub attributes: BTreeMap<String, usize>,
+    /// Traits listed in `#[derive(...)]`.
+    #[serde(default)]
+    pub derives: BTreeMap<String, usize>,
+    /// Lints listed in `#[allow(...)]`.
+    #[serde(default)]
+    pub allows: BTreeMap<String, usize>,
+}
+
+impl MacroCounts {
+    /// Returns `true` if nothing was counted.
+    pub fn is_empty(&self) -> bool {
+        self.macros.is_empty()
+            && self.attributes.is_empty()
+            && self.derives.is_empty()
+            && self.allows.is_empty()
+    }
+
+    /// Adds the counts of `other`.
+    pub fn merge(&mut self, other: &Self) {
+        for (counts, other) in [
+            (&mut self.macros, &other.macros),
+            (&mut self.attributes, &other.attributes),
+            (&mut self.derives, &other.derives),
+            (&mut self.allows, &other.allows),
+        ] {
+            for (name, count) in other {
+                *counts.entry(name.clone()).or_insert(0) += count;
+            }
+        }
+    }
+}
+
+/// Most used macros, attributes and derived traits, with all lint
+/// suppressions.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MacroReport {
+    /// Number of macro invocations.
+    pub macro_calls: usize,
+    /// Number of attributes.
+    pub attribute_count: usize,
+    /// Number of `#[allow(...)]` lint suppressions.
+    pub suppressions: usize,
+    /// Most invoked macros, most first.
+    pub macros: Vec<(String, usize)>,
+    /// Most used attributes, most first.
+    pub attributes: Vec<(String, usize)>,
+    /// Most derived traits, most first.
+    pub derives: Vec<(String, usize)>,
+    /// Suppressed lints, most first.
+    pub allows: Vec<(String, usize)>,
+}
+
+impl MacroReport {
+    /// Builds the report, keeping the `top` macros, attributes and derived
+    /// traits.
+    pub fn new(counts: &MacroCounts, top: usize) -> Self {
+        let mut macros = ranked(&counts.macros);
+        let mut attributes = ranked(&counts.attributes);
+        let mut derives = ranked(&counts.derives);
+        macros.truncate(top);
+        attributes.truncate(top);
+        derives.truncate(top);
+
+        Self {
+            macro_calls: counts.macros.values().sum(),
+            attribute_count: counts.attributes.values().sum(),
+            suppressions: counts.allows.values().sum(),
+            macros,
+            attributes,
+            derives,
+            allows: ranked(&counts.allows),
+        }
+    }
+}
+
+/// Returns the entries of `counts`, most first, then by name.
+fn ranked(counts: &BTreeMap<String, usize>) -> Vec<(String, usize)> {
+    let mut entries: Vec<(String, usize)> = counts
+        .iter()
+        .map(|(name, count)| (name.clone(), *count))
+        .collect();
+    entries.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    entries
+}
+
+impl Display for MacroReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Macro invocations: {}", self.macro_calls)?;
+        write_table(f, &self.macros, "!")?;
+        write!(f, "\nAttributes: {}", self.attribute_count)?;
+        write_table(f, &self.attributes, "")?;
+        if !self.derives.is_empty() {
+            write!(f, "\nDerived traits:")?;
+            write_table(f, &self.derives, "")?;
+        }
+        write!(f, "\nLint suppressions (#[allow]): {}", self.suppressions)?;
+        write_table(f, &self.allows, "")
+    }
+}
+
+fn write_table(
+    f: &mut Formatter<'_>,
+    entries: &[(String, usize)],
+    suffix: &str,
+) -> std::fmt::Result {
+    for (name, count) in entries {
+        write!(f, "\n  {:>5}  {}{}", count, name, suffix)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,357 @@
+//! Code metrics built on top of the line parsers.
+//!
+//! Each submodule holds the data collected per file (e.g. [`symbols::Symbol`])
+//! and the report computed from it. The per-file data of a language parser
+//! is kept in [`CodeMetrics`] and merged together with the line statistics.
+pub mod comments;
+pub mod complexity;
+pub mod duplicates;
+pub mod encodings;
+pub mod gates;
+pub mod graph;
+pub mod halstead;
+pub mod line_length;
+pub mod macros;
+pub mod nesting;
+pub mod panics;
+pub mod sarif;
+pub mod symbols;
+pub mod tasks;
+pub mod type_hints;
+pub mod unsafety;
+pub mod usage;
+pub mod whitespace;
+
+use crate::source::FileEncoding;
+use comments::{CommentReport, Documentation};
+use complexity::ComplexityReport;
+use duplicates::{CodeLine, DEFAULT_MIN_DUPLICATE_LINES, DuplicateReport};
+use encodings::EncodingReport;
+use graph::DependencyGraph;
+use halstead::{Halstead, HalsteadReport};
+use line_length::{DEFAULT_TAB_WIDTH, LineLengthReport, LineWidth};
+use macros::{MacroCounts, MacroReport};
+use nesting::{DepthStat, NestingReport};
+use panics::{PanicReport, PanicSite};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use symbols::{Symbol, SymbolReport};
+use tasks::{DEFAULT_TASK_TAGS, TaskMarker, TaskReport};
+use type_hints::{TypeHintReport, TypeHints};
+use unsafety::{UnsafeReport, UnsafeSite};
+use usage::{Import, UsageReport};
+use whitespace::{Whitespace, WhitespaceReport};
+
+/// Kinds of reports that can be computed from [`CodeMetrics`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ReportKind {
+    /// Inventory of functions, classes and other symbols.
+    Symbols,
+    /// Cyclomatic complexity of functions.
+    Complexity,
+    /// Nesting depth per file and per function.
+    Nesting,
+    /// Task markers (`TODO`, `FIXME`, ...) in comments and docstrings.
+    Tasks,
+    /// `unsafe` sites in Rust code.
+    Unsafe,
+    /// Panic-prone call sites in Rust code.
+    Panics,
+    /// Macro invocations and attributes in Rust code.
+    Macros,
+    /// Decorators, imports and dunder methods in Python code.
+    Usage,
+    /// Module dependency graph.
+    Graph,
+    /// Type-hint coverage of Python code.
+    TypeHints,
+    /// Duplicated code blocks.
+    Duplicates,
+    /// Halstead metrics and maintainability index.
+    Halstead,
+    /// Comment density and documentation ratios.
+    Comments,
+    /// Line length distribution and longest lines.
+    LineLength,
+    /// Whitespace hygiene.
+    Whitespace,
+    /// Encodings of the files.
+    Encodings,
+}
+
+impl ReportKind {
+    /// Name of the report, used as its key in JSON output.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Symbols => "symbols",
+            Self::Complexity => "complexity",
+            Self::Nesting => "nesting",
+            Self::Tasks => "tasks",
+            Self::Unsafe => "unsafe",
+            Self::Panics => "panics",
+            Self::Macros => "macros",
+            Self::Usage => "usage",
+            Self::Graph => "graph",
+            Self::TypeHints => "types",
+            Self::Duplicates => "duplicates",
+            Self::Halstead => "halstead",
+            Self::Comments => "comments",
+            Self::LineLength => "line-length",
+            Self::Whitespace => "whitespace",
+            Self::Encodings => "encodings",
+        }
+    }
+}
+
+/// Options shared by all reports.
+#[derive(Debug, Clone)]
+pub struct ReportOptions {
+    /// Number of entries in "top N" lists (e.g. the longest functions).
+    pub top: usize,
+    /// Tags of the task markers to report.
+    pub task_tags: Vec<String>,
+    /// Count indexing with `[]` as a panic path (heuristic).
+    pub panic_index: bool,
+    /// Code lines of the smallest reported clone.
+    pub min_duplicate_lines: usize,
+    /// Compare code with identifiers and literals replaced by placeholders
+    /// when looking for clones.
+    pub duplicate_placeholders: bool,
+    /// Columns of a tab in the line-length report.
+    pub tab_width: usize,
+    /// Line length limit of the line-length report.
+    pub max_line_length: Option<usize>,
+}
+
+impl Default for ReportOptions {
+    fn default() -> Self {
+        Self {
+            top: 10,
+            task_tags: DEFAULT_TASK_TAGS.iter().map(|t| t.to_string()).collect(),
+            panic_index: false,
+            min_duplicate_lines: DEFAULT_MIN_DUPLICATE_LINES,
+            duplicate_placeholders: false,
+            tab_width: DEFAULT_TAB_WIDTH,
+            max_line_length: None,
+        }
+    }
+}
+
+/// A computed report.
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum Report {
+    /// See [`SymbolReport`].
+    Symbols(SymbolReport),
+    /// See [`ComplexityReport`].
+    Complexity(ComplexityReport),
+    /// See [`NestingReport`].
+    Nesting(NestingReport),
+    /// See [`TaskReport`].
+    Tasks(TaskReport),
+    /// See [`UnsafeReport`].
+    Unsafe(UnsafeReport),
+    /// See [`PanicReport`].
+    Panics(PanicReport),
+    /// See [`MacroReport`].
+    Macros(MacroReport),
+    /// See [`UsageReport`].
+    Usage(UsageReport),
+    /// See [`DependencyGraph`].
+    Graph(DependencyGraph),
+    /// See [`TypeHintReport`].
+    TypeHints(TypeHintReport),
+    /// See [`DuplicateReport`].
+    Duplicates(DuplicateReport),
+    /// See [`HalsteadReport`].
+    Halstead(HalsteadReport),
+    /// See [`CommentReport`].
+    Comments(CommentReport),
+    /// See [`LineLengthReport`].
+    LineLength(LineLengthReport),
+    /// See [`WhitespaceReport`].
+    Whitespace(WhitespaceReport),
+    /// See [`EncodingReport`].
+    Encodings(EncodingReport),
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Symbols(report) => write!(f, "{}", report),
+            Self::Complexity(report) => write!(f, "{}", report),
+            Self::Nesting(report) => write!(f, "{}", report),
+            Self::Tasks(report) => write!(f, "{}", report),
+            Self::Unsafe(report) => write!(f, "{}", report),
+            Self::Panics(report) => write!(f, "{}", report),
+            Self::Macros(report) => write!(f, "{}", report),
+            Self::Usage(report) => write!(f, "{}", report),
+            Self::Graph(report) => write!(f, "{}", report),
+            Self::TypeHints(report) => write!(f, "{}", report),
+            Self::Duplicates(report) => write!(f, "{}", report),
+            Self::Halstead(report) => write!(f, "{}", report),
+            Self::Comments(report) => write!(f, "{}", report),
+            Self::LineLength(report) => write!(f, "{}", report),
+            Self::Whitespace(report) => write!(f, "{}", report),
+            Self::Encodings(report) => write!(f, "{}", report),
+        }
+    }
+}
+
+/// Metrics of a single file.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct FileMetrics {
+    /// Path of the file.
+    pub path: PathBuf,
+    /// Nesting depth of the code lines.
+    #[serde(default)]
+    pub nesting: DepthStat,
+    /// Lines indented with a mix of tabs and spaces, or in a different
+    /// style than the rest of the file (Python only).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mixed_indentation: Vec<usize>,
+    /// Lines of test code: `#[cfg(test)]` items, or the whole file under a
+    /// `tests` directory (Rust only).
+    #[serde(default)]
+    pub test_lines: usize,
+    /// Type-hint counts (Python only).
+    #[serde(default, skip_serializing_if = "TypeHints::is_empty")]
+    pub type_hints: TypeHints,
+    /// Hashes of the code lines, for duplicate detection. Not serialized;
+    /// the analysis cache keeps them separately.
+    #[serde(default, skip_serializing)]
+    pub code_lines: Vec<CodeLine>,
+    /// Halstead counts of the whole file.
+    #[serde(default, skip_serializing_if = "Halstead::is_empty")]
+    pub halstead: Halstead,
+    /// Comment and documentation counts.
+    #[serde(default, skip_serializing_if = "Documentation::is_empty")]
+    pub documentation: Documentation,
+    /// Display widths of the non-empty lines. Not serialized; the analysis
+    /// cache keeps them separately.
+    #[serde(default, skip_serializing)]
+    pub line_widths: Vec<LineWidth>,
+    /// Whitespace findings and line endings.
+    #[serde(default, skip_serializing_if = "Whitespace::is_empty")]
+    pub whitespace: Whitespace,
+    /// Encoding the file was decoded from.
+    #[serde(default, skip_serializing_if = "FileEncoding::is_default")]
+    pub encoding: FileEncoding,
+}
+
+impl FileMetrics {
+    /// Creates empty metrics for the file at `path`.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Per-file data collected by the language engines in addition to the line
+/// statistics.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct CodeMetrics {
+    /// Functions, classes and other symbols.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub symbols: Vec<Symbol>,
+    /// Metrics of every parsed file.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<FileMetrics>,
+    /// Candidate task markers of any upper-case tag.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tasks: Vec<TaskMarker>,
+    /// `unsafe` sites (Rust only).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unsafe_sites: Vec<UnsafeSite>,
+    /// Panic-prone call sites (Rust only).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub panic_sites: Vec<PanicSite>,
+    /// Macro invocations and attributes (Rust only).
+    #[serde(default, skip_serializing_if = "MacroCounts::is_empty")]
+    pub macros: MacroCounts,
+    /// Imported modules: `import` statements in Python, `use`, `mod` and
+    /// `extern crate` in Rust.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub imports: Vec<Import>,
+}
+
+impl CodeMetrics {
+    /// Merges another instance into this one, consuming it.
+    pub fn merge(&mut self, other: Self) {
+        self.symbols.extend(other.symbols);
+        self.files.extend(other.files);
+        self.tasks.extend(other.tasks);
+        self.unsafe_sites.extend(other.unsafe_sites);
+        self.panic_sites.extend(other.panic_sites);
+        self.macros.merge(&other.macros);
+        self.imports.extend(other.imports);
+    }
+
+    /// Borrowing version of `merge()`.
+    pub fn merge_ref(&mut self, other: &Self) {
+        self.symbols.extend_from_slice(&other.symbols);
+        self.files.extend_from_slice(&other.files);
+        self.tasks.extend_from_slice(&other.tasks);
+        self.unsafe_sites.extend_from_slice(&other.unsafe_sites);
+        self.panic_sites.extend_from_slice(&other.panic_sites);
+        self.macros.merge(&other.macros);
+        self.imports.extend_from_slice(&other.imports);
+    }
+
+    /// Computes a report.
+    pub fn report(&self, kind: ReportKind, options: &ReportOptions) -> Report {
+        match kind {
+            ReportKind::Symbols => Report::Symbols(SymbolReport::new(&self.symbols, options.top)),
+            ReportKind::Complexity => {
+                Report::Complexity(ComplexityReport::new(&self.symbols, options.top))
+            }
+            ReportKind::Nesting => {
+                Report::Nesting(NestingReport::new(&self.files, &self.symbols, options.top))
+            }
+            ReportKind::Tasks => Report::Tasks(TaskReport::new(&self.tasks, &options.task_tags)),
+            ReportKind::Unsafe => Report::Unsafe(UnsafeReport::new(&self.unsafe_sites)),
+            ReportKind::Panics => Report::Panics(PanicReport::new(
+                &self.panic_sites,
+                &self.files,
+                options.panic_index,
+                options.top,
+            )),
+            ReportKind::Macros => Report::Macros(MacroReport::new(&self.macros, options.top)),
+            ReportKind::Usage => Report::Usage(UsageReport::new(
+                &self.imports,
+                &self.symbols,
+                &self.files,
+                options.top,
+            )),
+            ReportKind::Graph => Report::Graph(DependencyGraph::new(
+                &self.imports,
+                &self.files,
+                options.top,
+            )),
+            ReportKind::TypeHints => {
+                Report::TypeHints(TypeHintReport::new(&self.files, options.top))
+            }
+            ReportKind::Duplicates => Report::Duplicates(DuplicateReport::new(
+                &self.files,
+                options.min_duplicate_lines,
+                options.duplicate_placeholders,
+                options.top,
+            )),
+            ReportKind::Halstead => {
+                Report::Halstead(HalsteadReport::new(&self.files, &self.symbols, options.top))
+            }
+            ReportKind::Comments => Report::Comments(CommentReport::new(&self.files, options.top)),
+            ReportKind::LineLength => Report::LineLength(LineLengthReport::new(
+                &self.files,
+                options.tab_width,
+                options.max_line_length,
+                options.top,
+            )),
+            ReportKind::Whitespace => Report::Whitespace(WhitespaceReport::new(&self.files)),
+            ReportKind::Encodings => Report::Encodings(EncodingReport::new(&self.files)),
+        }
+    }
+}
@@ -0,0 +1,154 @@
+//! Nesting depth of code: indentation levels in Python, brace depth in
+//! Rust.
+
+use crate::metrics::FileMetrics;
+use crate::metrics::symbols::Symbol;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+/// Maximum and average nesting depth over a set of lines.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DepthStat {
+    /// Deepest nesting seen.
+    pub max: usize,
+    /// Sum of the depths of all recorded lines.
+    pub total: usize,
+    /// Number of recorded lines.
+    pub lines: usize,
+    /// First line with the deepest nesting.
+    pub deepest_line: usize,
+}
+
+impl DepthStat {
+    /// Records the nesting depth of a line.
+    pub fn record(&mut self, line_no: usize, depth: usize) {
+        if depth > self.max || self.lines == 0 {
+            self.max = depth;
+            self.deepest_line = line_no;
+        }
+        self.total += depth;
+        self.lines += 1;
+    }
+
+    /// Average depth of the recorded lines.
+    pub fn avg(&self) -> f64 {
+        if self.lines == 0 {
+            0.0
+        } else {
+            self.total as f64 / self.lines as f64
+        }
+    }
+}
+
+/// A file with its nesting depth.
+#[derive(Debug, Clone, Serialize)]
+pub struct FileNesting {
+    /// `path:line` of the deepest line.
+    pub location: String,
+    /// Deepest nesting in the file.
+    pub max_depth: usize,
+    /// Average nesting of the lines of the file.
+    pub avg_depth: f64,
+}
+
+/// Summary of nesting depth per file and per function.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct NestingReport {
+    /// Deepest nesting over all files.
+    pub max_depth: usize,
+    /// Average nesting over all lines.
+    pub avg_depth: f64,
+    /// Most deeply nested files, deepest first.
+    pub deepest_files: Vec<FileNesting>,
+    /// Most deeply nested functions, deepest first.
+    pub deepest_functions: Vec<Symbol>,
+    /// `path:line` of lines indented with a mix of tabs and spaces.
+    pub mixed_indentation: Vec<String>,
+}
+
+impl NestingReport {
+    /// Builds the report, keeping the `top` deepest files and functions.
+    pub fn new(files: &[FileMetrics], symbols: &[Symbol], top: usize) -> Self {
+        let mut total = DepthStat::default();
+        for file in files {
+            total.max = total.max.max(file.nesting.max);
+            total.total += file.nesting.total;
+            total.lines += file.nesting.lines;
+        }
+
+        let mut deepest: Vec<&FileMetrics> = files.iter().filter(|f| f.nesting.lines > 0).collect();
+        deepest.sort_by_key(|f| std::cmp::Reverse(f.nesting.max));
+
+        let mut functions: Vec<&Symbol> = symbols.iter().filter(|s| s.kind.is_function()).collect();
+        functions.sort_by_key(|s| std::cmp::Reverse(s.max_nesting));
+
+        Self {
+            max_depth: total.max,
+            avg_depth: total.avg(),
+            deepest_files: deepest
+                .into_iter()
+                .take(top)
+                .map(|f| FileNesting {
+                    location: format!("{}:{}", f.path.display(), f.nesting.deepest_line),
+                    max_depth: f.nesting.max,
+                    avg_depth: f.nesting.avg(),
+                })
+                .collect(),
+            deepest_functions: functions.into_iter().take(top).cloned().collect(),
+            mixed_indentation: files
+                .iter()
+                .flat_map(|f| {
+                    f.mixed_indentation
+                        .iter()
+                        .map(|line| format!("{}:{}", f.path.display(), line))
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Display for NestingReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Nesting depth: max {}, avg {:.2}",
+            self.max_depth, self.avg_depth
+        )?;
+
+        if !self.deepest_functions.is_empty() {
+            write!(f, "\nDeepest functions:")?;
+            for symbol in &self.deepest_functions {
+                write!(
+                    f,
+                    "\n  {:>3}  avg {:.2}  {}  {}",
+                    symbol.max_nesting,
+                    symbol.avg_nesting,
+                    symbol.location(),
+                    symbol.name
+                )?;
+            }
+        }
+        if !self.deepest_files.is_empty() {
+            write!(f, "\nDeepest files:")?;
+            for file in &self.deepest_files {
+                write!(
+                    f,
+                    "\n  {:>3}  avg {:.2}  {}",
+                    file.max_depth, file.avg_depth, file.location
+                )?;
+            }
+        }
+        if !self.mixed_indentation.is_empty() {
+            write!(
+                f,
+                "\nMixed tab/space indentation ({} lines):",
+                self.mixed_indentation.len()
+            )?;
+            for location in &self.mixed_indentation {
+                write!(f, "\n  {}", location)?;
+            }
+        }
+
+        Ok(())
+    }
+}
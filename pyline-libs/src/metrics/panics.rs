@@ -0,0 +1,223 @@
+//! Panic-prone call sites in Rust code.
+//!
+//! The engine records `.unwrap()`, `.expect(...)`, the panicking macros and
+//! indexing with `[]`, telling test code from the rest. Indexing is a
+//! heuristic (any `[` after an operand) and is reported only on request.
+
+use crate::metrics::FileMetrics;
+use crate::metrics::symbols::crate_root;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+
+/// Kinds of panic-prone sites.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PanicKind {
+    /// `.unwrap()`.
+    Unwrap,
+    /// `.expect(...)`.
+    Expect,
+    /// `panic!`.
+    Panic,
+    /// `todo!`.
+    Todo,
+    /// `unimplemented!`.
+    Unimplemented,
+    /// `unreachable!`.
+    Unreachable,
+    /// Indexing with `[]`.
+    Index,
+}
+
+impl PanicKind {
+    /// Returns the kind of the panicking macro `name`.
+    pub fn from_macro(name: &str) -> Option<Self> {
+        match name {
+            "panic" => Some(Self::Panic),
+            "todo" => Some(Self::Todo),
+            "unimplemented" => Some(Self::Unimplemented),
+            "unreachable" => Some(Self::Unreachable),
+            _ => None,
+        }
+    }
+}
+
+impl Display for PanicKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Unwrap => ".unwrap()",
+            Self::Expect => ".expect()",
+            Self::Panic => "panic!",
+            Self::Todo => "todo!",
+            Self::Unimplemented => "unimplemented!",
+            Self::Unreachable => "unreachable!",
+            Self::Index => "[] indexing",
+        };
+        f.pad(name)
+    }
+}
+
+/// A panic-prone site.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PanicSite {
+    /// File the site is in.
+    pub path: PathBuf,
+    /// Line of the site.
+    pub line: usize,
+    /// Kind of the site.
+    pub kind: PanicKind,
+    /// The site is in test code: a `#[cfg(test)]` item or a file under a
+    /// `tests` directory.
+    pub in_test: bool,
+}
+
+/// Number of sites in test and non-test code.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct PanicCount {
+    /// Sites in non-test code.
+    pub code: usize,
+    /// Sites in test code.
+    pub test: usize,
+}
+
+impl PanicCount {
+    fn add(&mut self, in_test: bool) {
+        if in_test {
+            self.test += 1;
+        } else {
+            self.code += 1;
+        }
+    }
+}
+
+/// Robustness of a crate.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CrateRobustness {
+    /// Non-test code lines.
+    pub code_lines: usize,
+    /// Panic-prone sites in non-test code.
+    pub panic_sites: usize,
+    /// Sites per 1000 non-test code lines.
+    pub per_kloc: f64,
+    /// `100 / (1 + per_kloc / 10)`: 100 without panic sites, 50 with 10
+    /// sites per 1000 lines.
+    pub score: f64,
+}
+
+/// Panic-prone sites per kind, file and crate.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PanicReport {
+    /// Sites per kind.
+    pub counts: BTreeMap<PanicKind, PanicCount>,
+    /// All sites.
+    pub total: PanicCount,
+    /// Robustness per crate (directory with `Cargo.toml`).
+    pub crates: BTreeMap<String, CrateRobustness>,
+    /// Files with the most sites in non-test code, most first.
+    pub hotspots: Vec<(String, usize)>,
+}
+
+impl PanicReport {
+    /// Builds the report, leaving out indexing unless `with_index`, and
+    /// keeping the `top` files with the most sites.
+    pub fn new(sites: &[PanicSite], files: &[FileMetrics], with_index: bool, top: usize) -> Self {
+        let mut report = Self::default();
+        let mut roots: HashMap<PathBuf, Option<PathBuf>> = HashMap::new();
+        let mut crate_name = |path: &Path| {
+            let dir = path.parent().unwrap_or(Path::new("")).to_path_buf();
+            match roots.entry(dir).or_insert_with_key(|dir| crate_root(dir)) {
+                Some(root) => root.display().to_string(),
+                None => "<no crate>".to_string(),
+            }
+        };
+
+        for file in files
+            .iter()
+            .filter(|f| f.path.extension() == Some("rs".as_ref()))
+        {
+            let lines = file.nesting.lines.saturating_sub(file.test_lines);
+            report
+                .crates
+                .entry(crate_name(&file.path))
+                .or_default()
+                .code_lines += lines;
+        }
+
+        let mut per_file: HashMap<&Path, usize> = HashMap::new();
+        for site in sites {
+            if site.kind == PanicKind::Index && !with_index {
+                continue;
+            }
+            report
+                .counts
+                .entry(site.kind)
+                .or_default()
+                .add(site.in_test);
+            report.total.add(site.in_test);
+            if !site.in_test {
+                *per_file.entry(&site.path).or_insert(0) += 1;
+                report
+                    .crates
+                    .entry(crate_name(&site.path))
+                    .or_default()
+                    .panic_sites += 1;
+            }
+        }
+
+        for robustness in report.crates.values_mut() {
+            if robustness.code_lines > 0 {
+                robustness.per_kloc =
+                    robustness.panic_sites as f64 * 1000.0 / robustness.code_lines as f64;
+            }
+            robustness.score = 100.0 / (1.0 + robustness.per_kloc / 10.0);
+        }
+
+        let mut hotspots: Vec<(String, usize)> = per_file
+            .into_iter()
+            .map(|(path, count)| (path.display().to_string(), count))
+            .collect();
+        hotspots.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        hotspots.truncate(top);
+        report.hotspots = hotspots;
+
+        report
+    }
+}
+
+impl Display for PanicReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Panic paths: {} in code, {} in tests",
+            self.total.code, self.total.test
+        )?;
+        for (kind, count) in &self.counts {
+            write!(
+                f,
+                "\n  {:<14}  code {:>5}  tests {:>5}",
+                kind, count.code, count.test
+            )?;
+        }
+
+        if !self.crates.is_empty() {
+            write!(f, "\nRobustness per crate:")?;
+            for (name, crate_stat) in &self.crates {
+                write!(
+                    f,
+                    "\n  {:>5.1}  {:.2}/kloc  {}",
+                    crate_stat.score, crate_stat.per_kloc, name
+                )?;
+            }
+        }
+        if !self.hotspots.is_empty() {
+            write!(f, "\nFiles with the most panic paths:")?;
+            for (path, count) in &self.hotspots {
+                write!(f, "\n  {:>5}  {}", count, path)?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,73 @@
+//! Minimal [SARIF 2.1.0](https://sarifweb.azurewebsites.net/) output for
+//! code scanning tools.
+
+use serde::Serialize;
+use serde_json::{Value, json};
+use std::path::PathBuf;
+
+/// A finding to be reported in a SARIF log.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SarifResult {
+    /// Rule identifier, e.g. `todo`.
+    pub rule_id: String,
+    /// SARIF level: `note`, `warning` or `error`.
+    pub level: &'static str,
+    /// Message of the finding.
+    pub message: String,
+    /// File of the finding.
+    pub path: PathBuf,
+    /// Line of the finding (1-based).
+    pub line: usize,
+}
+
+/// A SARIF log with a single run of `pyline`.
+#[derive(Debug, Clone, Serialize)]
+pub struct SarifLog {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: Vec<Value>,
+}
+
+impl SarifLog {
+    /// Builds the log from the findings.
+    pub fn new(results: &[SarifResult]) -> Self {
+        let mut rules: Vec<&str> = results.iter().map(|r| r.rule_id.as_str()).collect();
+        rules.sort_unstable();
+        rules.dedup();
+
+        let results: Vec<Value> = results
+            .iter()
+            .map(|result| {
+                json!({
+                    "ruleId": result.rule_id,
+                    "level": result.level,
+                    "message": { "text": result.message },
+                    "locations": [{
+                        "physicalLocation": {
+                            "artifactLocation": {
+                                "uri": result.path.to_string_lossy().replace('\\', "/")
+                            },
+                            "region": { "startLine": result.line }
+                        }
+                    }]
+                })
+            })
+            .collect();
+
+        Self {
+            schema: "https://json.schemastore.org/sarif-2.1.0.json",
+            version: "2.1.0",
+            runs: vec![json!({
+                "tool": {
+                    "driver": {
+                        "name": "pyline",
+                        "version": env!("CARGO_PKG_VERSION"),
+                        "rules": rules.iter().map(|id| json!({ "id": id })).collect::<Vec<_>>()
+                    }
+                },
+                "results": results
+            })],
+        }
+    }
+}
@@ -0,0 +1,319 @@
+//! Symbol inventory: functions, methods, classes and other named items.
+
+use crate::metrics::halstead::Halstead;
+use crate::utils::percentile;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+
+/// Kind of a symbol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SymbolKind {
+    /// Module-level (free) function.
+    Function,
+    /// Function defined in a class body.
+    Method,
+    /// Function defined inside another function.
+    NestedFunction,
+    /// Anonymous function (`lambda`).
+    Lambda,
+    /// Class definition.
+    Class,
+    /// `struct` item.
+    Struct,
+    /// `enum` item.
+    Enum,
+    /// `union` item.
+    Union,
+    /// `trait` item.
+    Trait,
+    /// `type` alias (including associated types).
+    TypeAlias,
+    /// `impl Trait for Type` block.
+    TraitImpl,
+    /// `impl Type` block.
+    InherentImpl,
+    /// `macro_rules!` definition.
+    MacroRules,
+    /// `mod` item, inline or declared.
+    Module,
+}
+
+impl SymbolKind {
+    /// Returns `true` for named functions of any kind.
+    pub fn is_function(&self) -> bool {
+        matches!(self, Self::Function | Self::Method | Self::NestedFunction)
+    }
+}
+
+impl Display for SymbolKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Self::Function => "functions",
+            Self::Method => "methods",
+            Self::NestedFunction => "nested functions",
+            Self::Lambda => "lambdas",
+            Self::Class => "classes",
+            Self::Struct => "structs",
+            Self::Enum => "enums",
+            Self::Union => "unions",
+            Self::Trait => "traits",
+            Self::TypeAlias => "type aliases",
+            Self::TraitImpl => "trait impls",
+            Self::InherentImpl => "inherent impls",
+            Self::MacroRules => "macro_rules! definitions",
+            Self::Module => "modules",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Visibility of a Rust item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Visibility {
+    /// `pub`.
+    Public,
+    /// `pub(crate)`.
+    Crate,
+    /// `pub(super)` or `pub(in path)`.
+    Restricted,
+    /// No visibility modifier (or `pub(self)`).
+    Private,
+}
+
+impl Display for Visibility {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Self::Public => "pub",
+            Self::Crate => "pub(crate)",
+            Self::Restricted => "pub(restricted)",
+            Self::Private => "private",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// A symbol found in a source file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Symbol {
+    /// File the symbol is defined in.
+    pub path: PathBuf,
+    /// Symbol name (`<lambda>` for anonymous functions).
+    pub name: String,
+    /// Kind of the symbol.
+    pub kind: SymbolKind,
+    /// First line of the definition (1-based, without decorators).
+    pub start_line: usize,
+    /// Last line of the body.
+    pub end_line: usize,
+    /// Whether the function is declared `async`.
+    pub is_async: bool,
+    /// Decorator names, outermost first (e.g. `property`, `pytest.fixture`).
+    pub decorators: Vec<String>,
+    /// Cyclomatic complexity of functions (1 plus the number of decision
+    /// points); 0 for other symbols.
+    #[serde(default)]
+    pub complexity: usize,
+    /// Deepest nesting inside a function body (statements directly in the
+    /// body have depth 1).
+    #[serde(default)]
+    pub max_nesting: usize,
+    /// Average nesting of the lines of a function body.
+    #[serde(default)]
+    pub avg_nesting: f64,
+    /// Visibility of Rust items; `None` for languages without modifiers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub visibility: Option<Visibility>,
+    /// Halstead counts of functions.
+    #[serde(default, skip_serializing_if = "Halstead::is_empty")]
+    pub halstead: Halstead,
+    /// Whether the item has a doc comment (Rust) or a docstring (Python).
+    #[serde(default)]
+    pub documented: bool,
+}
+
+impl Symbol {
+    /// Creates a symbol spanning a single line.
+    pub fn new(path: PathBuf, name: impl Into<String>, kind: SymbolKind, line: usize) -> Self {
+        Self {
+            path,
+            name: name.into(),
+            kind,
+            start_line: line,
+            end_line: line,
+            is_async: false,
+            decorators: Vec::new(),
+            complexity: usize::from(kind.is_function()),
+            max_nesting: 0,
+            avg_nesting: 0.0,
+            visibility: None,
+            halstead: Halstead::default(),
+            documented: false,
+        }
+    }
+
+    /// Number of lines spanned by the symbol.
+    pub fn line_count(&self) -> usize {
+        self.end_line + 1 - self.start_line
+    }
+
+    /// Returns the `path:line` location of the symbol.
+    pub fn location(&self) -> String {
+        format!("{}:{}", self.path.display(), self.start_line)
+    }
+}
+
+/// Summary of a symbol inventory.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SymbolReport {
+    /// Number of symbols per kind.
+    pub counts: BTreeMap<SymbolKind, usize>,
+    /// Number of `async` functions.
+    pub async_functions: usize,
+    /// Number of decorated symbols.
+    pub decorated: usize,
+    /// Number of named functions (free functions, methods, nested).
+    pub functions: usize,
+    /// Average function length in lines.
+    pub avg_length: f64,
+    /// Median function length.
+    pub p50_length: usize,
+    /// 90th percentile of function length.
+    pub p90_length: usize,
+    /// 99th percentile of function length.
+    pub p99_length: usize,
+    /// Longest functions, longest first.
+    pub longest: Vec<Symbol>,
+    /// Public items per crate (directory with `Cargo.toml`); empty for
+    /// languages without visibility modifiers.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub api_surface: BTreeMap<String, ApiSurface>,
+}
+
+/// Items declared `pub` in a crate.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ApiSurface {
+    /// Number of public items.
+    pub items: usize,
+    /// Number of public items per kind.
+    pub by_kind: BTreeMap<SymbolKind, usize>,
+}
+
+impl SymbolReport {
+    /// Builds the report from a symbol inventory, keeping the `top` longest
+    /// functions.
+    pub fn new(symbols: &[Symbol], top: usize) -> Self {
+        let mut report = Self::default();
+
+        for symbol in symbols {
+            *report.counts.entry(symbol.kind).or_insert(0) += 1;
+            if !symbol.decorators.is_empty() {
+                report.decorated += 1;
+            }
+            if symbol.is_async {
+                report.async_functions += 1;
+            }
+        }
+
+        let mut functions: Vec<&Symbol> = symbols.iter().filter(|s| s.kind.is_function()).collect();
+        functions.sort_by_key(|s| std::cmp::Reverse(s.line_count()));
+
+        let mut lengths: Vec<usize> = functions.iter().map(|s| s.line_count()).collect();
+        lengths.sort_unstable();
+
+        report.functions = functions.len();
+        if !lengths.is_empty() {
+            report.avg_length = lengths.iter().sum::<usize>() as f64 / lengths.len() as f64;
+        }
+        report.p50_length = percentile(&lengths, 50.0);
+        report.p90_length = percentile(&lengths, 90.0);
+        report.p99_length = percentile(&lengths, 99.0);
+        report.longest = functions.into_iter().take(top).cloned().collect();
+        report.api_surface = Self::api_surface(symbols);
+
+        report
+    }
+
+    /// Counts `pub` items per crate. Nested functions are not reachable
+    /// from outside and are left out.
+    fn api_surface(symbols: &[Symbol]) -> BTreeMap<String, ApiSurface> {
+        let mut crates: HashMap<PathBuf, Option<PathBuf>> = HashMap::new();
+        let mut surface: BTreeMap<String, ApiSurface> = BTreeMap::new();
+
+        for symbol in symbols {
+            if symbol.visibility != Some(Visibility::Public)
+                || symbol.kind == SymbolKind::NestedFunction
+            {
+                continue;
+            }
+
+            let dir = symbol.path.parent().unwrap_or(Path::new("")).to_path_buf();
+            let root = crates.entry(dir).or_insert_with_key(|dir| crate_root(dir));
+            let name = match root {
+                Some(root) => root.display().to_string(),
+                None => "<no crate>".to_string(),
+            };
+
+            let api = surface.entry(name).or_default();
+            api.items += 1;
+            *api.by_kind.entry(symbol.kind).or_insert(0) += 1;
+        }
+
+        surface
+    }
+}
+
+/// Returns the nearest ancestor of `dir` (inclusive) that contains a
+/// `Cargo.toml`.
+pub(crate) fn crate_root(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .find(|dir| dir.join("Cargo.toml").is_file())
+        .map(Path::to_path_buf)
+}
+
+impl Display for SymbolReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Symbols:")?;
+        for (kind, count) in &self.counts {
+            write!(f, "\n  {}: {}", kind, count)?;
+        }
+        if self.async_functions > 0 {
+            write!(f, "\n  async functions: {}", self.async_functions)?;
+        }
+        if self.decorated > 0 {
+            write!(f, "\n  decorated: {}", self.decorated)?;
+        }
+
+        for (name, api) in &self.api_surface {
+            write!(f, "\nPublic API of {}: {} items", name, api.items)?;
+            for (kind, count) in &api.by_kind {
+                write!(f, "\n  {}: {}", kind, count)?;
+            }
+        }
+
+        if self.functions == 0 {
+            return Ok(());
+        }
+
+        write!(
+            f,
+            "\nFunction length (lines): avg {:.1}, p50 {}, p90 {}, p99 {}",
+            self.avg_length, self.p50_length, self.p90_length, self.p99_length
+        )?;
+        write!(f, "\nLongest functions:")?;
+        for symbol in &self.longest {
+            write!(
+                f,
+                "\n  {:>5}  {}  {}",
+                symbol.line_count(),
+                symbol.location(),
+                symbol.name
+            )?;
+        }
+
+        Ok(())
+    }
+}
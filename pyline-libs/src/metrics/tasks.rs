@@ -0,0 +1,174 @@
+//! Task markers (`TODO`, `FIXME`, ...) found in comments and docstrings.
+//!
+//! The engines record every candidate marker, that is an upper-case word
+//! at the start of a comment or followed by `:` or `(author)`. The report
+//! keeps the ones whose tag is configured, so changing the tag list does
+//! not require re-parsing.
+
+use crate::metrics::sarif::SarifResult;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+
+/// Tags reported by default.
+pub const DEFAULT_TASK_TAGS: &[&str] = &["TODO", "FIXME", "HACK", "XXX"];
+
+/// A task marker found in a comment or docstring.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskMarker {
+    /// File the marker is in.
+    pub path: PathBuf,
+    /// Line of the marker.
+    pub line: usize,
+    /// Tag, e.g. `TODO`.
+    pub tag: String,
+    /// Author given as `TODO(alice):`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// Text after the tag.
+    pub text: String,
+}
+
+impl TaskMarker {
+    /// Returns the `path:line` location of the marker.
+    pub fn location(&self) -> String {
+        format!("{}:{}", self.path.display(), self.line)
+    }
+}
+
+/// Finds a task marker in the text of a comment.
+///
+/// Returns the tag, the author and the text after the marker.
+///
+/// ```
+/// use pyline_libs::metrics::tasks::find_marker;
+///
+/// assert_eq!(
+///     find_marker(" TODO(alice): handle errors"),
+///     Some(("TODO".to_string(), Some("alice".to_string()), "handle errors".to_string()))
+/// );
+/// assert_eq!(
+///     find_marker(" XXX this is a hack"),
+///     Some(("XXX".to_string(), None, "this is a hack".to_string()))
+/// );
+/// assert_eq!(find_marker(" uses HTTP and JSON"), None);
+/// ```
+pub fn find_marker(comment: &str) -> Option<(String, Option<String>, String)> {
+    let bytes = comment.as_bytes();
+    let mut first_word = true;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if !bytes[i].is_ascii_alphanumeric() {
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+            i += 1;
+        }
+        let word = &comment[start..i];
+        let is_tag = word.len() >= 2 && word.bytes().all(|b| b.is_ascii_uppercase());
+        let rest = &comment[i..];
+
+        if is_tag && (first_word || rest.starts_with(':') || rest.starts_with('(')) {
+            let (author, rest) = match rest.strip_prefix('(').and_then(|r| r.split_once(')')) {
+                Some((author, rest)) => (Some(author.trim().to_string()), rest),
+                None => (None, rest),
+            };
+            let text = rest.trim_start_matches(':').trim();
+            return Some((word.to_string(), author, text.to_string()));
+        }
+        first_word = false;
+    }
+
+    None
+}
+
+/// Task markers with the configured tags.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TaskReport {
+    /// Number of markers per tag.
+    pub counts: BTreeMap<String, usize>,
+    /// Markers in file and line order.
+    pub markers: Vec<TaskMarker>,
+}
+
+impl TaskReport {
+    /// Builds the report from the markers whose tag is in `tags`.
+    pub fn new(markers: &[TaskMarker], tags: &[String]) -> Self {
+        let mut markers: Vec<TaskMarker> = markers
+            .iter()
+            .filter(|m| tags.contains(&m.tag))
+            .cloned()
+            .collect();
+        markers.sort_by(|a, b| (&a.path, a.line).cmp(&(&b.path, b.line)));
+
+        let mut counts = BTreeMap::new();
+        for marker in &markers {
+            *counts.entry(marker.tag.clone()).or_insert(0) += 1;
+        }
+
+        Self { counts, markers }
+    }
+
+    /// Converts the markers into SARIF results; `FIXME` is a warning,
+    /// other tags are notes.
+    pub fn sarif_results(&self) -> Vec<SarifResult> {
+        self.markers
+            .iter()
+            .map(|marker| SarifResult {
+                rule_id: marker.tag.to_lowercase(),
+                level: if marker.tag == "FIXME" {
+                    "warning"
+                } else {
+                    "note"
+                },
+                message: match &marker.author {
+                    Some(author) => format!("{}({}): {}", marker.tag, author, marker.text),
+                    None => format!("{}: {}", marker.tag, marker.text),
+                },
+                path: marker.path.clone(),
+                line: marker.line,
+            })
+            .collect()
+    }
+}
+
+impl Display for TaskReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let counts: Vec<String> = self
+            .counts
+            .iter()
+            .map(|(tag, count)| format!("{} {}", tag, count))
+            .collect();
+        write!(f, "Task markers: {}", self.markers.len())?;
+        if !counts.is_empty() {
+            write!(f, " ({})", counts.join(", "))?;
+        }
+
+        let tag_width = self.markers.iter().map(|m| m.tag.len()).max().unwrap_or(0);
+        let author_width = self
+            .markers
+            .iter()
+            .map(|m| m.author.as_deref().unwrap_or("-").len())
+            .max()
+            .unwrap_or(0);
+        for marker in &self.markers {
+            write!(
+                f,
+                "\n  {:<tw$}  {:<aw$}  {}  {}",
+                marker.tag,
+                marker.author.as_deref().unwrap_or("-"),
+                marker.location(),
+                marker.text,
+                tw = tag_width,
+                aw = author_width
+            )?;
+        }
+
+        Ok(())
+    }
+}
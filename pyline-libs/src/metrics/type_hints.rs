@@ -0,0 +1,157 @@
+//! Type-hint coverage of Python code.
+//!
+//! The engine counts, per file, the parameters and return types of `def`
+//! statements with and without annotations (`self` and `cls` left out),
+//! and the escape hatches of the type checker: `Any`, `cast(...)` and
+//! `# type: ignore`.
+
+use crate::metrics::FileMetrics;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::ops::AddAssign;
+use std::path::Path;
+
+/// Type-hint counts of a file or a group of files.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct TypeHints {
+    /// Parameters, without `self` and `cls`.
+    pub params: usize,
+    /// Parameters with an annotation.
+    pub annotated_params: usize,
+    /// Functions and methods.
+    pub functions: usize,
+    /// Functions with a return annotation.
+    pub annotated_returns: usize,
+    /// Occurrences of `Any`.
+    pub any: usize,
+    /// `cast(...)` calls.
+    pub casts: usize,
+    /// `# type: ignore` comments.
+    pub type_ignores: usize,
+}
+
+impl TypeHints {
+    /// Returns `true` if nothing was counted.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Percentage of annotated parameters.
+    pub fn param_coverage(&self) -> f64 {
+        percent(self.annotated_params, self.params)
+    }
+
+    /// Percentage of functions with a return annotation.
+    pub fn return_coverage(&self) -> f64 {
+        percent(self.annotated_returns, self.functions)
+    }
+
+    /// Percentage of annotated parameters and return types together.
+    pub fn coverage(&self) -> f64 {
+        percent(
+            self.annotated_params + self.annotated_returns,
+            self.params + self.functions,
+        )
+    }
+}
+
+impl AddAssign<&TypeHints> for TypeHints {
+    fn add_assign(&mut self, other: &TypeHints) {
+        self.params += other.params;
+        self.annotated_params += other.annotated_params;
+        self.functions += other.functions;
+        self.annotated_returns += other.annotated_returns;
+        self.any += other.any;
+        self.casts += other.casts;
+        self.type_ignores += other.type_ignores;
+    }
+}
+
+/// `part` of `total` in percent; 100 when there is nothing to annotate.
+fn percent(part: usize, total: usize) -> f64 {
+    if total == 0 {
+        100.0
+    } else {
+        part as f64 * 100.0 / total as f64
+    }
+}
+
+/// Type-hint coverage overall, per package and per file.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TypeHintReport {
+    /// Counts over all files.
+    pub total: TypeHints,
+    /// Counts per package (directory of the files).
+    pub packages: BTreeMap<String, TypeHints>,
+    /// Counts per file.
+    pub files: BTreeMap<String, TypeHints>,
+    /// Files with the lowest coverage, lowest first.
+    pub least_covered: Vec<(String, f64)>,
+}
+
+impl TypeHintReport {
+    /// Builds the report, keeping the `top` files with the lowest coverage.
+    pub fn new(files: &[FileMetrics], top: usize) -> Self {
+        let mut report = Self::default();
+
+        for file in files.iter().filter(|f| !f.type_hints.is_empty()) {
+            let dir = file.path.parent().unwrap_or(Path::new(""));
+            report.total += &file.type_hints;
+            *report
+                .packages
+                .entry(dir.display().to_string())
+                .or_default() += &file.type_hints;
+            report
+                .files
+                .insert(file.path.display().to_string(), file.type_hints);
+        }
+
+        let mut least: Vec<(String, f64)> = report
+            .files
+            .iter()
+            .filter(|(_, hints)| hints.params + hints.functions > 0)
+            .map(|(path, hints)| (path.clone(), hints.coverage()))
+            .collect();
+        least.sort_by(|a, b| a.1.total_cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+        least.truncate(top);
+        report.least_covered = least;
+
+        report
+    }
+}
+
+impl Display for TypeHintReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let total = &self.total;
+        write!(
+            f,
+            "Type hints: {:.1}% covered\n  parameters {}/{} ({:.1}%), returns {}/{} ({:.1}%)\n  Any {}, cast() {}, type: ignore {}",
+            total.coverage(),
+            total.annotated_params,
+            total.params,
+            total.param_coverage(),
+            total.annotated_returns,
+            total.functions,
+            total.return_coverage(),
+            total.any,
+            total.casts,
+            total.type_ignores
+        )?;
+
+        if !self.packages.is_empty() {
+            write!(f, "\nCoverage per package:")?;
+            for (package, hints) in &self.packages {
+                write!(f, "\n  {:>5.1}%  {}", hints.coverage(), package)?;
+            }
+        }
+        if !self.least_covered.is_empty() {
+            write!(f, "\nLeast covered files:")?;
+            for (path, coverage) in &self.least_covered {
+                write!(f, "\n  {:>5.1}%  {}", coverage, path)?;
+            }
+        }
+
+        Ok(())
+    }
+}
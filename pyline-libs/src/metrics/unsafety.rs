@@ -0,0 +1,171 @@
+//! Audit of `unsafe` code in Rust sources.
+
+use crate::metrics::sarif::SarifResult;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+
+/// Kinds of `unsafe` sites.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnsafeKind {
+    /// `unsafe { ... }` block.
+    Block,
+    /// `unsafe fn`.
+    Function,
+    /// `unsafe impl`.
+    Impl,
+    /// `unsafe trait`.
+    Trait,
+    /// `extern` block (`unsafe extern "C" { ... }`, or without `unsafe`
+    /// before the 2024 edition).
+    Extern,
+}
+
+impl Display for UnsafeKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Block => "block",
+            Self::Function => "fn",
+            Self::Impl => "impl",
+            Self::Trait => "trait",
+            Self::Extern => "extern",
+        };
+        f.pad(name)
+    }
+}
+
+/// An `unsafe` site in a Rust file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnsafeSite {
+    /// File the site is in.
+    pub path: PathBuf,
+    /// Line of the `unsafe` (or `extern`) keyword.
+    pub line: usize,
+    /// Line of the closing `}` of a block; the start line for other kinds.
+    pub end_line: usize,
+    /// Kind of the site.
+    pub kind: UnsafeKind,
+    /// A `// SAFETY:` comment is on the lines right above the site or on
+    /// its first line.
+    pub safety_comment: bool,
+}
+
+impl UnsafeSite {
+    /// Returns the `path:line` location of the site.
+    pub fn location(&self) -> String {
+        format!("{}:{}", self.path.display(), self.line)
+    }
+
+    /// Number of lines spanned by the site.
+    pub fn line_count(&self) -> usize {
+        self.end_line.saturating_sub(self.line) + 1
+    }
+
+    /// Returns `true` for an `unsafe` block without a `// SAFETY:` comment.
+    pub fn is_undocumented_block(&self) -> bool {
+        self.kind == UnsafeKind::Block && !self.safety_comment
+    }
+}
+
+/// Summary of the `unsafe` sites.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UnsafeReport {
+    /// Number of sites per kind.
+    pub counts: BTreeMap<UnsafeKind, usize>,
+    /// Lines spanned by `unsafe` blocks (nested blocks are counted again).
+    pub block_lines: usize,
+    /// Number of `unsafe` blocks without a `// SAFETY:` comment.
+    pub undocumented_blocks: usize,
+    /// Sites in file and line order.
+    pub sites: Vec<UnsafeSite>,
+}
+
+impl UnsafeReport {
+    /// Builds the report from the sites of all files.
+    pub fn new(sites: &[UnsafeSite]) -> Self {
+        let mut sites = sites.to_vec();
+        sites.sort_by(|a, b| (&a.path, a.line).cmp(&(&b.path, b.line)));
+
+        let mut counts = BTreeMap::new();
+        let mut block_lines = 0;
+        let mut undocumented_blocks = 0;
+        for site in &sites {
+            *counts.entry(site.kind).or_insert(0) += 1;
+            if site.kind == UnsafeKind::Block {
+                block_lines += site.line_count();
+            }
+            if site.is_undocumented_block() {
+                undocumented_blocks += 1;
+            }
+        }
+
+        Self {
+            counts,
+            block_lines,
+            undocumented_blocks,
+            sites,
+        }
+    }
+
+    /// Converts the `unsafe` blocks without a `// SAFETY:` comment into
+    /// SARIF results.
+    pub fn sarif_results(&self) -> Vec<SarifResult> {
+        self.sites
+            .iter()
+            .filter(|site| site.is_undocumented_block())
+            .map(|site| SarifResult {
+                rule_id: "undocumented-unsafe".to_string(),
+                level: "warning",
+                message: "unsafe block without a `// SAFETY:` comment".to_string(),
+                path: site.path.clone(),
+                line: site.line,
+            })
+            .collect()
+    }
+}
+
+impl Display for UnsafeReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let counts: Vec<String> = self
+            .counts
+            .iter()
+            .map(|(kind, count)| format!("{} {}", kind, count))
+            .collect();
+        write!(f, "Unsafe sites: {}", self.sites.len())?;
+        if !counts.is_empty() {
+            write!(f, " ({})", counts.join(", "))?;
+        }
+        write!(f, "\n  lines in unsafe blocks: {}", self.block_lines)?;
+        write!(
+            f,
+            "\n  blocks without SAFETY comment: {}",
+            self.undocumented_blocks
+        )?;
+
+        let width = self
+            .sites
+            .iter()
+            .map(|s| s.location().len())
+            .max()
+            .unwrap_or(0);
+        for site in &self.sites {
+            write!(
+                f,
+                "\n  {:<6}  {:<w$}",
+                site.kind,
+                site.location(),
+                w = width
+            )?;
+            if site.kind == UnsafeKind::Block {
+                write!(f, "  {} line(s)", site.line_count())?;
+                if !site.safety_comment {
+                    write!(f, "  no SAFETY comment")?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
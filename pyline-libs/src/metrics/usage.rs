@@ -0,0 +1,277 @@
+//! Decorators, imports and dunder methods of Python code.
+//!
+//! Decorators and dunder methods come from the symbol inventory; the engine
+//! records the imports. The report sorts the imported modules into the
+//! standard library, local modules (relative imports and the modules of the
+//! scanned files) and third-party packages.
+
+use crate::metrics::FileMetrics;
+use crate::metrics::symbols::Symbol;
+use crate::py::base::STDLIB_MODULES;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+
+/// An imported module.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Import {
+    /// File the import is in.
+    pub path: PathBuf,
+    /// Line of the import statement.
+    pub line: usize,
+    /// Module path as written: dotted in Python, with its leading dots for
+    /// a relative import, and `::`-separated in Rust.
+    pub module: String,
+    /// Names imported from the module by `from m import a, b` (Python).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub names: Vec<String>,
+    /// The import is a star import (`from m import *`, `use m::*`).
+    #[serde(default)]
+    pub star: bool,
+}
+
+impl Import {
+    /// Returns `true` for a relative import (`from . import m`).
+    pub fn is_relative(&self) -> bool {
+        self.module.starts_with('.')
+    }
+
+    /// First component of the module name (`os` for `os.path`).
+    pub fn top_level(&self) -> &str {
+        self.module.split('.').next().unwrap_or_default()
+    }
+
+    /// Modules counted as used by the import: the top-level name, or the
+    /// relative module as written. The names of `from . import a, b` are
+    /// sibling modules, counted as `.a` and `.b`.
+    fn used_modules(&self) -> Vec<String> {
+        if !self.is_relative() {
+            vec![self.top_level().to_string()]
+        } else if self.module.chars().all(|c| c == '.') && !self.names.is_empty() {
+            self.names
+                .iter()
+                .map(|name| format!("{}{}", self.module, name))
+                .collect()
+        } else {
+            vec![self.module.clone()]
+        }
+    }
+
+    /// Returns the `path:line` location of the import.
+    pub fn location(&self) -> String {
+        format!("{}:{}", self.path.display(), self.line)
+    }
+}
+
+/// Where an imported module comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportOrigin {
+    /// Python standard library.
+    Stdlib,
+    /// Installed package.
+    ThirdParty,
+    /// Relative import or a module of the scanned files.
+    Local,
+}
+
+impl Display for ImportOrigin {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Stdlib => "standard library",
+            Self::ThirdParty => "third-party",
+            Self::Local => "local",
+        };
+        f.pad(name)
+    }
+}
+
+/// Frequency tables of decorators, imported modules and dunder methods.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UsageReport {
+    /// Most used decorators by dotted name, most first.
+    pub decorators: Vec<(String, usize)>,
+    /// Number of import statements per origin.
+    pub import_counts: BTreeMap<ImportOrigin, usize>,
+    /// Most imported modules (top-level name) per origin, most first.
+    pub imports: BTreeMap<ImportOrigin, Vec<(String, usize)>>,
+    /// Most defined dunder methods, most first.
+    pub dunders: Vec<(String, usize)>,
+    /// Star imports.
+    pub star_imports: Vec<Import>,
+    /// Relative imports.
+    pub relative_imports: Vec<Import>,
+    /// Third-party modules imported by each top-level package (or
+    /// directory, for files outside a package).
+    pub dependencies: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl UsageReport {
+    /// Builds the report, keeping the `top` entries of each table.
+    pub fn new(imports: &[Import], symbols: &[Symbol], files: &[FileMetrics], top: usize) -> Self {
+        let mut report = Self::default();
+
+        let mut decorators = BTreeMap::new();
+        let mut dunders = BTreeMap::new();
+        for symbol in symbols {
+            for decorator in &symbol.decorators {
+                *decorators.entry(decorator.clone()).or_insert(0) += 1;
+            }
+            if symbol.kind.is_function() && is_dunder(&symbol.name) {
+                *dunders.entry(symbol.name.clone()).or_insert(0) += 1;
+            }
+        }
+        report.decorators = ranked(decorators, top);
+        report.dunders = ranked(dunders, top);
+
+        let local = LocalModules::new(files);
+        let mut modules: BTreeMap<ImportOrigin, BTreeMap<String, usize>> = BTreeMap::new();
+        for import in imports
+            .iter()
+            .filter(|i| i.path.extension() == Some("py".as_ref()))
+        {
+            let origin = local.origin(import);
+            *report.import_counts.entry(origin).or_insert(0) += 1;
+            for name in import.used_modules() {
+                if origin == ImportOrigin::ThirdParty {
+                    report
+                        .dependencies
+                        .entry(local.package(&import.path))
+                        .or_default()
+                        .insert(name.clone());
+                }
+                *modules.entry(origin).or_default().entry(name).or_insert(0) += 1;
+            }
+
+            if import.star {
+                report.star_imports.push(import.clone());
+            }
+            if import.is_relative() {
+                report.relative_imports.push(import.clone());
+            }
+        }
+        report.imports = modules
+            .into_iter()
+            .map(|(origin, counts)| (origin, ranked(counts, top)))
+            .collect();
+
+        for list in [&mut report.star_imports, &mut report.relative_imports] {
+            list.sort_by(|a, b| (&a.path, a.line).cmp(&(&b.path, b.line)));
+        }
+
+        report
+    }
+}
+
+/// Modules and packages of the scanned files.
+struct LocalModules {
+    /// Module names of the files and package names of their directories.
+    names: HashSet<String>,
+    /// Directories with an `__init__.py`.
+    packages: HashSet<PathBuf>,
+}
+
+impl LocalModules {
+    fn new(files: &[FileMetrics]) -> Self {
+        let mut names = HashSet::new();
+        let mut packages = HashSet::new();
+        for file in files {
+            let stem = file.path.file_stem().and_then(|s| s.to_str());
+            if stem == Some("__init__") {
+                if let Some(dir) = file.path.parent() {
+                    names.extend(dir.file_name().and_then(|n| n.to_str()).map(String::from));
+                    packages.insert(dir.to_path_buf());
+                }
+            } else {
+                names.extend(stem.map(String::from));
+            }
+        }
+        Self { names, packages }
+    }
+
+    fn origin(&self, import: &Import) -> ImportOrigin {
+        let top = import.top_level();
+        if import.is_relative() || self.names.contains(top) {
+            ImportOrigin::Local
+        } else if STDLIB_MODULES.binary_search(&top).is_ok() {
+            ImportOrigin::Stdlib
+        } else {
+            ImportOrigin::ThirdParty
+        }
+    }
+
+    /// Top-level package of the file at `path`, or its directory.
+    fn package(&self, path: &Path) -> String {
+        let dir = path.parent().unwrap_or(Path::new(""));
+        dir.ancestors()
+            .take_while(|dir| self.packages.contains(*dir))
+            .last()
+            .unwrap_or(dir)
+            .display()
+            .to_string()
+    }
+}
+
+/// Returns `true` for a dunder name such as `__init__`.
+fn is_dunder(name: &str) -> bool {
+    name.len() > 4 && name.starts_with("__") && name.ends_with("__")
+}
+
+/// Returns the `top` entries of `counts`, most first, then by name.
+fn ranked(counts: BTreeMap<String, usize>, top: usize) -> Vec<(String, usize)> {
+    let mut entries: Vec<(String, usize)> = counts.into_iter().collect();
+    entries.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    entries.truncate(top);
+    entries
+}
+
+impl Display for UsageReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Decorators:")?;
+        write_table(f, &self.decorators)?;
+
+        let counts: Vec<String> = self
+            .import_counts
+            .iter()
+            .map(|(origin, count)| format!("{} {}", origin, count))
+            .collect();
+        write!(f, "\nImports: {}", counts.join(", "))?;
+        for (origin, modules) in &self.imports {
+            write!(f, "\n  {}:", origin)?;
+            for (name, count) in modules {
+                write!(f, "\n    {:>5}  {}", count, name)?;
+            }
+        }
+
+        write!(f, "\nDunder methods:")?;
+        write_table(f, &self.dunders)?;
+
+        for (title, list) in [
+            ("Star imports", &self.star_imports),
+            ("Relative imports", &self.relative_imports),
+        ] {
+            write!(f, "\n{}: {}", title, list.len())?;
+            for import in list {
+                write!(f, "\n  {}  {}", import.location(), import.module)?;
+            }
+        }
+
+        if !self.dependencies.is_empty() {
+            write!(f, "\nThird-party modules per package:")?;
+            for (package, modules) in &self.dependencies {
+                let modules: Vec<&str> = modules.iter().map(String::as_str).collect();
+                write!(f, "\n  {}: {}", package, modules.join(", "))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn write_table(f: &mut Formatter<'_>, entries: &[(String, usize)]) -> std::fmt::Result {
+    for (name, count) in entries {
+        write!(f, "\n  {:>5}  {}", count, name)?;
+    }
+    Ok(())
+}
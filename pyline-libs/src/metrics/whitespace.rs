@@ -0,0 +1,225 @@
+//! Whitespace hygiene.
+//!
+//! Checked in the same pass as the line counting: trailing whitespace
+//! (including lines of whitespace only), files mixing `\n`, `\r\n` and `\r`
+//! line endings, a missing newline at the end of the file, a UTF-8 byte
+//! order mark, and tabs in the code of Python files (indentation or
+//! between tokens; tabs in strings and comments are fine).
+
+use crate::metrics::FileMetrics;
+use crate::source::{LineEnding, SourceLine};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+
+/// Line numbers listed per finding in the text report.
+const SHOWN_LINES: usize = 5;
+
+/// Whitespace findings of a file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Whitespace {
+    /// Lines ending with whitespace.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trailing_whitespace: Vec<usize>,
+    /// Number of lines per line ending.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub line_endings: BTreeMap<LineEnding, usize>,
+    /// The file does not end with a line ending.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub missing_final_newline: bool,
+    /// The file starts with a UTF-8 byte order mark.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub bom: bool,
+    /// Lines with tabs in the code (Python only).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tabs: Vec<usize>,
+}
+
+impl Whitespace {
+    /// Returns `true` if nothing was recorded.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Returns `true` if the file uses more than one kind of line ending.
+    pub fn has_mixed_line_endings(&self) -> bool {
+        self.line_endings.len() > 1
+    }
+
+    /// Returns `true` if there are any findings.
+    pub fn has_findings(&self) -> bool {
+        !self.trailing_whitespace.is_empty()
+            || self.has_mixed_line_endings()
+            || self.missing_final_newline
+            || self.bom
+            || !self.tabs.is_empty()
+    }
+}
+
+/// Collects the whitespace findings of a file, line by line.
+#[derive(Debug, Default)]
+pub(crate) struct WhitespaceTracker {
+    whitespace: Whitespace,
+    last_ending: Option<LineEnding>,
+    lines: usize,
+}
+
+impl WhitespaceTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Processes a physical line.
+    pub fn line(&mut self, line_no: usize, line: &SourceLine<'_>) {
+        self.lines += 1;
+        if line.text.ends_with(char::is_whitespace) {
+            self.whitespace.trailing_whitespace.push(line_no);
+        }
+        if line.lone_cr > 0 {
+            *self
+                .whitespace
+                .line_endings
+                .entry(LineEnding::Cr)
+                .or_insert(0) += line.lone_cr;
+        }
+        if let Some(ending) = line.ending {
+            *self.whitespace.line_endings.entry(ending).or_insert(0) += 1;
+        }
+        self.last_ending = line.ending;
+    }
+
+    /// Records a line with tabs in its code.
+    pub fn tab(&mut self, line_no: usize) {
+        self.whitespace.tabs.push(line_no);
+    }
+
+    /// Returns the findings; `bom` tells whether the file starts with a
+    /// byte order mark.
+    pub fn finish(mut self, bom: bool) -> Whitespace {
+        self.whitespace.missing_final_newline = self.lines > 0 && self.last_ending.is_none();
+        self.whitespace.bom = bom;
+        self.whitespace
+    }
+}
+
+/// Findings of a file in the [`WhitespaceReport`].
+#[derive(Debug, Clone, Serialize)]
+pub struct FileWhitespace {
+    /// Path of the file.
+    pub path: PathBuf,
+    /// Findings.
+    #[serde(flatten)]
+    pub whitespace: Whitespace,
+}
+
+/// Whitespace findings per file, with their counts.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WhitespaceReport {
+    /// Checked files.
+    pub files_checked: usize,
+    /// Lines with trailing whitespace.
+    pub trailing_whitespace: usize,
+    /// Files with mixed line endings.
+    pub mixed_line_endings: usize,
+    /// Files without a final newline.
+    pub missing_final_newline: usize,
+    /// Files with a byte order mark.
+    pub bom: usize,
+    /// Lines with tabs in Python code.
+    pub tabs: usize,
+    /// Files with findings.
+    pub files: Vec<FileWhitespace>,
+}
+
+impl WhitespaceReport {
+    /// Builds the report.
+    pub fn new(files: &[FileMetrics]) -> Self {
+        let mut report = Self {
+            files_checked: files.len(),
+            ..Default::default()
+        };
+
+        for file in files.iter().filter(|f| f.whitespace.has_findings()) {
+            let whitespace = &file.whitespace;
+            report.trailing_whitespace += whitespace.trailing_whitespace.len();
+            report.mixed_line_endings += usize::from(whitespace.has_mixed_line_endings());
+            report.missing_final_newline += usize::from(whitespace.missing_final_newline);
+            report.bom += usize::from(whitespace.bom);
+            report.tabs += whitespace.tabs.len();
+            report.files.push(FileWhitespace {
+                path: file.path.clone(),
+                whitespace: whitespace.clone(),
+            });
+        }
+        report.files.sort_by(|a, b| a.path.cmp(&b.path));
+
+        report
+    }
+}
+
+/// Formats line numbers, eliding all but the first few.
+fn line_list(lines: &[usize]) -> String {
+    let mut list: Vec<String> = lines
+        .iter()
+        .take(SHOWN_LINES)
+        .map(usize::to_string)
+        .collect();
+    if lines.len() > SHOWN_LINES {
+        list.push("...".to_string());
+    }
+    list.join(", ")
+}
+
+impl Display for WhitespaceReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Whitespace: {} of {} files with findings\n  trailing whitespace: {} lines, mixed line endings: {} files, no final newline: {} files, BOM: {} files, tabs: {} lines",
+            self.files.len(),
+            self.files_checked,
+            self.trailing_whitespace,
+            self.mixed_line_endings,
+            self.missing_final_newline,
+            self.bom,
+            self.tabs
+        )?;
+
+        for file in &self.files {
+            let whitespace = &file.whitespace;
+            write!(f, "\n  {}", file.path.display())?;
+            if !whitespace.trailing_whitespace.is_empty() {
+                write!(
+                    f,
+                    "\n    trailing whitespace: {} lines ({})",
+                    whitespace.trailing_whitespace.len(),
+                    line_list(&whitespace.trailing_whitespace)
+                )?;
+            }
+            if whitespace.has_mixed_line_endings() {
+                let endings: Vec<String> = whitespace
+                    .line_endings
+                    .iter()
+                    .map(|(ending, count)| format!("{} {}", count, ending))
+                    .collect();
+                write!(f, "\n    mixed line endings: {}", endings.join(", "))?;
+            }
+            if whitespace.missing_final_newline {
+                write!(f, "\n    no final newline")?;
+            }
+            if whitespace.bom {
+                write!(f, "\n    UTF-8 byte order mark")?;
+            }
+            if !whitespace.tabs.is_empty() {
+                write!(
+                    f,
+                    "\n    tabs: {} lines ({})",
+                    whitespace.tabs.len(),
+                    line_list(&whitespace.tabs)
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
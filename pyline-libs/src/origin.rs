@@ -0,0 +1,144 @@
+//! Classification of collected files by origin.
+//!
+//! Generated and vendored code is still counted, but kept apart from the
+//! code that is actually maintained by hand, so the totals can be reported
+//! per [`Origin`].
+
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+
+/// Number of leading lines searched for generated-code markers.
+pub const GENERATED_HEADER_LINES: usize = 25;
+
+/// Markers that identify generated files when found in the file header.
+pub const GENERATED_MARKERS: &[&str] = &[
+    "@generated",
+    "Code generated",
+    "DO NOT EDIT",
+    "Generated by Cython",
+    "Generated by the protocol buffer compiler",
+    "AUTO-GENERATED",
+    "autogenerated",
+];
+
+/// File name suffixes of well-known generated files.
+pub const GENERATED_SUFFIXES: &[&str] = &["_pb2.py", "_pb2_grpc.py", "_pb2.pyi", ".pb.rs"];
+
+/// Directory names that hold vendored third-party code.
+pub const VENDORED_DIRS: &[&str] = &[
+    "vendor",
+    "vendored",
+    "third_party",
+    "third-party",
+    "thirdparty",
+    "site-packages",
+    "dist-packages",
+];
+
+/// Where the code in a file comes from.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Origin {
+    /// Code written and maintained by hand.
+    #[default]
+    Authored,
+
+    /// Code produced by a tool (protobuf, Cython, `build.rs` outputs, ...).
+    Generated,
+
+    /// Third-party code copied into the tree.
+    Vendored,
+}
+
+impl Display for Origin {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Self::Authored => "authored",
+            Self::Generated => "generated",
+            Self::Vendored => "vendored",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Classifies a file by its path and, when available, the head of its
+/// content.
+///
+/// Vendored directories take precedence over generated markers: a generated
+/// file inside `vendor/` is still third-party code.
+///
+/// ```
+/// use std::path::Path;
+/// use pyline_libs::origin::{classify, Origin};
+///
+/// assert_eq!(classify(Path::new("src/app.py"), b"import os\n"), Origin::Authored);
+/// assert_eq!(classify(Path::new("api/user_pb2.py"), b""), Origin::Generated);
+/// assert_eq!(classify(Path::new("third_party/six.py"), b""), Origin::Vendored);
+/// ```
+pub fn classify(path: &Path, head: &[u8]) -> Origin {
+    if is_vendored_path(path) {
+        Origin::Vendored
+    } else if is_generated_path(path) || generated_marker(&String::from_utf8_lossy(head)).is_some()
+    {
+        Origin::Generated
+    } else {
+        Origin::Authored
+    }
+}
+
+/// Returns the generated-code marker found in the file header, if any.
+pub fn generated_marker(text: &str) -> Option<&'static str> {
+    text.lines()
+        .take(GENERATED_HEADER_LINES)
+        .find_map(|line| GENERATED_MARKERS.iter().find(|m| line.contains(*m)))
+        .copied()
+}
+
+fn is_vendored_path(path: &Path) -> bool {
+    path.components()
+        .filter_map(|c| c.as_os_str().to_str())
+        .any(|name| VENDORED_DIRS.contains(&name))
+}
+
+/// Well-known generated file names, plus `build.rs` outputs which Cargo
+/// places under `build/<crate>-<hash>/out/`.
+fn is_generated_path(path: &Path) -> bool {
+    let by_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| GENERATED_SUFFIXES.iter().any(|s| name.ends_with(s)));
+
+    let names: Vec<_> = path
+        .components()
+        .filter_map(|c| c.as_os_str().to_str())
+        .collect();
+    let build_out = names.windows(3).any(|w| w[0] == "build" && w[2] == "out");
+
+    by_name || build_out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn header_marker_marks_generated() {
+        let head = b"// Code generated by bindgen. DO NOT EDIT.\nfn x() {}\n";
+        assert_eq!(classify(Path::new("src/ffi.rs"), head), Origin::Generated);
+    }
+
+    #[test]
+    fn build_script_output_is_generated() {
+        let path = Path::new("target/debug/build/foo-1a2b/out/bindings.rs");
+        assert_eq!(classify(path, b""), Origin::Generated);
+    }
+
+    #[test]
+    fn vendored_wins_over_generated() {
+        let path = Path::new("vendor/proto/msg_pb2.py");
+        assert_eq!(classify(path, b"# @generated\n"), Origin::Vendored);
+    }
+}
@@ -1,10 +1,34 @@
 //! Core infrastructure for parsing and analyzing code files.
 
+use crate::metrics::comments::{doc_coverage, ratio};
+use crate::origin::Origin;
 use crate::{define_lang_struct, display_for_lang};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fmt::{Display, Formatter};
 
+/// Totals for the files of a single [`Origin`].
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OriginStat {
+    /// Number of files.
+    pub files: usize,
+    /// Number of lines in files.
+    pub lines: usize,
+    /// Number of code lines.
+    pub code_lines: usize,
+}
+
+impl OriginStat {
+    /// Sums the fields of another instance into this one.
+    pub fn merge_ref(&mut self, other: &OriginStat) {
+        self.files += other.files;
+        self.lines += other.lines;
+        self.code_lines += other.code_lines;
+    }
+}
+
 /// Data structure with statistics of analyzed files.
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct CodeFilesStat {
     /// Number of analyzed files (total).
     pub num_files_total: usize,
@@ -15,15 +39,26 @@ pub struct CodeFilesStat {
     pub lines_total: usize,
     /// Number of code lines.
     pub code_lines: usize,
+    /// Number of lines with a regular comment.
+    #[serde(default)]
+    pub comment_lines: usize,
+    /// Number of lines of doc comments (Rust) or docstrings (Python).
+    #[serde(default)]
+    pub doc_lines: usize,
+    /// Number of public items.
+    #[serde(default)]
+    pub public_items: usize,
+    /// Number of public items with a doc comment or docstring.
+    #[serde(default)]
+    pub documented_items: usize,
+    /// Totals split by the origin of the files.
+    pub by_origin: BTreeMap<Origin, OriginStat>,
 }
 
 impl CodeFilesStat {
     /// Merges another CodeFilesStat instance into this one, summing all fields.
     pub fn merge(&mut self, other: CodeFilesStat) {
-        self.num_files_total += other.num_files_total;
-        self.num_files_not_valid += other.num_files_not_valid;
-        self.lines_total += other.lines_total;
-        self.code_lines += other.code_lines;
+        self.merge_ref(&other);
     }
 
     /// Alternative version that borrows the other instance.
@@ -32,6 +67,48 @@ impl CodeFilesStat {
         self.num_files_not_valid += other.num_files_not_valid;
         self.lines_total += other.lines_total;
         self.code_lines += other.code_lines;
+        self.comment_lines += other.comment_lines;
+        self.doc_lines += other.doc_lines;
+        self.public_items += other.public_items;
+        self.documented_items += other.documented_items;
+        for (origin, stat) in &other.by_origin {
+            self.by_origin.entry(*origin).or_default().merge_ref(stat);
+        }
+    }
+
+    /// Attributes the current totals of a single-file instance to `origin`.
+    ///
+    /// Called once per parsed file, after all of its lines were counted.
+    pub fn record_origin(&mut self, origin: Origin) {
+        let stat = OriginStat {
+            files: self.num_files_total,
+            lines: self.lines_total,
+            code_lines: self.code_lines,
+        };
+        self.by_origin.entry(origin).or_default().merge_ref(&stat);
+    }
+
+    /// Comment lines per code line.
+    pub fn comment_ratio(&self) -> f64 {
+        ratio(self.comment_lines, self.code_lines)
+    }
+
+    /// Doc lines per code line.
+    pub fn doc_ratio(&self) -> f64 {
+        ratio(self.doc_lines, self.code_lines)
+    }
+
+    /// Percentage of public items with documentation; `None` without
+    /// public items.
+    pub fn doc_coverage(&self) -> Option<f64> {
+        doc_coverage(self.documented_items, self.public_items)
+    }
+
+    /// Returns `true` if any generated or vendored files were counted.
+    pub fn has_foreign_code(&self) -> bool {
+        self.by_origin
+            .keys()
+            .any(|origin| *origin != Origin::Authored)
     }
 
     /// Consumes both instances and returns a new merged instance
@@ -48,6 +125,34 @@ impl Display for CodeFilesStat {
         writeln!(f, "Files: {}", self.num_files_total)?;
         writeln!(f, "Lines: {}", self.lines_total)?;
         write!(f, "  of which are code lines: {}", self.code_lines)?;
+        write!(
+            f,
+            "\n  comment lines: {}, doc lines: {}",
+            self.comment_lines, self.doc_lines
+        )?;
+        write!(
+            f,
+            "\nComment/code ratio: {:.2}, doc/code ratio: {:.2}",
+            self.comment_ratio(),
+            self.doc_ratio()
+        )?;
+        if let Some(coverage) = self.doc_coverage() {
+            write!(
+                f,
+                "\nDocumented public items: {}/{} ({:.1}%)",
+                self.documented_items, self.public_items, coverage
+            )?;
+        }
+        if self.has_foreign_code() {
+            write!(f, "\nBy origin:")?;
+            for (origin, stat) in &self.by_origin {
+                write!(
+                    f,
+                    "\n  {}: files {}, lines {}, code lines {}",
+                    origin, stat.files, stat.lines, stat.code_lines
+                )?;
+            }
+        }
         if self.num_files_not_valid > 0 {
             write!(f, "\nFailed to read files: {}", self.num_files_not_valid)?;
         }
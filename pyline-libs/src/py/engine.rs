@@ -1,16 +1,36 @@
 use crate::collector::FileData;
 use crate::errors::PyLineError;
 use crate::impl_lang_parser;
+use crate::metrics::comments::{Documentation, count_public_items};
+use crate::metrics::duplicates::CodeLine;
+use crate::metrics::halstead::HalsteadTracker;
+use crate::metrics::line_length::LineWidth;
+use crate::metrics::tasks::{TaskMarker, find_marker};
+use crate::metrics::usage::Import;
+use crate::metrics::whitespace::WhitespaceTracker;
 use crate::parser::Python;
 use crate::py::base::{KEYWORDS, PyKeywords};
-use crate::py::py_methods::is_triple_quotes;
-use crate::traits::CodeParsers;
+use crate::py::halstead::halstead_tokens;
+use crate::py::hints::HintTracker;
+use crate::py::py_methods::{
+    LineState, imported_modules, is_noqa_line_length, is_triple_quotes, scan_line,
+};
+use crate::py::scopes::ScopeTracker;
+use crate::source::SourceText;
+use crate::traits::{CodeParsers, LangDefinition};
 use std::collections::HashMap;
-use tokio::fs::File;
-use tokio::io::{AsyncBufReadExt, BufReader};
 
 impl_lang_parser!(Python);
 
+impl LangDefinition for Python {
+    const NAME: &'static str = "python";
+    const ENGINE_REVISION: u32 = 17;
+
+    fn keywords() -> Vec<&'static str> {
+        KEYWORDS.keys().copied().collect()
+    }
+}
+
 /// Result of parsing a Python source line.
 enum PythonResult {
     /// Successfully parsed code containing Python keywords.
@@ -33,19 +53,114 @@ impl Python {
     ///
     /// Analyzes each line to identify code lines, comments, and Python
     /// keywords, updating the provided statistics structure accordingly.
+    /// Logical lines are passed to the [`ScopeTracker`] to build the symbol
+    /// inventory and measure the nesting depth of the file; their imports
+    /// are recorded for the usage report. The [`HintTracker`] counts the
+    /// type hints. Code lines are hashed for duplicate detection and split
+    /// into the operators and operands of the [`HalsteadTracker`]. Lines
+    /// with comments and docstrings (as told by the [`ScopeTracker`]) are
+    /// counted for the documentation ratios. The width of every line is measured for the line-length
+    /// report, lines with `# noqa: E501` being exempt, and checked for
+    /// whitespace issues by the [`WhitespaceTracker`].
     async fn parse_code_lines(
-        cursor: BufReader<File>,
+        source: &SourceText,
         code_stats: &mut Python,
+        file: &FileData,
     ) -> Result<(), PyLineError> {
         let mut triple_quotes: Option<char> = None;
+        let mut state = LineState::default();
+        let mut scopes = ScopeTracker::new(&file.path);
+        let mut hints = HintTracker::new();
+        let mut code_lines = Vec::new();
+        let mut halstead = HalsteadTracker::new();
+        let mut line_widths = Vec::new();
+        let mut whitespace = WhitespaceTracker::new();
+        let mut line_no = 0;
+        // Logical line of an import statement, from its first line.
+        let mut import_line: Option<(usize, String)> = None;
+        // The current logical line is a docstring.
+        let mut in_docstring = false;
 
-        let mut lines = cursor.lines();
-        while let Some(line) = lines.next_line().await? {
+        for source_line in source.lines() {
             code_stats.count_line();
+            line_no += 1;
+            whitespace.line(line_no, &source_line);
+            let line = source_line.text;
 
-            match Self::parse_line(&line, triple_quotes) {
+            let scanned = scan_line(line, state);
+            if state.is_logical_start() {
+                in_docstring = scopes.logical_line(line_no, &scanned.code);
+                if scanned.code.contains("import") {
+                    import_line = Some((line_no, scanned.code.clone()));
+                }
+            } else {
+                if let Some((_, code)) = &mut import_line {
+                    code.push(' ');
+                    code.push_str(&scanned.code);
+                }
+                if !line.trim().is_empty() {
+                    scopes.touch(line_no);
+                }
+            }
+            if scanned.state.is_logical_start()
+                && let Some((line, code)) = import_line.take()
+            {
+                for imported in imported_modules(&code) {
+                    code_stats.metrics.imports.push(Import {
+                        path: file.path.clone(),
+                        line,
+                        module: imported.module,
+                        names: imported.names,
+                        star: imported.star,
+                    });
+                }
+            }
+            scopes.lambdas(line_no, &scanned.code);
+            scopes.decisions(&scanned.code, state.is_logical_start());
+            hints.line(
+                &scanned.code,
+                scanned.comment.as_deref(),
+                scanned.state.is_logical_start(),
+            );
+            if scanned.comment.is_some() {
+                code_stats.count_comment_line();
+            }
+            if scanned.code.contains('\t') {
+                whitespace.tab(line_no);
+            }
+            line_widths.extend(LineWidth::new(
+                line_no,
+                line,
+                scanned.comment.as_deref().is_some_and(is_noqa_line_length),
+            ));
+            let docstring = scanned.docstring.as_ref().filter(|_| in_docstring);
+            if docstring.is_some() {
+                code_stats.count_doc_line();
+            }
+            for text in [scanned.comment.as_ref(), docstring].into_iter().flatten() {
+                if let Some((tag, author, text)) = find_marker(text) {
+                    code_stats.metrics.tasks.push(TaskMarker {
+                        path: file.path.clone(),
+                        line: line_no,
+                        tag,
+                        author,
+                        text,
+                    });
+                }
+            }
+            state = scanned.state;
+
+            match Self::parse_line(line, triple_quotes) {
                 PythonResult::Code(stat) => {
                     code_stats.count_code_line();
+                    code_lines.extend(CodeLine::new(
+                        line_no,
+                        line,
+                        scanned.comment.as_deref(),
+                        &scanned.code,
+                        |word| KEYWORDS.contains_key(word),
+                    ));
+                    halstead.line(line_no, halstead_tokens(&scanned.code));
 
                     for (k, v) in stat {
                         *code_stats.keywords.entry(k.to_string()).or_insert(0) += v;
@@ -61,6 +176,21 @@ impl Python {
             };
         }
 
+        let (mut symbols, mut file_metrics) = scopes.finish();
+        file_metrics.type_hints = hints.finish();
+        file_metrics.halstead = halstead.finish(&mut symbols);
+        (
+            code_stats.stats.public_items,
+            code_stats.stats.documented_items,
+        ) = count_public_items(&symbols);
+        file_metrics.documentation = Documentation::from(&code_stats.stats);
+        file_metrics.code_lines = code_lines;
+        file_metrics.line_widths = line_widths;
+        file_metrics.encoding = source.encoding().clone();
+        file_metrics.whitespace = whitespace.finish(source.has_utf8_bom());
+        code_stats.metrics.symbols = symbols;
+        code_stats.metrics.files.push(file_metrics);
+
         Ok(())
     }
 
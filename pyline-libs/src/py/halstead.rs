@@ -0,0 +1,95 @@
+//! Splitting of Python code into Halstead operators and operands.
+
+use crate::metrics::halstead::HalsteadToken;
+use crate::py::base::KEYWORDS;
+
+/// Operators of three and two characters, longest first.
+const COMPOUND_OPERATORS: &[&str] = &[
+    "**=", "//=", ">>=", "<<=", "...", "**", "//", ">>", "<<", "<=", ">=", "==", "!=", "->", ":=",
+    "+=", "-=", "*=", "/=", "%=", "&=", "|=", "^=", "@=",
+];
+
+/// Keyword constants, counted as operands.
+const CONSTANTS: &[&str] = &["false", "none", "true"];
+
+/// Letters of string prefixes (`r""`, `f""`, `b""`, `rb""`, ...).
+const STRING_PREFIX: &[char] = &['r', 'b', 'f', 'u', 't', 'R', 'B', 'F', 'U', 'T'];
+
+/// Classifies the tokens of scanned code: keywords and punctuation are
+/// operators, names, numbers and strings (with their prefix) operands.
+pub(crate) fn halstead_tokens(code: &str) -> Vec<HalsteadToken<'_>> {
+    let mut tokens = Vec::new();
+    let mut chars = code.char_indices().peekable();
+
+    while let Some((start, ch)) = chars.next() {
+        if ch.is_whitespace() || matches!(ch, ')' | ']' | '}' | '\\') {
+            continue;
+        }
+
+        if ch.is_alphanumeric() || ch == '_' || ch == '"' || ch == '\'' {
+            let mut end = start + ch.len_utf8();
+            let mut in_string = ch == '"' || ch == '\'';
+            while let Some(&(i, c)) = chars.peek() {
+                let word_char =
+                    c.is_alphanumeric() || c == '_' || (ch.is_ascii_digit() && c == '.');
+                let quote = c == '"' || c == '\'';
+                let prefix =
+                    !in_string && code[start..end].chars().all(|p| STRING_PREFIX.contains(&p));
+                if !((quote && (in_string || prefix)) || (word_char && !in_string)) {
+                    break;
+                }
+                in_string |= quote;
+                end = i + c.len_utf8();
+                chars.next();
+            }
+
+            let word = &code[start..end];
+            let keyword = KEYWORDS.contains_key(word) && !CONSTANTS.contains(&word);
+            tokens.push(if keyword {
+                HalsteadToken::Operator(word)
+            } else {
+                HalsteadToken::Operand(word)
+            });
+            continue;
+        }
+
+        let rest = &code[start..];
+        let len = COMPOUND_OPERATORS
+            .iter()
+            .find(|op| rest.starts_with(*op))
+            .map_or(ch.len_utf8(), |op| op.len());
+        for _ in 1..len {
+            chars.next();
+        }
+        tokens.push(HalsteadToken::Operator(&code[start..start + len]));
+    }
+
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use HalsteadToken::{Operand, Operator};
+
+    #[test]
+    fn operators_and_operands() {
+        assert_eq!(
+            halstead_tokens("    x **= f(y, rb'') if not None else 1.5"),
+            vec![
+                Operand("x"),
+                Operator("**="),
+                Operand("f"),
+                Operator("("),
+                Operand("y"),
+                Operator(","),
+                Operand("rb''"),
+                Operator("if"),
+                Operator("not"),
+                Operand("None"),
+                Operator("else"),
+                Operand("1.5"),
+            ]
+        );
+    }
+}
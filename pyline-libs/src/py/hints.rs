@@ -0,0 +1,156 @@
+//! Counting of type hints for the type-hint coverage.
+
+use crate::metrics::type_hints::TypeHints;
+use crate::py::py_methods::words;
+
+/// Parameters without a type: the receiver of methods and class methods.
+const RECEIVERS: &[&str] = &["self", "cls"];
+
+/// Counts the type hints of a file from its logical lines and comments.
+///
+/// A `def` statement is read as a whole logical line, so signatures split
+/// over several physical lines are counted once. `Any` is counted per
+/// logical line too, leaving out `import` and `from ... import` statements.
+pub(crate) struct HintTracker {
+    /// Counts so far.
+    hints: TypeHints,
+    /// Code of the logical line being read.
+    logical: String,
+}
+
+impl HintTracker {
+    pub fn new() -> Self {
+        Self {
+            hints: TypeHints::default(),
+            logical: String::new(),
+        }
+    }
+
+    /// Processes the code and comment of a physical line; `complete` is
+    /// `true` if the line ends its logical line.
+    pub fn line(&mut self, code: &str, comment: Option<&str>, complete: bool) {
+        self.hints.casts += cast_calls(code);
+        if comment.is_some_and(is_type_ignore) {
+            self.hints.type_ignores += 1;
+        }
+
+        self.logical.push_str(code);
+        self.logical.push('\n');
+        if complete {
+            let logical = std::mem::take(&mut self.logical);
+            self.hints.any += logical
+                .split(';')
+                .filter(|statement| !is_import(statement))
+                .map(|statement| words(statement).filter(|w| *w == "Any").count())
+                .sum::<usize>();
+            self.signature(&logical);
+        }
+    }
+
+    /// Returns the counts.
+    pub fn finish(self) -> TypeHints {
+        self.hints
+    }
+
+    /// Counts the annotations of a `def` statement.
+    fn signature(&mut self, logical: &str) {
+        let statement = logical.trim_start();
+        let statement = match statement.strip_prefix("async") {
+            Some(rest) if rest.starts_with(char::is_whitespace) => rest.trim_start(),
+            _ => statement,
+        };
+        let Some(rest) = statement
+            .strip_prefix("def")
+            .filter(|rest| rest.starts_with(char::is_whitespace))
+        else {
+            return;
+        };
+        let Some(open) = rest.find('(') else {
+            return;
+        };
+
+        let mut depth = 0;
+        let mut close = None;
+        for (i, ch) in rest[open..].char_indices() {
+            match ch {
+                '(' | '[' | '{' => depth += 1,
+                ')' | ']' | '}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        close = Some(open + i);
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+        let Some(close) = close else {
+            return;
+        };
+
+        self.hints.functions += 1;
+        if rest[close + 1..].trim_start().starts_with("->") {
+            self.hints.annotated_returns += 1;
+        }
+        for param in split_top_level(&rest[open + 1..close]) {
+            let param = param.trim().trim_start_matches('*').trim_start();
+            let name_end = param
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(param.len());
+            let name = &param[..name_end];
+            if name.is_empty() || RECEIVERS.contains(&name) {
+                continue;
+            }
+            self.hints.params += 1;
+            if param[name_end..].trim_start().starts_with(':') {
+                self.hints.annotated_params += 1;
+            }
+        }
+    }
+}
+
+/// Splits a parameter list at its top-level commas.
+fn split_top_level(params: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, ch) in params.char_indices() {
+        match ch {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(&params[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&params[start..]);
+    parts
+}
+
+/// Number of `cast(` calls in code, including `typing.cast(`.
+fn cast_calls(code: &str) -> usize {
+    code.match_indices("cast")
+        .filter(|(i, _)| {
+            let before = code[..*i].chars().next_back();
+            let after = code[i + 4..].trim_start();
+            !before.is_some_and(|c| c.is_alphanumeric() || c == '_') && after.starts_with('(')
+        })
+        .count()
+}
+
+/// Returns `true` for a `# type: ignore` comment, with or without error
+/// codes (`# type: ignore[attr-defined]`).
+fn is_type_ignore(comment: &str) -> bool {
+    comment.split('#').any(|part| {
+        part.trim_start()
+            .strip_prefix("type:")
+            .is_some_and(|rest| rest.trim_start().starts_with("ignore"))
+    })
+}
+
+/// Returns `true` if the statement is an `import` or `from ... import`.
+fn is_import(statement: &str) -> bool {
+    matches!(words(statement).next(), Some("import" | "from"))
+}
@@ -4,10 +4,16 @@
 //! - [`base`] — Basic data structures, enums, and constants shared across all parsers
 //! - [`engine`] — Core parsing algorithms and state machines (language-independent logic)
 //! - [`py_methods`] — Python-specific parsing logic and keyword handling
+//! - [`scopes`] — indentation-based scope tracking for the symbol inventory
+//! - [`hints`] — counting of type hints for the type-hint coverage
+//! - [`halstead`] — splitting of code into Halstead operators and operands
 //!
 //! The architecture separates language-agnostic infrastructure from language-specific
 //! implementations, enabling easy extension to new programming languages.
 pub mod base;
 #[macro_use]
 pub(crate) mod engine;
+pub(crate) mod halstead;
+pub(crate) mod hints;
 pub(crate) mod py_methods;
+pub(crate) mod scopes;
@@ -33,3 +33,308 @@ where
     iter.next_if_eq(&(index + 1, *quote)).is_some()
         && iter.next_if_eq(&(index + 1, *quote)).is_some()
 }
+
+/// Lexical state carried from one physical line to the next.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct LineState {
+    /// Number of open brackets (`(`, `[`, `{`).
+    pub brackets: usize,
+    /// Quote character of an open triple-quoted string.
+    pub string: Option<char>,
+    /// The line ended with an explicit line continuation (`\`).
+    pub backslash: bool,
+}
+
+impl LineState {
+    /// Returns `true` if the next physical line starts a new logical line.
+    pub fn is_logical_start(&self) -> bool {
+        self.brackets == 0 && self.string.is_none() && !self.backslash
+    }
+}
+
+/// A physical line with string contents and comments removed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScannedLine {
+    /// The code of the line. Indentation is kept, string literals are
+    /// reduced to their quotes (`""`) and the comment is dropped.
+    pub code: String,
+    /// The comment of the line, without the leading `#`.
+    pub comment: Option<String>,
+    /// Text inside triple-quoted strings (docstrings) on the line.
+    pub docstring: Option<String>,
+    /// State after the line.
+    pub state: LineState,
+}
+
+/// Scans a physical line, starting in `state`.
+///
+/// Unlike the keyword parser, the scanner knows about single-line strings,
+/// so `#` and brackets inside them are not taken for comments or code.
+pub fn scan_line(line: &str, mut state: LineState) -> ScannedLine {
+    let chars: Vec<char> = line.chars().collect();
+    let mut code = String::with_capacity(line.len());
+    let mut comment = None;
+    let mut docstring: Option<String> = None;
+    state.backslash = false;
+
+    let mut i = 0;
+    while i < chars.len() {
+        let ch = chars[i];
+
+        if let Some(quote) = state.string {
+            if is_triple_at(&chars, i, quote) {
+                state.string = None;
+                code.extend([quote; 3]);
+                i += 3;
+                continue;
+            }
+            let text = docstring.get_or_insert_default();
+            text.push(ch);
+            if ch == '\\' {
+                text.extend(chars.get(i + 1));
+                i += 1;
+            }
+            i += 1;
+            continue;
+        }
+
+        match ch {
+            '#' => {
+                comment = Some(chars[i + 1..].iter().collect());
+                break;
+            }
+            '\'' | '"' if is_triple_at(&chars, i, ch) => {
+                state.string = Some(ch);
+                code.extend([ch; 3]);
+                i += 3;
+                continue;
+            }
+            '\'' | '"' => {
+                code.push(ch);
+                i += 1;
+                while i < chars.len() && chars[i] != ch {
+                    if chars[i] == '\\' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+                if i < chars.len() {
+                    code.push(ch);
+                }
+                i += 1;
+                continue;
+            }
+            '(' | '[' | '{' => state.brackets += 1,
+            ')' | ']' | '}' => state.brackets = state.brackets.saturating_sub(1),
+            '\\' if i + 1 == chars.len() => state.backslash = true,
+            _ => {}
+        }
+
+        code.push(ch);
+        i += 1;
+    }
+
+    ScannedLine {
+        code,
+        comment,
+        docstring,
+        state,
+    }
+}
+
+/// Width of the leading whitespace of a line; tabs advance to the next
+/// multiple of 8, as in the Python tokenizer.
+pub fn indent_width(line: &str) -> usize {
+    let mut width = 0;
+    for ch in line.chars() {
+        match ch {
+            ' ' => width += 1,
+            '\t' => width = (width / 8 + 1) * 8,
+            '\u{000C}' => width = 0,
+            _ => break,
+        }
+    }
+    width
+}
+
+/// Splits code into identifier-like words.
+pub fn words(code: &str) -> impl Iterator<Item = &str> {
+    code.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|w| !w.is_empty())
+}
+
+/// Returns `true` if a comment (without its leading `#`) holds a `noqa`
+/// marker covering `E501` (line too long): a bare `# noqa`, or one listing
+/// `E501` among its codes, as flake8 reads it.
+pub fn is_noqa_line_length(comment: &str) -> bool {
+    comment.split('#').any(|part| {
+        let part = part.trim_start();
+        let Some(rest) = part
+            .get(..4)
+            .filter(|word| word.eq_ignore_ascii_case("noqa"))
+        else {
+            return false;
+        };
+        match part[rest.len()..].trim_start().strip_prefix(':') {
+            Some(codes) => codes
+                .split([',', ' ', '\t'])
+                .any(|code| code.eq_ignore_ascii_case("E501")),
+            None => true,
+        }
+    })
+}
+
+/// A module imported by an import statement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportedModule {
+    /// Dotted name, with its leading dots for a relative import.
+    pub module: String,
+    /// Names imported from the module (`a` and `b` in
+    /// `from m import a, b as c`).
+    pub names: Vec<String>,
+    /// Star import (`from m import *`).
+    pub star: bool,
+}
+
+/// Modules imported by the statements of a logical line, separated by `;`.
+pub fn imported_modules(code: &str) -> Vec<ImportedModule> {
+    code.split(';').flat_map(statement_imports).collect()
+}
+
+/// Modules imported by a single statement.
+fn statement_imports(statement: &str) -> Vec<ImportedModule> {
+    let statement = statement.trim();
+    let is_module = |name: &str| {
+        !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '_' || c == '.')
+    };
+
+    if let Some(rest) = keyword_rest(statement, "import") {
+        return rest
+            .split(',')
+            .filter_map(|part| part.split_whitespace().next())
+            .filter(|name| is_module(name))
+            .map(|name| ImportedModule {
+                module: name.to_string(),
+                names: Vec::new(),
+                star: false,
+            })
+            .collect();
+    }
+
+    if let Some(rest) = keyword_rest(statement, "from") {
+        let rest = rest.trim_start();
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let (module, names) = rest.split_at(end);
+        if is_module(module)
+            && let Some(names) = keyword_rest(names.trim_start(), "import")
+        {
+            let names: Vec<&str> = names
+                .split([',', '(', ')'])
+                .filter_map(|part| part.split_whitespace().next())
+                .collect();
+            return vec![ImportedModule {
+                module: module.to_string(),
+                star: names.contains(&"*"),
+                names: names
+                    .into_iter()
+                    .filter(|name| *name != "*" && !name.contains('.') && is_module(name))
+                    .map(String::from)
+                    .collect(),
+            }];
+        }
+    }
+
+    Vec::new()
+}
+
+/// Returns what follows `keyword` at the start of `statement`, if it is
+/// followed by whitespace.
+fn keyword_rest<'a>(statement: &'a str, keyword: &str) -> Option<&'a str> {
+    statement
+        .strip_prefix(keyword)
+        .filter(|rest| rest.starts_with(char::is_whitespace))
+}
+
+fn is_triple_at(chars: &[char], i: usize, quote: char) -> bool {
+    chars.get(i) == Some(&quote)
+        && chars.get(i + 1) == Some(&quote)
+        && chars.get(i + 2) == Some(&quote)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strings_hide_comments_and_brackets() {
+        let line = scan_line("x = f('#', [1,  # note", LineState::default());
+        assert_eq!(line.code, "x = f('', [1,  ");
+        assert_eq!(line.comment.as_deref(), Some(" note"));
+        assert_eq!(line.state.brackets, 2);
+        assert!(!line.state.is_logical_start());
+    }
+
+    #[test]
+    fn triple_quotes_span_lines() {
+        let first = scan_line("s = \"\"\"doc (", LineState::default());
+        assert_eq!(first.state.string, Some('"'));
+
+        let last = scan_line("end\"\"\" + g(", first.state);
+        assert_eq!(last.code, "\"\"\" + g(");
+        assert_eq!(last.docstring.as_deref(), Some("end"));
+        assert_eq!(last.state.string, None);
+        assert_eq!(last.state.brackets, 1);
+    }
+
+    #[test]
+    fn noqa_markers() {
+        assert!(is_noqa_line_length(" noqa"));
+        assert!(is_noqa_line_length(" NOQA: E501,W291"));
+        assert!(is_noqa_line_length(" type: ignore  # noqa:E501"));
+        assert!(!is_noqa_line_length(" noqa: F401"));
+        assert!(!is_noqa_line_length(" see noqa docs"));
+    }
+
+    #[test]
+    fn backslash_continues_line() {
+        let line = scan_line("x = 1 + \\", LineState::default());
+        assert!(line.state.backslash);
+        assert!(!line.state.is_logical_start());
+    }
+
+    #[test]
+    fn tabs_expand_to_eight() {
+        assert_eq!(indent_width("\tx"), 8);
+        assert_eq!(indent_width("  \tx"), 8);
+        assert_eq!(indent_width("    x"), 4);
+    }
+
+    #[test]
+    fn imports_and_from_imports() {
+        let imported = |module: &str, names: &[&str], star: bool| ImportedModule {
+            module: module.to_string(),
+            names: names.iter().map(|n| n.to_string()).collect(),
+            star,
+        };
+        assert_eq!(
+            imported_modules("import os.path as p, sys"),
+            vec![imported("os.path", &[], false), imported("sys", &[], false)]
+        );
+        assert_eq!(
+            imported_modules("    from ..models import *"),
+            vec![imported("..models", &[], true)]
+        );
+        assert_eq!(
+            imported_modules("from . import (views, urls as u,  )"),
+            vec![imported(".", &["views", "urls"], false)]
+        );
+        assert_eq!(
+            imported_modules("import a; from b import c"),
+            vec![imported("a", &[], false), imported("b", &["c"], false)]
+        );
+        assert!(imported_modules("important = 1").is_empty());
+    }
+}
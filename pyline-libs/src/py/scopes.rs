@@ -0,0 +1,260 @@
+//! Indentation-based scope tracking for the Python symbol inventory and
+//! nesting depth.
+
+use crate::metrics::FileMetrics;
+use crate::metrics::nesting::DepthStat;
+use crate::metrics::symbols::{Symbol, SymbolKind};
+use crate::py::py_methods::{indent_width, words};
+use std::path::{Path, PathBuf};
+
+/// Words that add a decision point to the enclosing function: branches,
+/// loops (including comprehension clauses), handlers and boolean operators.
+const DECISION_WORDS: &[&str] = &["if", "elif", "for", "while", "except", "and", "or"];
+
+/// Builds the symbol inventory of a file from its logical lines.
+///
+/// A scope opened by `def` or `class` lasts until a logical line with the
+/// same or smaller indentation; its end is the last non-blank line before
+/// it, so trailing comments and blank lines are not counted.
+///
+/// The nesting depth of a logical line is the number of indentation levels
+/// above it, as in the `INDENT`/`DEDENT` tokens of the Python tokenizer;
+/// continuation lines take the depth of their logical line.
+pub(crate) struct ScopeTracker {
+    /// File being parsed.
+    path: PathBuf,
+    /// Symbols found so far.
+    symbols: Vec<Symbol>,
+    /// Base depth and nesting of each symbol, aligned with `symbols`.
+    nesting: Vec<(usize, DepthStat)>,
+    /// Widths of the open indentation levels.
+    indents: Vec<usize>,
+    /// Indentation character of the first indented line.
+    indent_style: Option<char>,
+    /// Metrics of the whole file.
+    file: FileMetrics,
+    /// Open scopes: indentation and index into `symbols`.
+    open: Vec<(usize, usize)>,
+    /// Decorators waiting for their `def` or `class`.
+    decorators: Vec<String>,
+    /// Last line that belongs to the open scopes.
+    last_line: usize,
+    /// Indentation and index of the `def` or `class` whose first statement
+    /// has not been read yet.
+    awaiting_body: Option<(usize, usize)>,
+    /// The first statement of the module has been read.
+    module_started: bool,
+}
+
+impl ScopeTracker {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            symbols: Vec::new(),
+            nesting: Vec::new(),
+            indents: Vec::new(),
+            indent_style: None,
+            file: FileMetrics::new(path),
+            open: Vec::new(),
+            decorators: Vec::new(),
+            last_line: 0,
+            awaiting_body: None,
+            module_started: false,
+        }
+    }
+
+    /// Records a line that continues the current logical line or string.
+    pub fn touch(&mut self, line_no: usize) {
+        self.last_line = line_no;
+    }
+
+    /// Processes the first physical line of a logical line (comments and
+    /// strings already removed by the scanner).
+    ///
+    /// Returns `true` if the line starts a docstring: a string literal as
+    /// the first statement of the module or of a `def` or `class` body.
+    pub fn logical_line(&mut self, line_no: usize, code: &str) -> bool {
+        let statement = code.trim();
+        if statement.is_empty() {
+            return false;
+        }
+
+        let indent = indent_width(code);
+        let module_start = !std::mem::replace(&mut self.module_started, true);
+        let body_start = match self.awaiting_body.take() {
+            Some((header_indent, idx)) if indent > header_indent => Some(idx),
+            _ => None,
+        };
+        let docstring = (module_start || body_start.is_some()) && is_docstring(statement);
+        if let Some(idx) = body_start {
+            self.symbols[idx].documented = docstring;
+        }
+
+        self.statement(line_no, code, statement, indent);
+        docstring
+    }
+
+    /// Records a logical line: its nesting, decorators and the `def` or
+    /// `class` it opens.
+    fn statement(&mut self, line_no: usize, code: &str, statement: &str, indent: usize) {
+        self.close(indent);
+        self.touch(line_no);
+        self.check_indentation(line_no, code);
+
+        let depth = self.depth(indent);
+        self.file.nesting.record(line_no, depth);
+        if let Some(&(_, idx)) = self.open.last()
+            && self.symbols[idx].kind.is_function()
+        {
+            let (base, stat) = &mut self.nesting[idx];
+            stat.record(line_no, depth.saturating_sub(*base));
+        }
+
+        if let Some(decorator) = statement.strip_prefix('@') {
+            self.decorators.push(decorator_name(decorator));
+            return;
+        }
+
+        let (is_async, header) = match statement.strip_prefix("async") {
+            Some(rest) if rest.starts_with(char::is_whitespace) => (true, rest.trim_start()),
+            _ => (false, statement),
+        };
+        let (is_class, rest) = if let Some(rest) = header.strip_prefix("def") {
+            (false, rest)
+        } else if let Some(rest) = header.strip_prefix("class") {
+            (true, rest)
+        } else {
+            self.decorators.clear();
+            return;
+        };
+        if !rest.starts_with(char::is_whitespace) {
+            self.decorators.clear();
+            return;
+        }
+        let Some(name) = words(rest).next() else {
+            return;
+        };
+
+        let parent = self.open.last().map(|&(_, idx)| self.symbols[idx].kind);
+        let kind = match (is_class, parent) {
+            (true, _) => SymbolKind::Class,
+            (false, None) => SymbolKind::Function,
+            (false, Some(SymbolKind::Class)) => SymbolKind::Method,
+            (false, Some(_)) => SymbolKind::NestedFunction,
+        };
+
+        let mut symbol = Symbol::new(self.path.clone(), name, kind, line_no);
+        symbol.is_async = is_async;
+        symbol.decorators = std::mem::take(&mut self.decorators);
+
+        self.open.push((indent, self.symbols.len()));
+        self.awaiting_body = Some((indent, self.symbols.len()));
+        self.push(symbol, depth);
+    }
+
+    /// Records the `lambda` expressions of a line.
+    pub fn lambdas(&mut self, line_no: usize, code: &str) {
+        for _ in words(code).filter(|w| *w == "lambda") {
+            let symbol = Symbol::new(self.path.clone(), "<lambda>", SymbolKind::Lambda, line_no);
+            self.push(symbol, 0);
+        }
+    }
+
+    /// Adds the decision points of a line to the innermost open scope, if
+    /// it is a function. `case` counts only at the start of a logical line,
+    /// where it begins a `match` arm.
+    pub fn decisions(&mut self, code: &str, logical: bool) {
+        let Some(&(_, idx)) = self.open.last() else {
+            return;
+        };
+        if !self.symbols[idx].kind.is_function() {
+            return;
+        }
+
+        let mut count = words(code).filter(|w| DECISION_WORDS.contains(w)).count();
+        if logical && words(code).next() == Some("case") {
+            count += 1;
+        }
+        self.symbols[idx].complexity += count;
+    }
+
+    /// Closes the remaining scopes at the end of the file and returns the
+    /// symbols with the metrics of the file.
+    pub fn finish(mut self) -> (Vec<Symbol>, FileMetrics) {
+        self.close(0);
+        for (symbol, (_, stat)) in self.symbols.iter_mut().zip(&self.nesting) {
+            symbol.max_nesting = stat.max;
+            symbol.avg_nesting = stat.avg();
+        }
+        (self.symbols, self.file)
+    }
+
+    fn push(&mut self, symbol: Symbol, depth: usize) {
+        self.symbols.push(symbol);
+        self.nesting.push((depth, DepthStat::default()));
+    }
+
+    /// Updates the indentation levels with a logical line indented `indent`
+    /// and returns its depth.
+    fn depth(&mut self, indent: usize) -> usize {
+        while self.indents.last().is_some_and(|&open| open > indent) {
+            self.indents.pop();
+        }
+        if indent > self.indents.last().copied().unwrap_or(0) {
+            self.indents.push(indent);
+        }
+        self.indents.len()
+    }
+
+    /// Flags lines indented with both tabs and spaces, or with a different
+    /// character than the first indented line of the file.
+    fn check_indentation(&mut self, line_no: usize, code: &str) {
+        let leading: Vec<char> = code
+            .chars()
+            .take_while(|c| *c == ' ' || *c == '\t')
+            .collect();
+        let Some(&first) = leading.first() else {
+            return;
+        };
+
+        let mixed = leading.iter().any(|c| *c != first);
+        let style = *self.indent_style.get_or_insert(first);
+        if mixed || first != style {
+            self.file.mixed_indentation.push(line_no);
+        }
+    }
+
+    /// Closes the scopes indented at least `indent`.
+    fn close(&mut self, indent: usize) {
+        while let Some(&(open_indent, idx)) = self.open.last() {
+            if open_indent < indent {
+                break;
+            }
+            self.symbols[idx].end_line = self.last_line;
+            self.open.pop();
+        }
+    }
+}
+
+/// Returns `true` if a statement is a lone string literal, optionally
+/// prefixed (`r"""...`). String contents are already removed by the
+/// scanner, so only the quotes are left.
+fn is_docstring(statement: &str) -> bool {
+    let prefix = statement
+        .chars()
+        .take_while(|c| matches!(c, 'r' | 'R' | 'u' | 'U'))
+        .count();
+    let literal = &statement[prefix..];
+    literal.starts_with(['"', '\'']) && literal.trim_matches(['"', '\'']).trim().is_empty()
+}
+
+/// Name of a decorator without its arguments (`app.route("/")` gives
+/// `app.route`).
+fn decorator_name(decorator: &str) -> String {
+    decorator
+        .split('(')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_string()
+}
@@ -0,0 +1,81 @@
+//! Detection of doc comments and documented items.
+
+use crate::metrics::symbols::Symbol;
+use crate::rust::rust_methods::ScannedLine;
+
+/// What a physical line holds, as far as documentation is concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LineKind {
+    /// Doc comment.
+    Doc,
+    /// Code ending an item or a statement (`;`, `{` or `}`).
+    Boundary,
+    /// Anything else: attributes, regular comments, blank lines, code in
+    /// the middle of a statement.
+    Other,
+}
+
+/// Classifies the lines of a file and marks the items preceded by a doc
+/// comment as documented.
+///
+/// Outer (`///`, `/** */`) and inner (`//!`, `/*! */`) doc comments are
+/// doc lines. An item is documented if a doc line precedes it with only
+/// attributes, regular comments or blank lines in between.
+pub(crate) struct DocTracker {
+    /// Kind of each line, from the first.
+    lines: Vec<LineKind>,
+    /// A doc block comment is open.
+    in_doc_block: bool,
+}
+
+impl DocTracker {
+    pub fn new() -> Self {
+        Self {
+            lines: Vec::new(),
+            in_doc_block: false,
+        }
+    }
+
+    /// Processes a physical line and returns `Some(true)` for a doc line,
+    /// `Some(false)` for a line with a regular comment and `None` for a line
+    /// without comments.
+    pub fn line(&mut self, line: &str, scanned: &ScannedLine) -> Option<bool> {
+        let text = line.trim_start();
+        let starts_doc_block =
+            (text.starts_with("/**") && !text.starts_with("/***")) || text.starts_with("/*!");
+        let is_doc = self.in_doc_block
+            || starts_doc_block
+            || (text.starts_with("///") && !text.starts_with("////"))
+            || text.starts_with("//!");
+        self.in_doc_block =
+            (self.in_doc_block || starts_doc_block) && scanned.state.block_comments > 0;
+
+        let code = scanned.code.trim_end();
+        let kind = if is_doc && code.trim().is_empty() {
+            LineKind::Doc
+        } else if code.ends_with([';', '{', '}']) {
+            LineKind::Boundary
+        } else {
+            LineKind::Other
+        };
+        self.lines.push(kind);
+
+        match (is_doc, &scanned.comment) {
+            (true, _) => Some(true),
+            (false, Some(_)) => Some(false),
+            (false, None) => None,
+        }
+    }
+
+    /// Marks the documented `symbols`.
+    pub fn finish(self, symbols: &mut [Symbol]) {
+        for symbol in symbols.iter_mut() {
+            let before = &self.lines[..symbol.start_line.saturating_sub(1).min(self.lines.len())];
+            symbol.documented = before
+                .iter()
+                .rev()
+                .find(|kind| **kind != LineKind::Other)
+                .is_some_and(|kind| *kind == LineKind::Doc);
+        }
+    }
+}
@@ -1,15 +1,38 @@
 use crate::collector::FileData;
 use crate::errors::PyLineError;
 use crate::impl_lang_parser;
+use crate::metrics::comments::{Documentation, count_public_items};
+use crate::metrics::duplicates::CodeLine;
+use crate::metrics::halstead::HalsteadTracker;
+use crate::metrics::line_length::LineWidth;
+use crate::metrics::tasks::{TaskMarker, find_marker};
+use crate::metrics::whitespace::WhitespaceTracker;
 use crate::parser::Rust;
 use crate::rust::base::{RUST_KEYWORDS, RustKeywords};
-use crate::traits::CodeParsers;
+use crate::rust::docs::DocTracker;
+use crate::rust::halstead::halstead_tokens;
+use crate::rust::imports::ImportTracker;
+use crate::rust::items::ItemTracker;
+use crate::rust::macros::MacroTracker;
+use crate::rust::panics::PanicTracker;
+use crate::rust::rust_methods::{LineState, scan_line, tokenize};
+use crate::rust::rustfmt::SkipTracker;
+use crate::rust::unsafety::UnsafeTracker;
+use crate::source::SourceText;
+use crate::traits::{CodeParsers, LangDefinition};
 use std::collections::HashMap;
-use tokio::fs::File;
-use tokio::io::{AsyncBufReadExt, BufReader};
 
 impl_lang_parser!(Rust);
 
+impl LangDefinition for Rust {
+    const NAME: &'static str = "rust";
+    const ENGINE_REVISION: u32 = 15;
+
+    fn keywords() -> Vec<&'static str> {
+        RUST_KEYWORDS.keys().copied().collect()
+    }
+}
+
 enum RustResult {
     Code(HashMap<RustKeywords, usize>),
     NoCode,
@@ -23,19 +46,79 @@ impl Rust {
     ///
     /// Analyzes each line to identify code lines, comments, and Rust
     /// keywords, updating the provided statistics structure accordingly.
+    /// The tokens of each line are passed to the [`ItemTracker`] to build
+    /// the item inventory and measure the brace depth of the file, and to
+    /// the [`UnsafeTracker`] and [`PanicTracker`] to find the `unsafe` and
+    /// panic-prone sites, to the [`MacroTracker`] to count macro calls and
+    /// attributes, and to the [`ImportTracker`] to collect the imports. The
+    /// [`DocTracker`] tells comment lines from doc lines and finds the
+    /// documented items. The width of every line is measured for the
+    /// line-length report, code under `#[rustfmt::skip]` being exempt, and
+    /// checked for whitespace issues by the [`WhitespaceTracker`].
+    /// Code lines are hashed for duplicate detection and their tokens split
+    /// into the operators and operands of the [`HalsteadTracker`].
     pub async fn parse_code_lines(
-        cursor: BufReader<File>,
+        source: &SourceText,
         code_stats: &mut Rust,
+        file: &FileData,
     ) -> Result<(), PyLineError> {
         let mut in_block_comment = false;
+        let mut state = LineState::default();
+        let mut items = ItemTracker::new(&file.path);
+        let mut unsafety = UnsafeTracker::new(&file.path);
+        let mut panics = PanicTracker::new(&file.path);
+        let mut macros = MacroTracker::new();
+        let mut imports = ImportTracker::new(&file.path);
+        let mut code_lines = Vec::new();
+        let mut halstead = HalsteadTracker::new();
+        let mut docs = DocTracker::new();
+        let mut skips = SkipTracker::new();
+        let mut line_widths = Vec::new();
+        let mut whitespace = WhitespaceTracker::new();
+        let mut line_no = 0;
 
-        let mut lines = cursor.lines();
-        while let Some(line) = lines.next_line().await? {
+        for source_line in source.lines() {
             code_stats.count_line();
+            line_no += 1;
+            whitespace.line(line_no, &source_line);
+            let line = source_line.text;
 
-            match Self::parse_line(&line, in_block_comment) {
+            let scanned = scan_line(line, state);
+            let tokens = tokenize(&scanned.code);
+            items.line(line_no, &tokens);
+            unsafety.line(line_no, &tokens, scanned.comment.as_deref());
+            panics.line(line_no, &tokens);
+            macros.line(&tokens);
+            imports.line(line_no, &tokens);
+            let exempt = skips.line(&scanned.code);
+            line_widths.extend(LineWidth::new(line_no, line, exempt));
+            match docs.line(line, &scanned) {
+                Some(true) => code_stats.count_doc_line(),
+                Some(false) => code_stats.count_comment_line(),
+                None => {}
+            }
+            if let Some((tag, author, text)) = scanned.comment.as_deref().and_then(find_marker) {
+                code_stats.metrics.tasks.push(TaskMarker {
+                    path: file.path.clone(),
+                    line: line_no,
+                    tag,
+                    author,
+                    text,
+                });
+            }
+            state = scanned.state;
+
+            match Self::parse_line(line, in_block_comment) {
                 RustResult::Code(stat) => {
                     code_stats.count_code_line();
+                    code_lines.extend(CodeLine::new(
+                        line_no,
+                        line,
+                        scanned.comment.as_deref(),
+                        &scanned.code,
+                        |word| RUST_KEYWORDS.contains_key(word),
+                    ));
+                    halstead.line(line_no, halstead_tokens(&tokens));
 
                     for (k, v) in stat {
                         *code_stats.keywords.entry(k.to_string()).or_insert(0) += v;
@@ -51,6 +134,27 @@ impl Rust {
             }
         }
 
+        let (mut symbols, mut file_metrics) = items.finish(line_no);
+        file_metrics.code_lines = code_lines;
+        file_metrics.line_widths = line_widths;
+        file_metrics.encoding = source.encoding().clone();
+        file_metrics.whitespace = whitespace.finish(source.has_utf8_bom());
+        file_metrics.halstead = halstead.finish(&mut symbols);
+        docs.finish(&mut symbols);
+        (
+            code_stats.stats.public_items,
+            code_stats.stats.documented_items,
+        ) = count_public_items(&symbols);
+        file_metrics.documentation = Documentation::from(&code_stats.stats);
+        let (panic_sites, test_lines) = panics.finish();
+        file_metrics.test_lines = test_lines;
+        code_stats.metrics.symbols = symbols;
+        code_stats.metrics.files.push(file_metrics);
+        code_stats.metrics.unsafe_sites = unsafety.finish(line_no);
+        code_stats.metrics.panic_sites = panic_sites;
+        code_stats.metrics.macros = macros.finish();
+        code_stats.metrics.imports = imports.finish();
+
         Ok(())
     }
 
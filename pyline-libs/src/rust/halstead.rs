@@ -0,0 +1,90 @@
+//! Splitting of Rust tokens into Halstead operators and operands.
+
+use crate::metrics::halstead::HalsteadToken;
+use crate::rust::base::RUST_KEYWORDS;
+use crate::rust::items::is_identifier;
+
+/// Keywords that name a type, a value or a path root, counted as operands.
+const NAME_KEYWORDS: &[&str] = &[
+    "bool", "char", "str", "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64",
+    "u128", "usize", "f32", "f64", "true", "false", "self", "Self", "crate", "super", "drop",
+    "sizeof", "alignof", "offsetof",
+];
+
+/// Prefixes of string literals (`r""`, `b""`, `br""`, `c""`, `cr""`).
+const STRING_PREFIXES: &[&str] = &["r", "b", "br", "c", "cr"];
+
+/// Classifies the tokens of a line: keywords and punctuation are operators,
+/// names and literals operands. A string or character literal, emptied by
+/// the scanner, is one operand; a lifetime counts as its name.
+pub(crate) fn halstead_tokens<'a>(tokens: &[&'a str]) -> Vec<HalsteadToken<'a>> {
+    let mut classified = Vec::with_capacity(tokens.len());
+    let mut i = 0;
+
+    while i < tokens.len() {
+        let token = tokens[i];
+        let next = tokens.get(i + 1).copied();
+        i += 1;
+
+        match token {
+            ")" | "]" | "}" => {}
+            "\"" => {
+                classified.push(HalsteadToken::Operand(token));
+                // Skips the closing quote.
+                i += 1;
+            }
+            "'" if next == Some("'") => {
+                classified.push(HalsteadToken::Operand(token));
+                i += 1;
+            }
+            // The lifetime name follows as an identifier.
+            "'" => {}
+            _ if STRING_PREFIXES.contains(&token) && next == Some("\"") => {}
+            _ if is_identifier(token) || token.starts_with(|c: char| c.is_ascii_digit()) => {
+                let keyword = RUST_KEYWORDS.contains_key(token) && !NAME_KEYWORDS.contains(&token);
+                classified.push(if keyword {
+                    HalsteadToken::Operator(token)
+                } else {
+                    HalsteadToken::Operand(token)
+                });
+            }
+            _ => classified.push(HalsteadToken::Operator(token)),
+        }
+    }
+
+    classified
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rust::rust_methods::tokenize;
+    use HalsteadToken::{Operand, Operator};
+
+    #[test]
+    fn operators_and_operands() {
+        let code = r#"let x: &'a str = f(b"", '', 1) as u8;"#;
+        assert_eq!(
+            halstead_tokens(&tokenize(code)),
+            vec![
+                Operator("let"),
+                Operand("x"),
+                Operator(":"),
+                Operator("&"),
+                Operand("a"),
+                Operand("str"),
+                Operator("="),
+                Operand("f"),
+                Operator("("),
+                Operand("\""),
+                Operator(","),
+                Operand("'"),
+                Operator(","),
+                Operand("1"),
+                Operator("as"),
+                Operand("u8"),
+                Operator(";"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,163 @@
+//! Collection of `use` declarations, `mod` declarations and `extern crate`
+//! items for the dependency graph.
+
+use crate::metrics::usage::Import;
+use crate::rust::items::is_identifier;
+use std::path::{Path, PathBuf};
+
+/// Declaration being read.
+#[derive(Debug)]
+enum Pending {
+    /// `use` and the tokens of its tree so far.
+    Use(Vec<String>),
+    /// `mod` and its name, if read.
+    Mod(Option<String>),
+    /// `extern`, waiting for `crate`.
+    Extern,
+    /// `extern crate`, waiting for the name.
+    ExternCrate,
+}
+
+/// Collects the imports of a file from its tokens.
+///
+/// A `use` tree is expanded into one import per path (`use a::{b, c::*}`
+/// gives `a::b` and the star import `a::c`); `mod m;` is recorded as
+/// `self::m` and `extern crate c;` as `c`.
+pub(crate) struct ImportTracker {
+    /// File being parsed.
+    path: PathBuf,
+    /// Imports found so far.
+    imports: Vec<Import>,
+    /// Declaration being read, with its first line.
+    pending: Option<(Pending, usize)>,
+    /// Previous token.
+    prev: String,
+}
+
+impl ImportTracker {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            imports: Vec::new(),
+            pending: None,
+            prev: String::new(),
+        }
+    }
+
+    /// Processes the tokens of a physical line.
+    pub fn line(&mut self, line_no: usize, tokens: &[&str]) {
+        for token in tokens {
+            self.token(line_no, token);
+        }
+    }
+
+    /// Returns the imports.
+    pub fn finish(self) -> Vec<Import> {
+        self.imports
+    }
+
+    fn token(&mut self, line_no: usize, token: &str) {
+        match self.pending.take() {
+            Some((Pending::Use(mut tree), line)) => match token {
+                ";" => {
+                    let mut pos = 0;
+                    let mut paths = Vec::new();
+                    use_tree(&tree, &mut pos, "", &mut paths);
+                    for (module, star) in paths {
+                        self.push(line, module, star);
+                    }
+                }
+                // `impl Trait + use<'a>` is not a declaration.
+                "<" if tree.is_empty() => {}
+                _ => {
+                    tree.push(token.to_string());
+                    self.pending = Some((Pending::Use(tree), line));
+                }
+            },
+            Some((Pending::Mod(None), line)) if is_identifier(token) => {
+                self.pending = Some((Pending::Mod(Some(token.to_string())), line));
+            }
+            Some((Pending::Mod(Some(name)), line)) if token == ";" => {
+                self.push(line, format!("self::{}", name), false);
+            }
+            Some((Pending::Extern, line)) if token == "crate" => {
+                self.pending = Some((Pending::ExternCrate, line));
+            }
+            Some((Pending::ExternCrate, line)) if is_identifier(token) => {
+                self.push(line, token.to_string(), false);
+            }
+            _ => {
+                // A path segment such as `x::use` is not a keyword.
+                let pending = match token {
+                    "use" => Some(Pending::Use(Vec::new())),
+                    "mod" => Some(Pending::Mod(None)),
+                    "extern" => Some(Pending::Extern),
+                    _ => None,
+                };
+                if self.prev != "::" {
+                    self.pending = pending.map(|pending| (pending, line_no));
+                }
+            }
+        }
+        self.prev = token.to_string();
+    }
+
+    fn push(&mut self, line: usize, module: String, star: bool) {
+        self.imports.push(Import {
+            path: self.path.clone(),
+            line,
+            module,
+            names: Vec::new(),
+            star,
+        });
+    }
+}
+
+/// Expands the use tree starting at `pos` into `paths`, prefixing them with
+/// `prefix`.
+fn use_tree(tokens: &[String], pos: &mut usize, prefix: &str, paths: &mut Vec<(String, bool)>) {
+    let mut path = prefix.to_string();
+    let mut named = false;
+
+    while let Some(token) = tokens.get(*pos) {
+        match token.as_str() {
+            "::" => {}
+            "*" => {
+                paths.push((path.clone(), true));
+                named = false;
+            }
+            "{" => {
+                *pos += 1;
+                loop {
+                    use_tree(tokens, pos, &path, paths);
+                    if tokens.get(*pos).is_some_and(|t| t == ",") {
+                        *pos += 1;
+                    } else {
+                        break;
+                    }
+                }
+                if tokens.get(*pos).is_some_and(|t| t == "}") {
+                    *pos += 1;
+                }
+                return;
+            }
+            // Skips the alias.
+            "as" => *pos += 1,
+            "," | "}" => break,
+            // `a::{self}` is `a` itself.
+            "self" if !path.is_empty() => named = true,
+            _ => {
+                if !path.is_empty() {
+                    path.push_str("::");
+                }
+                path.push_str(token);
+                named = true;
+            }
+        }
+        *pos += 1;
+    }
+
+    if named {
+        paths.push((path, false));
+    }
+}
@@ -0,0 +1,411 @@
+//! Brace-depth item tracking for the Rust symbol inventory and nesting
+//! depth.
+
+use crate::metrics::FileMetrics;
+use crate::metrics::nesting::DepthStat;
+use crate::metrics::symbols::{Symbol, SymbolKind, Visibility};
+use std::path::{Path, PathBuf};
+
+/// Tokens that may precede an item keyword without ending the item
+/// position (`pub(crate) const unsafe extern "C" fn`).
+const ITEM_MODIFIERS: &[&str] = &[
+    "async", "const", "unsafe", "extern", "default", "auto", "\"",
+];
+
+/// Keywords after which `&&`, `||` and `?` do not follow an expression.
+pub(crate) const NON_EXPR_KEYWORDS: &[&str] = &[
+    "move", "return", "in", "else", "let", "mut", "if", "while", "match", "break", "ref",
+];
+
+/// A scope opened by `{`.
+#[derive(Debug, Clone, Copy)]
+enum Scope {
+    /// Body of an item (index into the symbols).
+    Item(usize),
+    /// Any other block.
+    Block,
+}
+
+/// What the tracker expects from the next tokens.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Expect {
+    /// Nothing in particular.
+    Nothing,
+    /// The name of an item of the given kind.
+    Name(SymbolKind),
+    /// The restriction of `pub(...)`.
+    Restriction,
+    /// The `[` of an attribute after `#` (or `#!`).
+    AttributeStart,
+}
+
+/// Builds the item inventory of a file from its tokens.
+///
+/// Items are recognized only at item position: at the start of the file
+/// or after `;`, `{`, `}` or an attribute, optionally preceded by
+/// visibility and other modifiers. An item spans from its keyword to the
+/// `}` closing its body, or to the `;` ending a body-less item.
+pub(crate) struct ItemTracker {
+    /// File being parsed.
+    path: PathBuf,
+    /// Items found so far.
+    symbols: Vec<Symbol>,
+    /// Brace depth of the body and nesting of each item, aligned with
+    /// `symbols`.
+    nesting: Vec<(usize, DepthStat)>,
+    /// Metrics of the whole file.
+    file: FileMetrics,
+    /// Scopes of the open braces.
+    scopes: Vec<Scope>,
+    /// Item whose body has not been opened yet.
+    pending: Option<usize>,
+    /// Header tokens of a pending `impl` block.
+    impl_header: Option<ImplHeader>,
+    /// The next token is at item position.
+    at_item_start: bool,
+    /// Visibility seen at the current item position.
+    visibility: Visibility,
+    /// `async` seen at the current item position.
+    is_async: bool,
+    /// Number of open parentheses and square brackets.
+    nest: usize,
+    /// Bracket depth inside an attribute.
+    attribute: usize,
+    /// Expectation set by the previous token.
+    expect: Expect,
+    /// The previous token ends an expression (an operand, `)` or `]`).
+    after_expr: bool,
+}
+
+/// Header of an `impl` block, collected up to its body.
+#[derive(Default)]
+struct ImplHeader {
+    /// Depth of the `<...>` generics directly after `impl`.
+    generics: usize,
+    /// Generics were read (or absent); tokens now belong to the header.
+    started: bool,
+    /// Tokens of the implemented trait and type.
+    tokens: Vec<String>,
+    /// The header contains a `where` clause; the rest is ignored.
+    in_where: bool,
+}
+
+impl ItemTracker {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            symbols: Vec::new(),
+            nesting: Vec::new(),
+            file: FileMetrics::new(path),
+            scopes: Vec::new(),
+            pending: None,
+            impl_header: None,
+            at_item_start: true,
+            visibility: Visibility::Private,
+            is_async: false,
+            nest: 0,
+            attribute: 0,
+            expect: Expect::Nothing,
+            after_expr: false,
+        }
+    }
+
+    /// Processes the tokens of a physical line.
+    ///
+    /// The nesting depth of the line is the brace depth at its start, not
+    /// counting the braces it closes first (so a closing `}` is at the
+    /// depth of its opening line).
+    pub fn line(&mut self, line_no: usize, tokens: &[&str]) {
+        if !tokens.is_empty() && self.attribute == 0 {
+            let closing = tokens.iter().take_while(|t| **t == "}").count();
+            let depth = self.scopes.len().saturating_sub(closing);
+            self.file.nesting.record(line_no, depth);
+
+            if let Some(idx) = self.innermost_function() {
+                let (base, stat) = &mut self.nesting[idx];
+                if depth > *base {
+                    stat.record(line_no, depth - *base);
+                }
+            }
+        }
+
+        for token in tokens {
+            self.token(line_no, token);
+        }
+    }
+
+    /// Returns the items, closing the ones left open, with the metrics of
+    /// the file.
+    pub fn finish(mut self, last_line: usize) -> (Vec<Symbol>, FileMetrics) {
+        for scope in self.scopes.drain(..) {
+            if let Scope::Item(idx) = scope {
+                self.symbols[idx].end_line = last_line;
+            }
+        }
+        for (symbol, (_, stat)) in self.symbols.iter_mut().zip(&self.nesting) {
+            symbol.max_nesting = stat.max;
+            symbol.avg_nesting = stat.avg();
+        }
+        (self.symbols, self.file)
+    }
+
+    fn token(&mut self, line_no: usize, token: &str) {
+        if self.attribute > 0 {
+            match token {
+                "[" => self.attribute += 1,
+                "]" => self.attribute -= 1,
+                _ => {}
+            }
+            return;
+        }
+
+        if self.is_decision(token) {
+            self.add_decision();
+        }
+        self.after_expr = ends_expression(token);
+
+        match std::mem::replace(&mut self.expect, Expect::Nothing) {
+            Expect::AttributeStart => match token {
+                "!" => {
+                    self.expect = Expect::AttributeStart;
+                    return;
+                }
+                "[" => {
+                    self.attribute = 1;
+                    return;
+                }
+                _ => {}
+            },
+            Expect::Restriction => {
+                if token == ")" {
+                    self.nest = self.nest.saturating_sub(1);
+                    return;
+                }
+                // Only the first token decides: `pub(in crate::a)` is restricted.
+                if self.visibility == Visibility::Public {
+                    self.visibility = match token {
+                        "crate" => Visibility::Crate,
+                        "self" => Visibility::Private,
+                        _ => Visibility::Restricted,
+                    };
+                }
+                self.expect = Expect::Restriction;
+                return;
+            }
+            Expect::Name(kind) => {
+                if kind == SymbolKind::MacroRules && token == "!" {
+                    self.expect = Expect::Name(kind);
+                    return;
+                }
+                if is_identifier(token) {
+                    self.open_item(kind, token, line_no);
+                    return;
+                }
+            }
+            Expect::Nothing => {}
+        }
+
+        let ends_header = self.nest == 0 && matches!(token, "{" | ";");
+        if let Some(header) = &mut self.impl_header
+            && !ends_header
+        {
+            header.push(token);
+            if token == "for" && header.generics == 0 && !header.in_where {
+                self.symbols[self.pending.unwrap_or_default()].kind = SymbolKind::TraitImpl;
+            }
+        }
+
+        match token {
+            "#" => self.expect = Expect::AttributeStart,
+            "(" | "[" => {
+                if token == "(" && self.at_item_start && self.visibility == Visibility::Public {
+                    self.expect = Expect::Restriction;
+                } else {
+                    self.at_item_start = false;
+                }
+                self.nest += 1;
+            }
+            ")" | "]" => {
+                self.nest = self.nest.saturating_sub(1);
+                self.at_item_start = false;
+            }
+            "{" => {
+                let body = if self.nest == 0 {
+                    self.pending.take()
+                } else {
+                    None
+                };
+                match body {
+                    Some(idx) => {
+                        self.finish_header(idx);
+                        self.nesting[idx].0 = self.scopes.len();
+                        self.scopes.push(Scope::Item(idx));
+                    }
+                    None => self.scopes.push(Scope::Block),
+                }
+                self.reset_item_start();
+            }
+            "}" => {
+                if let Some(Scope::Item(idx)) = self.scopes.pop() {
+                    self.symbols[idx].end_line = line_no;
+                }
+                self.reset_item_start();
+            }
+            ";" if self.nest == 0 => {
+                if let Some(idx) = self.pending.take() {
+                    self.finish_header(idx);
+                    self.symbols[idx].end_line = line_no;
+                }
+                self.reset_item_start();
+            }
+            _ if !self.at_item_start || self.pending.is_some() => {}
+            "pub" => self.visibility = Visibility::Public,
+            "async" => self.is_async = true,
+            _ if ITEM_MODIFIERS.contains(&token) => {}
+            "fn" => self.expect = Expect::Name(self.function_kind()),
+            "struct" => self.expect = Expect::Name(SymbolKind::Struct),
+            "enum" => self.expect = Expect::Name(SymbolKind::Enum),
+            "union" => self.expect = Expect::Name(SymbolKind::Union),
+            "trait" => self.expect = Expect::Name(SymbolKind::Trait),
+            "type" => self.expect = Expect::Name(SymbolKind::TypeAlias),
+            "mod" => self.expect = Expect::Name(SymbolKind::Module),
+            "macro_rules" => self.expect = Expect::Name(SymbolKind::MacroRules),
+            "impl" => {
+                self.open_item(SymbolKind::InherentImpl, "", line_no);
+                self.impl_header = Some(ImplHeader::default());
+            }
+            _ => self.at_item_start = false,
+        }
+    }
+
+    /// Registers an item whose body (or `;`) is still to come.
+    fn open_item(&mut self, kind: SymbolKind, name: &str, line_no: usize) {
+        let mut symbol = Symbol::new(self.path.clone(), name, kind, line_no);
+        symbol.is_async = self.is_async;
+        symbol.visibility = Some(self.visibility);
+
+        self.pending = Some(self.symbols.len());
+        self.symbols.push(symbol);
+        self.nesting.push((0, DepthStat::default()));
+        self.at_item_start = false;
+    }
+
+    /// Returns `true` for tokens that add a decision point: branches,
+    /// loops, match arms, `?` and the short-circuit operators. `||` and
+    /// `&&` count only after an operand, where they are not an empty
+    /// closure or a double reference.
+    fn is_decision(&self, token: &str) -> bool {
+        match token {
+            "if" | "while" | "for" | "=>" => true,
+            "&&" | "||" | "?" => self.after_expr,
+            _ => false,
+        }
+    }
+
+    /// Adds a decision point to the innermost item, if it is a function.
+    fn add_decision(&mut self) {
+        if let Some(idx) = self.innermost_function() {
+            self.symbols[idx].complexity += 1;
+        }
+    }
+
+    /// Index of the innermost item, if it is a function.
+    fn innermost_function(&self) -> Option<usize> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| match scope {
+                Scope::Item(idx) => Some(*idx),
+                Scope::Block => None,
+            })
+            .filter(|idx| self.symbols[*idx].kind.is_function())
+    }
+
+    /// Kind of a function opened in the current scope.
+    fn function_kind(&self) -> SymbolKind {
+        let parent = self.scopes.iter().rev().find_map(|scope| match scope {
+            Scope::Item(idx) => Some(self.symbols[*idx].kind),
+            Scope::Block => None,
+        });
+
+        match parent {
+            Some(SymbolKind::Trait | SymbolKind::TraitImpl | SymbolKind::InherentImpl) => {
+                SymbolKind::Method
+            }
+            Some(kind) if kind.is_function() => SymbolKind::NestedFunction,
+            _ => SymbolKind::Function,
+        }
+    }
+
+    /// Names the `impl` block `idx` after its header.
+    fn finish_header(&mut self, idx: usize) {
+        if let Some(header) = self.impl_header.take() {
+            self.symbols[idx].name = header.name();
+        }
+    }
+
+    fn reset_item_start(&mut self) {
+        self.at_item_start = true;
+        self.visibility = Visibility::Private;
+        self.is_async = false;
+    }
+}
+
+impl ImplHeader {
+    fn push(&mut self, token: &str) {
+        if !self.started {
+            match token {
+                "<" => self.generics += 1,
+                ">" => {
+                    self.generics = self.generics.saturating_sub(1);
+                    self.started = self.generics == 0;
+                }
+                _ if self.generics == 0 => {
+                    self.started = true;
+                    self.tokens.push(token.to_string());
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        if token == "where" {
+            self.in_where = true;
+        }
+        if !self.in_where {
+            self.tokens.push(token.to_string());
+        }
+    }
+
+    /// Implemented trait and type, e.g. `fmt::Display for Wrapper<T>`.
+    fn name(&self) -> String {
+        let mut name = String::new();
+        let mut prev_word = false;
+        for token in &self.tokens {
+            let word = is_identifier(token);
+            if word && prev_word {
+                name.push(' ');
+            }
+            name.push_str(token);
+            prev_word = word;
+        }
+        name
+    }
+}
+
+pub(crate) fn ends_expression(token: &str) -> bool {
+    let operand = token
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_alphanumeric() || c == '_');
+
+    (operand && !NON_EXPR_KEYWORDS.contains(&token))
+        || matches!(token, ")" | "]" | "\"" | "'" | "?")
+}
+
+pub(crate) fn is_identifier(token: &str) -> bool {
+    token
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_')
+}
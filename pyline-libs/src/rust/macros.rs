@@ -0,0 +1,135 @@
+//! Counting of macro invocations and attributes.
+
+use crate::metrics::macros::MacroCounts;
+use crate::rust::items::{NON_EXPR_KEYWORDS, is_identifier};
+
+/// Counts the macro invocations and attributes of a file from its tokens.
+///
+/// A macro invocation is a name followed by `!` and a bracket; an
+/// attribute is read from its `[` after `#` (or `#!`) to the matching `]`.
+pub(crate) struct MacroTracker {
+    /// Counts so far.
+    counts: MacroCounts,
+    /// Tokens of the attribute being read, after its `[`.
+    attribute: Option<Vec<String>>,
+    /// Bracket depth inside the attribute.
+    attribute_nest: usize,
+    /// The two previous tokens, the last one first.
+    prev: [String; 2],
+}
+
+impl MacroTracker {
+    pub fn new() -> Self {
+        Self {
+            counts: MacroCounts::default(),
+            attribute: None,
+            attribute_nest: 0,
+            prev: Default::default(),
+        }
+    }
+
+    /// Processes the tokens of a physical line.
+    pub fn line(&mut self, tokens: &[&str]) {
+        for token in tokens {
+            self.token(token);
+        }
+    }
+
+    /// Returns the counts.
+    pub fn finish(self) -> MacroCounts {
+        self.counts
+    }
+
+    fn token(&mut self, token: &str) {
+        if let Some(attribute) = &mut self.attribute {
+            match token {
+                "[" => self.attribute_nest += 1,
+                "]" => self.attribute_nest -= 1,
+                _ => {}
+            }
+            if self.attribute_nest == 0 {
+                let tokens = std::mem::take(attribute);
+                self.attribute = None;
+                self.count_attribute(&tokens);
+            } else {
+                attribute.push(token.to_string());
+            }
+            self.shift(token);
+            return;
+        }
+
+        let [prev, prev2] = &self.prev;
+        match token {
+            "[" if prev == "#" || (prev == "!" && prev2 == "#") => {
+                self.attribute = Some(Vec::new());
+                self.attribute_nest = 1;
+            }
+            "(" | "[" | "{"
+                if prev == "!"
+                    && is_identifier(prev2)
+                    && !NON_EXPR_KEYWORDS.contains(&prev2.as_str()) =>
+            {
+                *self.counts.macros.entry(prev2.clone()).or_insert(0) += 1;
+            }
+            _ => {}
+        }
+        self.shift(token);
+    }
+
+    /// Counts an attribute from the tokens between its brackets.
+    fn count_attribute(&mut self, tokens: &[String]) {
+        let path_len = tokens
+            .iter()
+            .take_while(|t| is_identifier(t) || *t == "::")
+            .count();
+        if path_len == 0 {
+            return;
+        }
+        let name = tokens[..path_len].concat();
+
+        let list = match name.as_str() {
+            "derive" => Some(&mut self.counts.derives),
+            "allow" => Some(&mut self.counts.allows),
+            _ => None,
+        };
+        if let Some(list) = list
+            && tokens.get(path_len).is_some_and(|t| t == "(")
+        {
+            for arg in arguments(&tokens[path_len + 1..]) {
+                *list.entry(arg).or_insert(0) += 1;
+            }
+        }
+        *self.counts.attributes.entry(name).or_insert(0) += 1;
+    }
+
+    fn shift(&mut self, token: &str) {
+        self.prev.swap(0, 1);
+        self.prev[0] = token.to_string();
+    }
+}
+
+/// Splits the tokens after the `(` of an attribute into its
+/// comma-separated arguments, up to the matching `)`.
+fn arguments(tokens: &[String]) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut nest = 0;
+
+    for token in tokens {
+        match token.as_str() {
+            "(" | "[" => nest += 1,
+            ")" | "]" if nest == 0 => break,
+            ")" | "]" => nest -= 1,
+            "," if nest == 0 => {
+                args.push(std::mem::take(&mut current));
+                continue;
+            }
+            _ => {}
+        }
+        current.push_str(token);
+    }
+    args.push(current);
+
+    args.retain(|arg| !arg.is_empty());
+    args
+}
@@ -4,9 +4,29 @@
 //! - [`base`] — Basic data structures, enums, and constants shared across all parsers
 //! - [`engine`] — Core parsing algorithms and state machines (language-independent
 //!   logic)
+//! - [`rust_methods`] — Rust line scanner (comments, strings) and tokenizer
+//! - [`items`] — brace-depth tracking for the item inventory
+//! - [`unsafety`] — detection of `unsafe` sites for the unsafe audit
+//! - [`panics`] — detection of panic-prone call sites
+//! - [`macros`] — counting of macro invocations and attributes
+//! - [`imports`] — collection of `use`, `mod` and `extern crate` for the
+//!   dependency graph
+//! - [`docs`] — detection of doc comments and documented items
+//! - [`halstead`] — splitting of tokens into Halstead operators and operands
+//! - [`rustfmt`] — detection of code excluded from formatting, exempt from
+//!   line length limits
 //!
 //! The architecture separates language-agnostic infrastructure from language-specific
 //! implementations, enabling easy extension to new programming languages.
 pub mod base;
 #[macro_use]
 pub(crate) mod engine;
+pub(crate) mod docs;
+pub(crate) mod halstead;
+pub(crate) mod imports;
+pub(crate) mod items;
+pub(crate) mod macros;
+pub(crate) mod panics;
+pub(crate) mod rust_methods;
+pub(crate) mod rustfmt;
+pub(crate) mod unsafety;
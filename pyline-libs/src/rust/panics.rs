@@ -0,0 +1,158 @@
+//! Detection of panic-prone call sites.
+
+use crate::metrics::panics::{PanicKind, PanicSite};
+use crate::rust::items::{ends_expression, is_identifier};
+use std::path::{Path, PathBuf};
+
+/// Operand-like keywords that cannot be indexed (`impl Trait for [T]`).
+const NOT_INDEXED: &[&str] = &[
+    "as", "const", "dyn", "for", "impl", "static", "type", "unsafe", "where",
+];
+
+/// Collects the panic-prone sites of a file from its tokens.
+///
+/// Test code is a file under a `tests` directory or the body of an item
+/// annotated `#[cfg(test)]`.
+pub(crate) struct PanicTracker {
+    /// File being parsed.
+    path: PathBuf,
+    /// Sites found so far.
+    sites: Vec<PanicSite>,
+    /// The whole file is test code.
+    test_file: bool,
+    /// Brace depth.
+    depth: usize,
+    /// Number of open parentheses and square brackets.
+    nest: usize,
+    /// Brace depth inside the open `#[cfg(test)]` item.
+    test_depth: Option<usize>,
+    /// A `#[cfg(test)]` attribute waits for its item.
+    cfg_test: bool,
+    /// Tokens of the attribute being read, from its `[`.
+    attribute: Option<String>,
+    /// The attribute being read is an inner one (`#![...]`).
+    inner_attribute: bool,
+    /// Bracket depth inside the attribute.
+    attribute_nest: usize,
+    /// The two previous tokens, the last one first.
+    prev: [String; 2],
+    /// Lines of test code with tokens.
+    test_lines: usize,
+}
+
+impl PanicTracker {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            sites: Vec::new(),
+            test_file: path.components().any(|c| c.as_os_str() == "tests"),
+            depth: 0,
+            nest: 0,
+            test_depth: None,
+            cfg_test: false,
+            attribute: None,
+            inner_attribute: false,
+            attribute_nest: 0,
+            prev: Default::default(),
+            test_lines: 0,
+        }
+    }
+
+    /// Processes the tokens of a physical line.
+    pub fn line(&mut self, line_no: usize, tokens: &[&str]) {
+        let mut in_test = self.in_test();
+        for token in tokens {
+            self.token(line_no, token);
+            in_test |= self.in_test();
+        }
+        if in_test && !tokens.is_empty() {
+            self.test_lines += 1;
+        }
+    }
+
+    /// Returns the sites with the number of test code lines.
+    pub fn finish(self) -> (Vec<PanicSite>, usize) {
+        (self.sites, self.test_lines)
+    }
+
+    fn in_test(&self) -> bool {
+        self.test_file || self.test_depth.is_some()
+    }
+
+    fn token(&mut self, line_no: usize, token: &str) {
+        if let Some(attribute) = &mut self.attribute {
+            attribute.push_str(token);
+            match token {
+                "[" => self.attribute_nest += 1,
+                "]" => self.attribute_nest -= 1,
+                _ => {}
+            }
+            if self.attribute_nest == 0 {
+                if attribute == "[cfg(test)]" {
+                    if self.inner_attribute {
+                        // `#![cfg(test)]` applies to the enclosing block.
+                        self.test_depth.get_or_insert(self.depth);
+                    } else {
+                        self.cfg_test = true;
+                    }
+                }
+                self.attribute = None;
+            }
+            self.shift(token);
+            return;
+        }
+
+        let [prev, prev2] = &self.prev;
+        let kind = match token {
+            "(" if prev2 == "." && prev == "unwrap" => Some(PanicKind::Unwrap),
+            "(" if prev2 == "." && prev == "expect" => Some(PanicKind::Expect),
+            "(" | "[" | "{" if prev == "!" => PanicKind::from_macro(prev2),
+            "[" if ends_expression(prev)
+                && prev2 != "'"
+                && !NOT_INDEXED.contains(&prev.as_str())
+                && (is_identifier(prev) || matches!(prev.as_str(), ")" | "]")) =>
+            {
+                Some(PanicKind::Index)
+            }
+            _ => None,
+        };
+        if let Some(kind) = kind {
+            self.sites.push(PanicSite {
+                path: self.path.clone(),
+                line: line_no,
+                kind,
+                in_test: self.in_test(),
+            });
+        }
+
+        match token {
+            "[" if matches!(prev.as_str(), "#" | "!") && (prev == "#" || prev2 == "#") => {
+                self.attribute = Some("[".to_string());
+                self.inner_attribute = prev == "!";
+                self.attribute_nest = 1;
+            }
+            "(" | "[" => self.nest += 1,
+            ")" | "]" => self.nest = self.nest.saturating_sub(1),
+            "{" => {
+                self.depth += 1;
+                if std::mem::take(&mut self.cfg_test) && self.test_depth.is_none() {
+                    self.test_depth = Some(self.depth);
+                }
+            }
+            "}" => {
+                if self.test_depth == Some(self.depth) {
+                    self.test_depth = None;
+                }
+                self.depth = self.depth.saturating_sub(1);
+            }
+            ";" if self.nest == 0 => self.cfg_test = false,
+            _ => {}
+        }
+        self.shift(token);
+    }
+
+    fn shift(&mut self, token: &str) {
+        self.prev.swap(0, 1);
+        self.prev[0] = token.to_string();
+    }
+}
@@ -0,0 +1,248 @@
+//! Rust source code scanning utilities.
+//!
+//! Splits physical lines into code and comments, keeping track of nested
+//! block comments and string literals that span several lines, and breaks
+//! the code into tokens for the item tracker.
+
+/// A string literal left open at the end of a line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpenString {
+    /// Regular (possibly byte or C) string literal.
+    Normal,
+    /// Raw string literal with the given number of `#`.
+    Raw(usize),
+}
+
+/// Lexical state carried from one physical line to the next.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct LineState {
+    /// Depth of nested block comments.
+    pub block_comments: usize,
+    /// String literal that continues on the next line.
+    pub string: Option<OpenString>,
+}
+
+/// A physical line with string contents and comments removed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScannedLine {
+    /// The code of the line. Indentation is kept, string and character
+    /// literals are reduced to their quotes (`""`, `''`).
+    pub code: String,
+    /// Text of the comments on the line, without the comment markers.
+    pub comment: Option<String>,
+    /// State after the line.
+    pub state: LineState,
+}
+
+/// Scans a physical line, starting in `state`.
+pub fn scan_line(line: &str, mut state: LineState) -> ScannedLine {
+    let chars: Vec<char> = line.chars().collect();
+    let mut code = String::with_capacity(line.len());
+    let mut comment: Option<String> = None;
+
+    let mut i = 0;
+    while i < chars.len() {
+        let ch = chars[i];
+        let next = chars.get(i + 1).copied();
+
+        if state.block_comments > 0 {
+            match (ch, next) {
+                ('/', Some('*')) => {
+                    state.block_comments += 1;
+                    i += 2;
+                }
+                ('*', Some('/')) => {
+                    state.block_comments -= 1;
+                    i += 2;
+                }
+                _ => {
+                    comment.get_or_insert_default().push(ch);
+                    i += 1;
+                }
+            }
+            continue;
+        }
+
+        match state.string {
+            Some(OpenString::Normal) => {
+                if ch == '\\' {
+                    i += 2;
+                } else {
+                    if ch == '"' {
+                        code.push('"');
+                        state.string = None;
+                    }
+                    i += 1;
+                }
+                continue;
+            }
+            Some(OpenString::Raw(hashes)) => {
+                if ch == '"' && (1..=hashes).all(|k| chars.get(i + k) == Some(&'#')) {
+                    code.push('"');
+                    code.extend(std::iter::repeat_n('#', hashes));
+                    state.string = None;
+                    i += 1 + hashes;
+                } else {
+                    i += 1;
+                }
+                continue;
+            }
+            None => {}
+        }
+
+        match (ch, next) {
+            ('/', Some('/')) => {
+                let text: String = chars[i + 2..].iter().collect();
+                let text = text.trim_start_matches(['/', '!']);
+                comment.get_or_insert_default().push_str(text);
+                break;
+            }
+            ('/', Some('*')) => {
+                state.block_comments = 1;
+                i += 2;
+            }
+            ('"', _) => {
+                code.push('"');
+                state.string = Some(OpenString::Normal);
+                i += 1;
+            }
+            ('r', Some('"' | '#')) if starts_literal(&chars, i) => {
+                let hashes = chars[i + 1..].iter().take_while(|c| **c == '#').count();
+                if chars.get(i + 1 + hashes) == Some(&'"') {
+                    code.push('r');
+                    code.extend(std::iter::repeat_n('#', hashes));
+                    code.push('"');
+                    state.string = Some(OpenString::Raw(hashes));
+                    i += 2 + hashes;
+                } else {
+                    // Raw identifier (`r#type`).
+                    code.push(ch);
+                    i += 1;
+                }
+            }
+            ('\'', Some('\\')) => {
+                // Escaped character literal.
+                let end = (i + 3..chars.len())
+                    .find(|&k| chars[k] == '\'')
+                    .unwrap_or(chars.len() - 1);
+                code.push_str("''");
+                i = end + 1;
+            }
+            ('\'', Some(_)) if chars.get(i + 2) == Some(&'\'') => {
+                code.push_str("''");
+                i += 3;
+            }
+            _ => {
+                // Lifetimes and everything else are kept as is.
+                code.push(ch);
+                i += 1;
+            }
+        }
+    }
+
+    ScannedLine {
+        code,
+        comment,
+        state,
+    }
+}
+
+/// Returns `true` if the `r` at `i` is the prefix of a literal (`r"`, `br"`)
+/// rather than the end of an identifier.
+fn starts_literal(chars: &[char], i: usize) -> bool {
+    let is_ident = |k: usize| chars[k].is_alphanumeric() || chars[k] == '_';
+
+    match i {
+        0 => true,
+        1 => !is_ident(0) || chars[0] == 'b' || chars[0] == 'c',
+        _ => !is_ident(i - 1) || (matches!(chars[i - 1], 'b' | 'c') && !is_ident(i - 2)),
+    }
+}
+
+/// Splits scanned code into tokens: identifiers (with numbers), `::`, `->`,
+/// `=>`, `&&`, `||` and single punctuation characters.
+pub fn tokenize(code: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut chars = code.char_indices().peekable();
+
+    while let Some((start, ch)) = chars.next() {
+        if ch.is_whitespace() {
+            continue;
+        }
+
+        if ch.is_alphanumeric() || ch == '_' {
+            let mut end = start + ch.len_utf8();
+            while let Some(&(i, c)) = chars.peek() {
+                if !(c.is_alphanumeric() || c == '_') {
+                    break;
+                }
+                end = i + c.len_utf8();
+                chars.next();
+            }
+            tokens.push(&code[start..end]);
+            continue;
+        }
+
+        let pair = chars.peek().map(|&(_, c)| (ch, c));
+        if matches!(
+            pair,
+            Some((':', ':') | ('-', '>') | ('=', '>') | ('&', '&') | ('|', '|'))
+        ) {
+            chars.next();
+            tokens.push(&code[start..start + 2]);
+        } else {
+            tokens.push(&code[start..start + ch.len_utf8()]);
+        }
+    }
+
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strings_and_chars_are_emptied() {
+        let line = scan_line(
+            r#"let s = "{ // }"; let c = '{'; let l: &'a str = r"}"; // note"#,
+            LineState::default(),
+        );
+        assert_eq!(
+            line.code,
+            r#"let s = ""; let c = ''; let l: &'a str = r""; "#
+        );
+        assert_eq!(line.comment.as_deref(), Some(" note"));
+    }
+
+    #[test]
+    fn nested_block_comments_span_lines() {
+        let first = scan_line("a /* one /* two */", LineState::default());
+        assert_eq!(first.code, "a ");
+        assert_eq!(first.state.block_comments, 1);
+
+        let last = scan_line("still */ b", first.state);
+        assert_eq!(last.code, " b");
+        assert_eq!(last.state, LineState::default());
+    }
+
+    #[test]
+    fn raw_strings_span_lines() {
+        let first = scan_line(r###"let s = r#"a " {"###, LineState::default());
+        assert_eq!(first.state.string, Some(OpenString::Raw(1)));
+
+        let last = scan_line(r###"}"# ;"###, first.state);
+        assert_eq!(last.code, r###""# ;"###);
+    }
+
+    #[test]
+    fn tokenizes_paths_and_arrows() {
+        assert_eq!(
+            tokenize("pub(crate) fn f() -> std::io::Result<()> {"),
+            vec![
+                "pub", "(", "crate", ")", "fn", "f", "(", ")", "->", "std", "::", "io", "::",
+                "Result", "<", "(", ")", ">", "{"
+            ]
+        );
+    }
+}
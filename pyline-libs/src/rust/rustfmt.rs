@@ -0,0 +1,123 @@
+//! Detection of code excluded from formatting with `#[rustfmt::skip]`.
+
+/// Attributes that exclude code from formatting, with whitespace removed.
+const SKIP_ATTRIBUTES: &[&str] = &[
+    "#[rustfmt::skip]",
+    "#![rustfmt::skip]",
+    "#[cfg_attr(rustfmt,rustfmt_skip)]",
+    "#![cfg_attr(rustfmt,rustfmt_skip)]",
+];
+
+/// A region of code excluded from formatting.
+#[derive(Debug, Clone, Copy)]
+struct Skip {
+    /// Bracket depth of the attribute.
+    depth: usize,
+    /// Inner attribute (`#![...]`), covering the enclosing block.
+    inner: bool,
+    /// The body (`{`) of the item was entered.
+    in_body: bool,
+}
+
+/// Tracks the lines excluded from formatting, which are exempt from line
+/// length limits.
+///
+/// An outer attribute covers the next item or statement: up to a `;` at
+/// the depth of the attribute, the closing brace of the body it opens or
+/// the end of the enclosing block. An inner attribute covers the rest of
+/// the enclosing block, or of the file at the top level.
+pub(crate) struct SkipTracker {
+    /// Depth of `()`, `[]` and `{}` brackets.
+    depth: usize,
+    /// The open region.
+    skip: Option<Skip>,
+}
+
+impl SkipTracker {
+    pub fn new() -> Self {
+        Self {
+            depth: 0,
+            skip: None,
+        }
+    }
+
+    /// Processes the code of a line, as given by the line scanner, and
+    /// returns `true` if the line is excluded from formatting.
+    pub fn line(&mut self, code: &str) -> bool {
+        let code: String = code.chars().filter(|c| !c.is_whitespace()).collect();
+        if self.skip.is_none()
+            && let Some(attribute) = SKIP_ATTRIBUTES.iter().find(|a| code.contains(*a))
+        {
+            self.skip = Some(Skip {
+                depth: self.depth,
+                inner: attribute.starts_with("#!"),
+                in_body: false,
+            });
+        }
+        let exempt = self.skip.is_some();
+
+        for ch in code.chars() {
+            match ch {
+                '(' | '[' | '{' => {
+                    if let Some(skip) = &mut self.skip
+                        && ch == '{'
+                        && !skip.inner
+                        && self.depth == skip.depth
+                    {
+                        skip.in_body = true;
+                    }
+                    self.depth += 1;
+                }
+                ')' | ']' | '}' => {
+                    self.depth = self.depth.saturating_sub(1);
+                    if let Some(skip) = self.skip
+                        && (self.depth < skip.depth
+                            || (ch == '}' && skip.in_body && self.depth == skip.depth))
+                    {
+                        self.skip = None;
+                    }
+                }
+                ';' => {
+                    if let Some(skip) = self.skip
+                        && !skip.inner
+                        && self.depth == skip.depth
+                    {
+                        self.skip = None;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        exempt
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skip_covers_the_next_item() {
+        let mut skips = SkipTracker::new();
+        let lines = [
+            "#[rustfmt::skip]",
+            "const TABLE: [u8; 4] = [",
+            "    1, 2, 3, 4,",
+            "];",
+            "fn formatted() {}",
+            "mod m {",
+            "    #![rustfmt::skip]",
+            "    fn f() { g(); }",
+            "}",
+            "fn after() {}",
+        ];
+        let exempt: Vec<bool> = lines.iter().map(|line| skips.line(line)).collect();
+        assert_eq!(
+            exempt,
+            [
+                true, true, true, true, false, false, true, true, true, false
+            ]
+        );
+    }
+}
@@ -0,0 +1,143 @@
+//! Detection of `unsafe` sites for the unsafe audit.
+
+use crate::metrics::unsafety::{UnsafeKind, UnsafeSite};
+use crate::rust::items::is_identifier;
+use std::path::{Path, PathBuf};
+
+/// Marker of a safety comment.
+const SAFETY_MARKER: &str = "SAFETY:";
+
+/// Keyword sequence read so far.
+#[derive(Debug, Clone, Copy)]
+enum Pending {
+    /// `unsafe`.
+    Unsafe,
+    /// `extern`, possibly after `unsafe`, and its ABI string.
+    Extern { is_unsafe: bool },
+    /// `unsafe fn`, waiting for the name (a function pointer type has none).
+    Function,
+}
+
+/// Collects the `unsafe` sites of a file from its tokens.
+///
+/// A site starts at its `unsafe` (or `extern`) keyword; an `unsafe` block
+/// ends at the `}` matching its `{`. Comments directly above a site (with
+/// no blank or code line in between) or on its first line are searched for
+/// `SAFETY:`.
+pub(crate) struct UnsafeTracker {
+    /// File being parsed.
+    path: PathBuf,
+    /// Sites found so far.
+    sites: Vec<UnsafeSite>,
+    /// Brace depth.
+    depth: usize,
+    /// Open `unsafe` and `extern` blocks: depth inside and index into `sites`.
+    open: Vec<(usize, usize)>,
+    /// Keywords read so far, with the line of the first one.
+    pending: Option<(Pending, usize)>,
+    /// The comment lines right above contain a `SAFETY:` marker.
+    safety_above: bool,
+}
+
+impl UnsafeTracker {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            sites: Vec::new(),
+            depth: 0,
+            open: Vec::new(),
+            pending: None,
+            safety_above: false,
+        }
+    }
+
+    /// Processes the tokens and the comment of a physical line.
+    pub fn line(&mut self, line_no: usize, tokens: &[&str], comment: Option<&str>) {
+        let has_marker = comment.is_some_and(|c| c.contains(SAFETY_MARKER));
+        let safety_comment = self.safety_above || has_marker;
+
+        for token in tokens {
+            self.token(line_no, token, safety_comment);
+        }
+
+        self.safety_above = match (tokens.is_empty(), comment) {
+            (true, Some(_)) => self.safety_above || has_marker,
+            _ => false,
+        };
+    }
+
+    /// Returns the sites, closing the blocks left open at `last_line`.
+    pub fn finish(mut self, last_line: usize) -> Vec<UnsafeSite> {
+        for (_, idx) in self.open.drain(..) {
+            self.sites[idx].end_line = last_line;
+        }
+        self.sites
+    }
+
+    fn token(&mut self, line_no: usize, token: &str, safety_comment: bool) {
+        let start = self.pending.map_or(line_no, |(_, line)| line);
+        let mut push = |kind| {
+            self.sites.push(UnsafeSite {
+                path: self.path.clone(),
+                line: start,
+                end_line: start,
+                kind,
+                safety_comment,
+            });
+            self.sites.len() - 1
+        };
+
+        let mut opened = None;
+        self.pending = match (self.pending.map(|(p, _)| p), token) {
+            (None, "unsafe") => Some((Pending::Unsafe, line_no)),
+            (None, "extern") => Some((Pending::Extern { is_unsafe: false }, line_no)),
+            (Some(Pending::Unsafe), "{") => {
+                opened = Some(push(UnsafeKind::Block));
+                None
+            }
+            (Some(Pending::Unsafe), "fn") => Some((Pending::Function, start)),
+            (Some(Pending::Unsafe), "impl") => {
+                push(UnsafeKind::Impl);
+                None
+            }
+            (Some(Pending::Unsafe), "trait") => {
+                push(UnsafeKind::Trait);
+                None
+            }
+            (Some(Pending::Unsafe), "auto") => Some((Pending::Unsafe, start)),
+            (Some(Pending::Unsafe), "extern") => Some((Pending::Extern { is_unsafe: true }, start)),
+            (Some(Pending::Extern { is_unsafe }), "\"") => {
+                Some((Pending::Extern { is_unsafe }, start))
+            }
+            (Some(Pending::Extern { .. }), "{") => {
+                opened = Some(push(UnsafeKind::Extern));
+                None
+            }
+            (Some(Pending::Extern { is_unsafe: true }), "fn") => Some((Pending::Function, start)),
+            (Some(Pending::Function), _) if is_identifier(token) => {
+                push(UnsafeKind::Function);
+                None
+            }
+            _ => None,
+        };
+
+        match token {
+            "{" => {
+                self.depth += 1;
+                if let Some(idx) = opened {
+                    self.open.push((self.depth, idx));
+                }
+            }
+            "}" => {
+                if let Some(&(depth, idx)) = self.open.last()
+                    && depth == self.depth
+                {
+                    self.sites[idx].end_line = line_no;
+                    self.open.pop();
+                }
+                self.depth = self.depth.saturating_sub(1);
+            }
+            _ => {}
+        }
+    }
+}
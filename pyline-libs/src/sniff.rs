@@ -0,0 +1,212 @@
+//! Content sniffing for collected files.
+//!
+//! Reads the head of a file and decides whether it looks like source code
+//! worth counting. Binary blobs and minified sources are reported with
+//! a [`SkipReason`] so the collector can leave them out. Generated files are
+//! kept and classified by [`crate::origin`]. UTF-16 text is not binary,
+//! despite its NUL bytes, and neither is text in a declared or detected
+//! legacy encoding such as CP1251, despite its invalid UTF-8 (see
+//! [`crate::source`]).
+
+use crate::errors::{Operation, PyLineError};
+use crate::source::{decode_legacy_head, decode_utf16_head};
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use tokio::io::AsyncReadExt;
+
+/// Number of bytes read from the head of a file for sniffing.
+pub const SNIFF_BYTES: usize = 8 * 1024;
+
+/// Share of bytes in invalid UTF-8 sequences above which a file is treated
+/// as binary.
+pub const INVALID_UTF8_RATIO: f64 = 0.3;
+
+/// Average line length (in bytes) above which a file is treated as minified.
+pub const MINIFIED_AVG_LINE_LEN: usize = 300;
+
+/// Reason why a file was left out of the collection.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SkipReason {
+    /// File is larger than the configured maximum size.
+    TooLarge {
+        /// Configured limit in bytes.
+        limit: u64,
+    },
+
+    /// File is smaller than the configured minimum size.
+    TooSmall {
+        /// Configured limit in bytes.
+        limit: u64,
+    },
+
+    /// File contains NUL bytes (and is not UTF-16) or too many invalid
+    /// UTF-8 sequences.
+    Binary,
+
+    /// Average line length suggests a minified file.
+    Minified {
+        /// Average line length of the sniffed sample, in bytes.
+        avg_line_len: usize,
+    },
+}
+
+impl Display for SkipReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TooLarge { limit } => write!(f, "larger than {} bytes", limit),
+            Self::TooSmall { limit } => write!(f, "smaller than {} bytes", limit),
+            Self::Binary => write!(f, "binary content"),
+            Self::Minified { avg_line_len } => {
+                write!(f, "minified (average line length {})", avg_line_len)
+            }
+        }
+    }
+}
+
+/// Reads up to [`SNIFF_BYTES`] from the head of the file.
+///
+/// The error tells whether opening or reading the file failed.
+pub async fn read_head(path: &Path) -> Result<Vec<u8>, PyLineError> {
+    let file = tokio::fs::File::open(path)
+        .await
+        .map_err(|err| PyLineError::io_error(Operation::Open, path, err))?;
+    let mut head = Vec::with_capacity(SNIFF_BYTES);
+    file.take(SNIFF_BYTES as u64)
+        .read_to_end(&mut head)
+        .await
+        .map_err(|err| PyLineError::io_error(Operation::Read, path, err))?;
+
+    Ok(head)
+}
+
+/// Sniffs a sample taken from the head of a file.
+///
+/// Returns `None` if the sample looks like regular source code.
+///
+/// ```
+/// use pyline_libs::sniff::{sniff_bytes, SkipReason};
+///
+/// assert_eq!(sniff_bytes(b"def main():\n    pass\n"), None);
+/// assert_eq!(sniff_bytes(b"\x7fELF\0\0\0"), Some(SkipReason::Binary));
+/// ```
+pub fn sniff_bytes(head: &[u8]) -> Option<SkipReason> {
+    if let Some(text) = decode_utf16_head(head) {
+        return minified_line_len(&text, text.len())
+            .map(|avg_line_len| SkipReason::Minified { avg_line_len });
+    }
+    if !has_control_bytes(head)
+        && !is_valid_utf8(head)
+        && let Some(text) = decode_legacy_head(head)
+    {
+        return minified_line_len(&text, head.len())
+            .map(|avg_line_len| SkipReason::Minified { avg_line_len });
+    }
+    if is_binary(head) {
+        return Some(SkipReason::Binary);
+    }
+
+    let text = String::from_utf8_lossy(head);
+
+    minified_line_len(&text, head.len()).map(|avg_line_len| SkipReason::Minified { avg_line_len })
+}
+
+/// Checks the sample for control bytes other than whitespace, which text
+/// in any of the supported encodings does not contain.
+fn has_control_bytes(head: &[u8]) -> bool {
+    head.iter()
+        .any(|&b| (b < 0x20 && !b"\t\n\x0b\x0c\r".contains(&b)) || b == 0x7f)
+}
+
+/// Checks whether the sample is UTF-8, allowing a character cut by the
+/// sample boundary.
+fn is_valid_utf8(head: &[u8]) -> bool {
+    match std::str::from_utf8(head) {
+        Ok(_) => true,
+        Err(err) => err.error_len().is_none(),
+    }
+}
+
+/// Checks the sample for NUL bytes and the share of invalid UTF-8.
+fn is_binary(head: &[u8]) -> bool {
+    if head.contains(&0) {
+        return true;
+    }
+
+    let mut invalid = 0;
+    let mut rest = head;
+    while !rest.is_empty() {
+        match std::str::from_utf8(rest) {
+            Ok(_) => break,
+            Err(err) => {
+                // A sequence cut by the sample boundary is not an error.
+                let Some(len) = err.error_len() else { break };
+                invalid += len;
+                rest = &rest[err.valid_up_to() + len..];
+            }
+        }
+    }
+
+    !head.is_empty() && invalid as f64 / head.len() as f64 > INVALID_UTF8_RATIO
+}
+
+/// Returns the average line length if it exceeds [`MINIFIED_AVG_LINE_LEN`].
+fn minified_line_len(text: &str, sample_len: usize) -> Option<usize> {
+    let lines = text.lines().filter(|l| !l.trim().is_empty()).count();
+    if lines == 0 {
+        return None;
+    }
+
+    let avg_line_len = sample_len / lines;
+    (avg_line_len > MINIFIED_AVG_LINE_LEN).then_some(avg_line_len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_source_is_not_skipped() {
+        assert_eq!(sniff_bytes(b"fn main() {}\n"), None);
+        assert_eq!(sniff_bytes(b""), None);
+    }
+
+    #[test]
+    fn detects_minified_lines() {
+        let line = "x=1;".repeat(200);
+        assert!(matches!(
+            sniff_bytes(line.as_bytes()),
+            Some(SkipReason::Minified { .. })
+        ));
+    }
+
+    #[test]
+    fn invalid_utf8_ratio_marks_binary() {
+        assert_eq!(
+            sniff_bytes(&[0x80, 0x81, 0x82, b'a']),
+            Some(SkipReason::Binary)
+        );
+        assert_eq!(
+            sniff_bytes(&[0xff, 0xfe, 0x01, 0xfd, 0xfc]),
+            Some(SkipReason::Binary)
+        );
+        assert_eq!(sniff_bytes("# комментарий\n".as_bytes()), None);
+    }
+
+    #[test]
+    fn legacy_encodings_are_not_binary() {
+        // "# Комментарий на русском" in CP1251.
+        let cp1251 = b"# \xca\xee\xec\xec\xe5\xed\xf2\xe0\xf0\xe8\xe9 \xed\xe0 \xf0\xf3\xf1\xf1\xea\xee\xec\n";
+        assert_eq!(sniff_bytes(cp1251), None);
+        let declared = b"# -*- coding: cp1251 -*-\n# \xcf\xf0\xe8\xe2\xe5\xf2\n";
+        assert_eq!(sniff_bytes(declared), None);
+    }
+
+    #[test]
+    fn utf16_is_not_binary() {
+        let utf16: Vec<u8> = "fn main() {}\n"
+            .encode_utf16()
+            .flat_map(u16::to_be_bytes)
+            .collect();
+        assert_eq!(sniff_bytes(&utf16), None);
+    }
+}
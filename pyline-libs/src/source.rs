@@ -0,0 +1,490 @@
+//! Decoding of source files and reading them line by line.
+//!
+//! The encoding of a file is detected from, in this order:
+//! - a byte order mark (UTF-8, UTF-16LE, UTF-16BE);
+//! - a PEP 263 declaration (`# -*- coding: latin-1 -*-`) in the first
+//!   two lines;
+//! - NUL bytes at every other position, as ASCII text in UTF-16 without
+//!   a byte order mark has them;
+//! - valid UTF-8, the default; a file with invalid sequences between valid
+//!   non-ASCII characters is taken for damaged UTF-8;
+//! - the other files are in a legacy 8-bit encoding: Cyrillic text in
+//!   CP1251 is made of runs of letters above `0xC0`, while the accented
+//!   letters of Latin-1 mostly stand alone between ASCII letters.
+//!
+//! Bytes that are invalid in the detected encoding make the file fail,
+//! unless decoding is lossy: they are then replaced with U+FFFD.
+//!
+//! Lines are split at `\n`, like `BufReader::lines()`, and [`SourceLines`]
+//! tells the ending of each line. A lone `\r` does not end a line, as in the
+//! Rust lexer; it is reported as an old Mac OS line ending.
+
+use encoding_rs::{Encoding, UTF_8, UTF_16BE, UTF_16LE};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+/// Bytes sampled for the UTF-16 and 8-bit heuristics.
+const SAMPLE_BYTES: usize = 8 * 1024;
+
+/// Share of letter bytes (`0xC0..=0xFF`) next to another letter byte above
+/// which 8-bit text is taken for CP1251.
+const CP1251_RUN_RATIO: f64 = 0.6;
+
+/// Labels of ISO-8859-1, which `encoding_rs` maps to windows-1252 as web
+/// browsers do, unlike Python.
+const LATIN1_LABELS: &[&str] = &[
+    "latin-1",
+    "latin1",
+    "iso-8859-1",
+    "iso8859-1",
+    "iso-ir-100",
+    "l1",
+    "cp819",
+    "8859",
+];
+
+/// Name of ISO-8859-1.
+const LATIN1: &str = "ISO-8859-1";
+
+/// How the encoding of a file was found.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Detection {
+    /// No hint: UTF-8.
+    #[default]
+    Default,
+    /// Byte order mark.
+    Bom,
+    /// PEP 263 `coding` declaration.
+    Declaration,
+    /// Distribution of the bytes.
+    Heuristic,
+}
+
+impl Display for Detection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Default => write!(f, "default"),
+            Self::Bom => write!(f, "byte order mark"),
+            Self::Declaration => write!(f, "coding declaration"),
+            Self::Heuristic => write!(f, "heuristic"),
+        }
+    }
+}
+
+/// Encoding a file was decoded from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileEncoding {
+    /// Name of the encoding (`UTF-8`, `UTF-16LE`, `ISO-8859-1`,
+    /// `windows-1251`, ...).
+    pub name: String,
+    /// How the encoding was found.
+    pub detection: Detection,
+    /// Invalid bytes were replaced with U+FFFD.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub lossy: bool,
+}
+
+impl Default for FileEncoding {
+    fn default() -> Self {
+        Self {
+            name: UTF_8.name().to_string(),
+            detection: Detection::Default,
+            lossy: false,
+        }
+    }
+}
+
+impl FileEncoding {
+    /// Returns `true` for UTF-8 found without hints, decoded without
+    /// errors.
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Returns `true` for UTF-8 found without hints, lossy or not.
+    pub fn is_plain_utf8(&self) -> bool {
+        self.name == UTF_8.name() && self.detection == Detection::Default
+    }
+}
+
+/// A file that is not valid in its encoding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodeError {
+    /// Name of the encoding.
+    pub encoding: String,
+    /// Offset of the first invalid byte in the file, byte order mark
+    /// included, if known.
+    pub offset: Option<usize>,
+}
+
+impl DecodeError {
+    /// Returns the line of the first invalid byte in `bytes`, the data
+    /// that failed to decode, if the offset is known.
+    pub fn line(&self, bytes: &[u8]) -> Option<usize> {
+        let offset = self.offset?.min(bytes.len());
+        Some(bytes[..offset].iter().filter(|&&b| b == b'\n').count() + 1)
+    }
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.offset {
+            Some(offset) => write!(f, "invalid {} at byte {}", self.encoding, offset),
+            None => write!(f, "invalid {} data", self.encoding),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Decoder of a detected encoding.
+#[derive(Clone, Copy)]
+enum Decoder {
+    /// ISO-8859-1: every byte is the code point of the same value.
+    Latin1,
+    /// Any encoding known to `encoding_rs`.
+    Other(&'static Encoding),
+}
+
+impl Decoder {
+    fn from_label(label: &str) -> Option<Self> {
+        let label = label.to_ascii_lowercase().replace('_', "-");
+        if LATIN1_LABELS.contains(&label.as_str()) {
+            return Some(Self::Latin1);
+        }
+        Encoding::for_label(label.as_bytes()).map(Self::Other)
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Latin1 => LATIN1,
+            Self::Other(encoding) => encoding.name(),
+        }
+    }
+
+    fn decode(&self, bytes: &[u8], lossy: bool) -> Result<(String, bool), DecodeError> {
+        let error = |offset| DecodeError {
+            encoding: self.name().to_string(),
+            offset,
+        };
+
+        match self {
+            Self::Latin1 => Ok((bytes.iter().map(|&b| char::from(b)).collect(), false)),
+            Self::Other(encoding) if *encoding == UTF_8 => match std::str::from_utf8(bytes) {
+                Ok(text) => Ok((text.to_string(), false)),
+                Err(_) if lossy => Ok((String::from_utf8_lossy(bytes).into_owned(), true)),
+                Err(err) => Err(error(Some(err.valid_up_to()))),
+            },
+            Self::Other(encoding) => {
+                if let Some(text) =
+                    encoding.decode_without_bom_handling_and_without_replacement(bytes)
+                {
+                    Ok((text.into_owned(), false))
+                } else if lossy {
+                    let (text, _) = encoding.decode_without_bom_handling(bytes);
+                    Ok((text.into_owned(), true))
+                } else {
+                    Err(error(None))
+                }
+            }
+        }
+    }
+}
+
+/// The decoded text of a source file.
+#[derive(Debug, Clone)]
+pub struct SourceText {
+    text: String,
+    encoding: FileEncoding,
+    bom: bool,
+}
+
+impl SourceText {
+    /// Detects the encoding of `bytes` and decodes them. With `lossy`,
+    /// invalid bytes are replaced instead of failing.
+    ///
+    /// ```
+    /// use pyline_libs::source::{Detection, SourceText};
+    ///
+    /// let text = SourceText::decode(b"# coding: cp1251\n# \xcf\xf0\xe8\xe2\xe5\xf2\n", false).unwrap();
+    /// assert_eq!(text.encoding().name, "windows-1251");
+    /// assert_eq!(text.encoding().detection, Detection::Declaration);
+    /// assert!(text.text().ends_with("Привет\n"));
+    ///
+    /// assert!(SourceText::decode(b"\xd0\x9f\xff\n", false).is_err());
+    /// assert!(SourceText::decode(b"\xd0\x9f\xff\n", true).unwrap().encoding().lossy);
+    /// ```
+    pub fn decode(bytes: &[u8], lossy: bool) -> Result<Self, DecodeError> {
+        let (decoder, detection, bom_len) = if let Some((encoding, len)) = Encoding::for_bom(bytes)
+        {
+            (Decoder::Other(encoding), Detection::Bom, len)
+        } else if let Some(decoder) = declared_encoding(bytes).and_then(|l| Decoder::from_label(&l))
+        {
+            (decoder, Detection::Declaration, 0)
+        } else if let Some(encoding) = utf16_encoding(bytes) {
+            (Decoder::Other(encoding), Detection::Heuristic, 0)
+        } else if std::str::from_utf8(bytes).is_ok() || has_utf8_characters(bytes) {
+            (Decoder::Other(UTF_8), Detection::Default, 0)
+        } else {
+            (legacy_encoding(bytes), Detection::Heuristic, 0)
+        };
+
+        let body = &bytes[bom_len..];
+        let (text, lossy) = decoder.decode(body, lossy).map_err(|err| DecodeError {
+            offset: err.offset.map(|offset| offset + bom_len),
+            ..err
+        })?;
+        Ok(Self {
+            text,
+            encoding: FileEncoding {
+                name: decoder.name().to_string(),
+                detection,
+                lossy,
+            },
+            bom: bom_len > 0,
+        })
+    }
+
+    /// Returns the decoded text, without the byte order mark.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the encoding the text was decoded from.
+    pub fn encoding(&self) -> &FileEncoding {
+        &self.encoding
+    }
+
+    /// Returns `true` if the file starts with a UTF-8 byte order mark.
+    pub fn has_utf8_bom(&self) -> bool {
+        self.bom && self.encoding.name == UTF_8.name()
+    }
+
+    /// Returns an iterator over the lines of the text.
+    pub fn lines(&self) -> SourceLines<'_> {
+        SourceLines { rest: &self.text }
+    }
+}
+
+/// Returns the encoding named by a PEP 263 declaration in the first two
+/// lines. The second line is only looked at if the first one holds no code.
+fn declared_encoding(bytes: &[u8]) -> Option<String> {
+    for line in bytes.split(|&b| b == b'\n').take(2) {
+        let line = String::from_utf8_lossy(line);
+        let line = line.trim_start_matches([' ', '\t', '\x0c']);
+        let Some(comment) = line.strip_prefix('#') else {
+            if line.trim().is_empty() {
+                continue;
+            }
+            return None;
+        };
+
+        if let Some(pos) = comment.find("coding")
+            && let Some(rest) = comment[pos + "coding".len()..].strip_prefix([':', '='])
+        {
+            let name: String = rest
+                .trim_start_matches([' ', '\t'])
+                .chars()
+                .take_while(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+                .collect();
+            if !name.is_empty() {
+                return Some(name);
+            }
+        }
+    }
+    None
+}
+
+/// Detects UTF-16 without a byte order mark: in ASCII text, the high byte
+/// of every code unit is NUL.
+fn utf16_encoding(bytes: &[u8]) -> Option<&'static Encoding> {
+    let sample = &bytes[..bytes.len().min(SAMPLE_BYTES) & !1];
+    let units = sample.len() / 2;
+    if units == 0 {
+        return None;
+    }
+
+    let even = sample.iter().step_by(2).filter(|&&b| b == 0).count();
+    let odd = sample
+        .iter()
+        .skip(1)
+        .step_by(2)
+        .filter(|&&b| b == 0)
+        .count();
+    if odd * 2 > units && even * 10 < units {
+        Some(UTF_16LE)
+    } else if even * 2 > units && odd * 10 < units {
+        Some(UTF_16BE)
+    } else {
+        None
+    }
+}
+
+/// Decodes the head of a file if it looks like UTF-16 text, with or
+/// without a byte order mark. Used by [`crate::sniff`], as UTF-16 text has
+/// NUL bytes.
+pub fn decode_utf16_head(head: &[u8]) -> Option<String> {
+    let body = match Encoding::for_bom(head) {
+        Some((encoding, len)) if encoding != UTF_8 => &head[len..],
+        _ => head,
+    };
+    let encoding = utf16_encoding(body)?;
+    Some(encoding.decode_without_bom_handling(body).0.into_owned())
+}
+
+/// Decodes the head of a file that is not UTF-8 but has a byte order mark,
+/// a PEP 263 declaration or looks like CP1251 text. Used by
+/// [`crate::sniff`], as such text is mostly invalid UTF-8. Returns `None`
+/// for anything else, which would only decode as Latin-1.
+pub fn decode_legacy_head(head: &[u8]) -> Option<String> {
+    let decoder = if let Some((encoding, len)) = Encoding::for_bom(head) {
+        return Some(
+            encoding
+                .decode_without_bom_handling(&head[len..])
+                .0
+                .into_owned(),
+        );
+    } else if let Some(decoder) = declared_encoding(head).and_then(|l| Decoder::from_label(&l)) {
+        decoder
+    } else {
+        match legacy_encoding(head) {
+            Decoder::Latin1 => return None,
+            decoder => decoder,
+        }
+    };
+    decoder.decode(head, true).ok().map(|(text, _)| text)
+}
+
+/// Returns `true` if the bytes contain valid multi-byte UTF-8 characters.
+fn has_utf8_characters(bytes: &[u8]) -> bool {
+    bytes[..bytes.len().min(SAMPLE_BYTES)]
+        .utf8_chunks()
+        .any(|chunk| !chunk.valid().is_ascii())
+}
+
+/// Tells CP1251 from Latin-1 text.
+fn legacy_encoding(bytes: &[u8]) -> Decoder {
+    let sample = &bytes[..bytes.len().min(SAMPLE_BYTES)];
+    let is_letter = |b: u8| b >= 0xC0;
+    let letters = sample.iter().filter(|&&b| is_letter(b)).count();
+    let in_runs = sample
+        .iter()
+        .enumerate()
+        .filter(|&(i, &b)| {
+            is_letter(b)
+                && (i > 0 && is_letter(sample[i - 1])
+                    || sample.get(i + 1).is_some_and(|&next| is_letter(next)))
+        })
+        .count();
+
+    if letters > 0 && in_runs as f64 / letters as f64 > CP1251_RUN_RATIO {
+        Decoder::Other(encoding_rs::WINDOWS_1251)
+    } else {
+        Decoder::Latin1
+    }
+}
+
+/// Line ending of a physical line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum LineEnding {
+    /// `\n` (Unix).
+    Lf,
+    /// `\r\n` (Windows).
+    CrLf,
+    /// `\r` (old Mac OS).
+    Cr,
+}
+
+impl Display for LineEnding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Lf => write!(f, "LF"),
+            Self::CrLf => write!(f, "CRLF"),
+            Self::Cr => write!(f, "CR"),
+        }
+    }
+}
+
+/// A physical line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceLine<'a> {
+    /// Text of the line, without the line ending.
+    pub text: &'a str,
+    /// Line ending; `None` for a last line without one.
+    pub ending: Option<LineEnding>,
+    /// Lone `\r` characters in the line.
+    pub lone_cr: usize,
+}
+
+/// Iterator over the lines of a [`SourceText`].
+pub struct SourceLines<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Iterator for SourceLines<'a> {
+    type Item = SourceLine<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rest.is_empty() {
+            return None;
+        }
+
+        let (mut text, ending) = match self.rest.find('\n') {
+            Some(end) => {
+                let line = &self.rest[..end];
+                self.rest = &self.rest[end + 1..];
+                match line.strip_suffix('\r') {
+                    Some(line) => (line, Some(LineEnding::CrLf)),
+                    None => (line, Some(LineEnding::Lf)),
+                }
+            }
+            None => (std::mem::take(&mut self.rest), None),
+        };
+        let ending = match (ending, text.strip_suffix('\r')) {
+            (None, Some(line)) => {
+                text = line;
+                Some(LineEnding::Cr)
+            }
+            (ending, _) => ending,
+        };
+
+        Some(SourceLine {
+            text,
+            ending,
+            lone_cr: text.matches('\r').count(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_encodings() {
+        let utf16: Vec<u8> = "x = 1\n"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect();
+        let text = SourceText::decode(&utf16, false).unwrap();
+        assert_eq!(text.encoding().name, "UTF-16LE");
+        assert_eq!(text.text(), "x = 1\n");
+
+        let cp1251 = b"# \xcf\xf0\xe8\xe2\xe5\xf2 \xec\xe8\xf0\nx = 1\n";
+        assert_eq!(
+            SourceText::decode(cp1251, false).unwrap().encoding().name,
+            "windows-1251"
+        );
+
+        let latin1 = b"# caf\xe9 cr\xe8me\nx = 1\n";
+        let text = SourceText::decode(latin1, false).unwrap();
+        assert_eq!(text.encoding().name, LATIN1);
+        assert!(text.text().starts_with("# café crème"));
+
+        let bom = SourceText::decode(b"\xef\xbb\xbfx\r\ny", false).unwrap();
+        assert!(bom.has_utf8_bom());
+        let lines: Vec<_> = bom.lines().map(|l| (l.text, l.ending)).collect();
+        assert_eq!(lines, [("x", Some(LineEnding::CrLf)), ("y", None)]);
+    }
+}
@@ -2,6 +2,10 @@
 
 use crate::collector::FileData;
 use crate::errors::PyLineError;
+use crate::metrics::CodeMetrics;
+use crate::parser::CodeFilesStat;
+use std::collections::HashMap;
+use std::path::PathBuf;
 
 /// Core trait for language-specific code parsers.
 ///
@@ -65,9 +69,40 @@ pub trait CodeParsers {
     fn parse(&mut self, files: &[FileData])
     -> impl Future<Output = Result<(), PyLineError>> + Send;
 
+    /// Parses a single file into a new instance.
+    ///
+    /// Unlike `parse()`, the per-file result is returned as is, so it can
+    /// be stored (e.g. in [`crate::cache::AnalysisCache`]) before merging.
+    fn parse_one(file: &FileData) -> impl Future<Output = Result<Self::Code, PyLineError>> + Send;
+
+    /// Parses the already read content of a single file into a new
+    /// instance, like `parse_one()` without reading the file again.
+    fn parse_bytes(
+        file: &FileData,
+        bytes: &[u8],
+    ) -> impl Future<Output = Result<Self::Code, PyLineError>> + Send;
+
+    /// Returns the collected file statistics.
+    fn stats(&self) -> &CodeFilesStat;
+
+    /// Returns the keyword frequency counts.
+    fn keywords(&self) -> &HashMap<String, usize>;
+
+    /// Returns the symbols and other per-file metrics.
+    fn metrics(&self) -> &CodeMetrics;
+
+    /// Returns the symbols and other per-file metrics for modification.
+    fn metrics_mut(&mut self) -> &mut CodeMetrics;
+
     /// Increment the files_count value by 1.
     fn count_file(&mut self);
 
+    /// Returns the files that failed to parse, with their errors.
+    fn failures(&self) -> &[(PathBuf, PyLineError)];
+
+    /// Counts a file that failed to parse and keeps its error.
+    fn record_failure(&mut self, path: PathBuf, error: PyLineError);
+
     /// Increment the files_invalid value by 1.
     fn count_invalid_file(&mut self);
 
@@ -76,6 +111,43 @@ pub trait CodeParsers {
 
     /// Increment the code_lines value by 1.
     fn count_code_line(&mut self);
+
+    /// Increment the comment_lines value by 1.
+    fn count_comment_line(&mut self);
+
+    /// Increment the doc_lines value by 1.
+    fn count_doc_line(&mut self);
+}
+
+/// Identity of a language definition.
+///
+/// Used to tell results produced by different parsers apart, for example
+/// to invalidate cached results when the keyword set or the parsing rules
+/// change.
+pub trait LangDefinition {
+    /// Short language name (`python`, `rust`).
+    const NAME: &'static str;
+
+    /// Revision of the parsing rules. Bumped whenever the engine changes
+    /// the way lines are classified or keywords are counted.
+    const ENGINE_REVISION: u32;
+
+    /// Language keywords known to the parser.
+    fn keywords() -> Vec<&'static str>;
+
+    /// Stable fingerprint of the language definition.
+    fn fingerprint() -> u64 {
+        let mut keywords = Self::keywords();
+        keywords.sort_unstable();
+
+        let definition = format!(
+            "{}:{}:{}",
+            Self::NAME,
+            Self::ENGINE_REVISION,
+            keywords.join(",")
+        );
+        xxhash_rust::xxh3::xxh3_64(definition.as_bytes())
+    }
 }
 
 /// Extension trait for collections of [`FileData`] providing verbose
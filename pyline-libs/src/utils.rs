@@ -62,3 +62,57 @@ pub fn get_timestamp() -> u64 {
         .unwrap()
         .as_secs()
 }
+
+/// Parses a human-readable size into a number of bytes.
+///
+/// Accepts a plain number of bytes or a number followed by a `K`, `M`
+/// or `G` suffix (case-insensitive, an optional trailing `b` is allowed).
+///
+/// ```
+/// use pyline_libs::utils::parse_file_size;
+///
+/// assert_eq!(parse_file_size("512").unwrap(), 512);
+/// assert_eq!(parse_file_size("2K").unwrap(), 2048);
+/// assert_eq!(parse_file_size("1mb").unwrap(), 1024 * 1024);
+/// assert!(parse_file_size("ten").is_err());
+/// ```
+pub fn parse_file_size(size: &str) -> Result<u64, PyLineError> {
+    let size = size.trim().to_lowercase();
+    let size = size.strip_suffix('b').unwrap_or(&size);
+
+    let (digits, multiplier) = match size.chars().last() {
+        Some('k') => (&size[..size.len() - 1], 1024),
+        Some('m') => (&size[..size.len() - 1], 1024_u64.pow(2)),
+        Some('g') => (&size[..size.len() - 1], 1024_u64.pow(3)),
+        _ => (size, 1),
+    };
+
+    digits
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| n.checked_mul(multiplier))
+        .ok_or_else(|| PyLineError::scanner_error(format!("invalid file size: {}", size)))
+}
+
+/// Returns the `p`-th percentile (nearest-rank method) of sorted values.
+///
+/// Returns `0` for an empty slice.
+///
+/// ```
+/// use pyline_libs::utils::percentile;
+///
+/// let values = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
+/// assert_eq!(percentile(&values, 50.0), 5);
+/// assert_eq!(percentile(&values, 90.0), 9);
+/// assert_eq!(percentile(&values, 100.0), 10);
+/// assert_eq!(percentile(&[], 50.0), 0);
+/// ```
+pub fn percentile(sorted: &[usize], p: f64) -> usize {
+    if sorted.is_empty() {
+        return 0;
+    }
+
+    let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
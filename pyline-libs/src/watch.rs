@@ -0,0 +1,323 @@
+//! Watch mode: live re-analysis of changed files.
+//!
+//! [`FileWatcher`] turns file system notifications (inotify and friends,
+//! falling back to polling) into batches of changed paths. [`WatchState`]
+//! keeps the per-file results of the last analysis and re-parses only
+//! the files of each batch, reporting what changed as a [`WatchUpdate`].
+//! Files that fail to parse are kept with their error in the failures of
+//! the totals until they change again.
+
+use crate::collector::{Collector, FileData};
+use crate::errors::PyLineError;
+use crate::traits::CodeParsers;
+use notify::{Event, EventKind, PollWatcher, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
+
+/// Default interval of the polling watcher.
+pub const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Default time to wait for more events before reporting a batch.
+pub const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// How file changes are detected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchMode {
+    /// Native notifications of the platform (inotify on Linux).
+    Native,
+    /// Periodic rescans of the watched trees.
+    Polling,
+}
+
+impl Display for WatchMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Native => write!(f, "native notifications"),
+            Self::Polling => write!(f, "polling"),
+        }
+    }
+}
+
+/// Watches directory trees and reports changed files in batches.
+pub struct FileWatcher {
+    /// Receives changed paths from the notification thread.
+    events: UnboundedReceiver<PathBuf>,
+    /// Keeps the underlying watcher alive.
+    _watcher: Box<dyn Watcher + Send>,
+    /// The way changes are detected.
+    mode: WatchMode,
+}
+
+impl FileWatcher {
+    /// Watches `roots` recursively with native notifications, falling back
+    /// to polling if they are unavailable (e.g. inotify limits are reached
+    /// or the file system does not support them).
+    pub fn new(roots: &[PathBuf]) -> Result<Self, PyLineError> {
+        let (tx, events) = unbounded_channel();
+
+        match Self::native(roots, tx.clone()) {
+            Ok(watcher) => Ok(Self {
+                events,
+                _watcher: watcher,
+                mode: WatchMode::Native,
+            }),
+            Err(_) => Ok(Self {
+                events,
+                _watcher: Self::poll(roots, tx, POLL_INTERVAL)?,
+                mode: WatchMode::Polling,
+            }),
+        }
+    }
+
+    /// Watches `roots` recursively by rescanning them every `interval`.
+    pub fn polling(roots: &[PathBuf], interval: Duration) -> Result<Self, PyLineError> {
+        let (tx, events) = unbounded_channel();
+
+        Ok(Self {
+            events,
+            _watcher: Self::poll(roots, tx, interval)?,
+            mode: WatchMode::Polling,
+        })
+    }
+
+    /// Returns the way changes are detected.
+    pub fn mode(&self) -> WatchMode {
+        self.mode
+    }
+
+    /// Waits for the next batch of changed files.
+    ///
+    /// After the first change arrives, keeps collecting changes until none
+    /// arrive for `debounce`, so that saving several files at once produces
+    /// a single batch. Returns `None` when the watcher stopped.
+    pub async fn next_batch(&mut self, debounce: Duration) -> Option<Vec<PathBuf>> {
+        let mut batch = BTreeSet::new();
+        batch.insert(self.events.recv().await?);
+
+        while let Ok(Some(path)) = tokio::time::timeout(debounce, self.events.recv()).await {
+            batch.insert(path);
+        }
+
+        Some(batch.into_iter().collect())
+    }
+
+    fn native(
+        roots: &[PathBuf],
+        tx: UnboundedSender<PathBuf>,
+    ) -> notify::Result<Box<dyn Watcher + Send>> {
+        let mut watcher = RecommendedWatcher::new(Self::handler(tx), notify::Config::default())?;
+        for root in roots {
+            watcher.watch(root, RecursiveMode::Recursive)?;
+        }
+
+        Ok(Box::new(watcher))
+    }
+
+    fn poll(
+        roots: &[PathBuf],
+        tx: UnboundedSender<PathBuf>,
+        interval: Duration,
+    ) -> Result<Box<dyn Watcher + Send>, PyLineError> {
+        let config = notify::Config::default().with_poll_interval(interval);
+        let mut watcher = PollWatcher::new(Self::handler(tx), config)
+            .map_err(|err| PyLineError::scanner_error(format!("watcher: {}", err)))?;
+        for root in roots {
+            watcher
+                .watch(root, RecursiveMode::Recursive)
+                .map_err(|err| PyLineError::scanner_error(format!("watcher: {}", err)))?;
+        }
+
+        Ok(Box::new(watcher))
+    }
+
+    /// Forwards the paths of content-changing events to the channel.
+    fn handler(tx: UnboundedSender<PathBuf>) -> impl FnMut(notify::Result<Event>) + Send {
+        move |event: notify::Result<Event>| {
+            let Ok(event) = event else { return };
+            if matches!(
+                event.kind,
+                EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+            ) {
+                for path in event.paths {
+                    let _ = tx.send(path);
+                }
+            }
+        }
+    }
+}
+
+/// Changes of the totals caused by a batch of changed files.
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct WatchUpdate {
+    /// Changed files that are (or were) part of the analysis.
+    pub changed: Vec<PathBuf>,
+    /// Changed files that failed to parse; their errors are listed in the
+    /// failures of the totals.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub failed: Vec<PathBuf>,
+    /// Change of the number of analyzed files.
+    pub files_delta: i64,
+    /// Change of the total number of lines.
+    pub lines_delta: i64,
+    /// Change of the number of code lines.
+    pub code_lines_delta: i64,
+    /// Changes of keyword counts; keywords that did not change are omitted.
+    pub keyword_deltas: BTreeMap<String, i64>,
+}
+
+impl WatchUpdate {
+    /// Returns `true` if none of the changed files is part of the analysis.
+    pub fn is_empty(&self) -> bool {
+        self.changed.is_empty()
+    }
+}
+
+impl Display for WatchUpdate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Changed:")?;
+        for path in &self.changed {
+            write!(f, "\n  {}", path.display())?;
+        }
+        if !self.failed.is_empty() {
+            write!(f, "\nFailed to parse:")?;
+            for path in &self.failed {
+                write!(f, "\n  {}", path.display())?;
+            }
+        }
+        write!(
+            f,
+            "\nFiles: {:+}, lines: {:+}, code lines: {:+}",
+            self.files_delta, self.lines_delta, self.code_lines_delta
+        )?;
+        if !self.keyword_deltas.is_empty() {
+            write!(f, "\nKeywords:")?;
+            for (keyword, delta) in &self.keyword_deltas {
+                write!(f, "\n  {} {:+}", keyword, delta)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Per-file results of a watched analysis.
+pub struct WatchState<C> {
+    /// Results of every analyzed file.
+    files: BTreeMap<PathBuf, C>,
+    /// Errors of the files that failed to parse.
+    failures: BTreeMap<PathBuf, PyLineError>,
+    /// Merged results of all files.
+    totals: C,
+}
+
+impl<C: CodeParsers<Code = C>> WatchState<C> {
+    /// Parses `files` and remembers the result of each one.
+    ///
+    /// Files that fail to parse are counted as invalid, with their error,
+    /// until they change again.
+    pub async fn new(files: &[FileData]) -> Self {
+        let tasks: Vec<_> = files.iter().map(C::parse_one).collect();
+        let results = futures::future::join_all(tasks).await;
+
+        let mut state = Self {
+            files: BTreeMap::new(),
+            failures: BTreeMap::new(),
+            totals: C::new(),
+        };
+        for (file, result) in files.iter().zip(results) {
+            match result {
+                Ok(result) => {
+                    state.files.insert(file.path.clone(), result);
+                }
+                Err(error) => {
+                    state.failures.insert(file.path.clone(), error);
+                }
+            }
+        }
+        state.totals = state.merge_all();
+        state
+    }
+
+    /// Returns the merged results of all files.
+    pub fn totals(&self) -> &C {
+        &self.totals
+    }
+
+    /// Re-checks the changed paths against the collector filters, re-parses
+    /// the files that are still collected and drops the ones that are not.
+    /// Files that fail to parse are kept with their error and listed in
+    /// [`WatchUpdate::failed`].
+    pub async fn update(
+        &mut self,
+        collector: &Collector,
+        changed: &[PathBuf],
+    ) -> Result<WatchUpdate, PyLineError> {
+        let mut update = WatchUpdate::default();
+
+        for path in changed {
+            let result = match collector.check_file(path).await? {
+                Some(file) => Some(C::parse_one(&file).await),
+                None => None,
+            };
+
+            let parsed = self.files.remove(path).is_some();
+            let failed = self.failures.remove(path).is_some();
+            let relevant = match result {
+                Some(Ok(result)) => {
+                    self.files.insert(path.clone(), result);
+                    true
+                }
+                Some(Err(error)) => {
+                    self.failures.insert(path.clone(), error);
+                    update.failed.push(path.clone());
+                    true
+                }
+                None => parsed || failed,
+            };
+            if relevant {
+                update.changed.push(path.clone());
+            }
+        }
+
+        let totals = self.merge_all();
+        Self::fill_deltas(&mut update, &self.totals, &totals);
+        self.totals = totals;
+
+        Ok(update)
+    }
+
+    fn merge_all(&self) -> C {
+        let mut totals = C::new();
+        for result in self.files.values() {
+            totals.merge_ref(result);
+        }
+        for (path, error) in &self.failures {
+            totals.record_failure(path.clone(), error.clone());
+        }
+        totals
+    }
+
+    fn fill_deltas(update: &mut WatchUpdate, before: &C, after: &C) {
+        let (old, new) = (before.stats(), after.stats());
+        update.files_delta = new.num_files_total as i64 - old.num_files_total as i64;
+        update.lines_delta = new.lines_total as i64 - old.lines_total as i64;
+        update.code_lines_delta = new.code_lines as i64 - old.code_lines as i64;
+
+        let keywords: BTreeSet<_> = before
+            .keywords()
+            .keys()
+            .chain(after.keywords().keys())
+            .collect();
+        for keyword in keywords {
+            let old = before.keywords().get(keyword).copied().unwrap_or(0) as i64;
+            let new = after.keywords().get(keyword).copied().unwrap_or(0) as i64;
+            if old != new {
+                update.keyword_deltas.insert(keyword.clone(), new - old);
+            }
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use pyline_libs::cache::AnalysisCache;
+use pyline_libs::collector::FileData;
+use pyline_libs::errors::PyLineError;
+use pyline_libs::origin::Origin;
+use pyline_libs::parser::Python;
+use pyline_libs::traits::CodeParsers;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+use uuid::Uuid;
+
+async fn setup_test_dir() -> PathBuf {
+    let tmp_dir = std::env::temp_dir().join(format!("cache_test_{}", Uuid::new_v4()));
+    fs::create_dir_all(&tmp_dir).await.unwrap();
+    tmp_dir
+}
+
+async fn run_cached(cache_dir: &Path, files: &[FileData]) -> Result<(Python, usize), PyLineError> {
+    let mut cache = AnalysisCache::<Python>::load(cache_dir).await;
+    let mut stats = Python::new();
+    cache.parse(&mut stats, files).await?;
+    cache.save().await?;
+
+    Ok((stats, cache.stat().hits))
+}
+
+#[tokio::test]
+async fn test_cache_reuses_unchanged_files() -> Result<(), PyLineError> {
+    let root = setup_test_dir().await;
+    let cache_dir = root.join(".pyline-cache");
+    let source = root.join("main.py");
+    fs::write(&source, "def main():\n    return 1\n").await?;
+    let files = vec![FileData::new(source.clone(), 0)];
+
+    let (fresh, hits) = run_cached(&cache_dir, &files).await?;
+    assert_eq!(hits, 0);
+
+    let (cached, hits) = run_cached(&cache_dir, &files).await?;
+    assert_eq!(hits, 1);
+    assert_eq!(cached.stats.code_lines, fresh.stats.code_lines);
+    assert_eq!(cached.keywords, fresh.keywords);
+    // Line hashes and widths survive the cache but stay out of the serialized results.
+    assert_eq!(
+        cached.metrics().files[0].code_lines,
+        fresh.metrics().files[0].code_lines
+    );
+    assert!(!cached.metrics().files[0].line_widths.is_empty());
+    assert_eq!(
+        cached.metrics().files[0].line_widths,
+        fresh.metrics().files[0].line_widths
+    );
+    let json = serde_json::to_string(&cached).unwrap();
+    assert!(!json.contains("\"abstracted\"") && !json.contains("\"segments\""));
+
+    fs::write(
+        &source,
+        "def main():\n    pass\n\n\ndef other():\n    pass\n",
+    )
+    .await?;
+    let (changed, hits) = run_cached(&cache_dir, &files).await?;
+    assert_eq!(hits, 0);
+    assert_eq!(changed.keywords.get("def"), Some(&2));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_cache_prunes_files_and_checks_origin() -> Result<(), PyLineError> {
+    let root = setup_test_dir().await;
+    let cache_dir = root.join(".pyline-cache");
+    let mut files = Vec::new();
+    for name in ["a.py", "b.py"] {
+        let path = root.join(name);
+        fs::write(&path, "x = 1\n").await?;
+        files.push(FileData::new(path, 0));
+    }
+
+    let (_, hits) = run_cached(&cache_dir, &files).await?;
+    assert_eq!(hits, 0);
+    // `b.py` is not analyzed, so its entry is dropped.
+    let (_, hits) = run_cached(&cache_dir, &files[..1]).await?;
+    assert_eq!(hits, 1);
+    let (_, hits) = run_cached(&cache_dir, &files).await?;
+    assert_eq!(hits, 1);
+
+    // A file whose origin changed is parsed again.
+    let vendored = vec![FileData::new(files[0].path.clone(), 0).with_origin(Origin::Vendored)];
+    let (stats, hits) = run_cached(&cache_dir, &vendored).await?;
+    assert_eq!(hits, 0);
+    assert_eq!(stats.stats.by_origin[&Origin::Vendored].files, 1);
+
+    fs::remove_dir_all(&root).await?;
+    Ok(())
+}
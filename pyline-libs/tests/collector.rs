@@ -1,5 +1,10 @@
 use pyline_libs::collector::Collector;
-use pyline_libs::errors::PyLineError;
+use pyline_libs::dedupe::DuplicateKind;
+use pyline_libs::errors::{ErrorKind, Operation, PyLineError};
+use pyline_libs::origin::Origin;
+use pyline_libs::parser::Python;
+use pyline_libs::sniff::read_head;
+use pyline_libs::traits::CodeParsers;
 use std::fs::File;
 use std::path::PathBuf;
 use tokio::fs;
@@ -103,3 +108,295 @@ async fn test_exclude_dot_dir_panics() {
     // Этот вызов должен паниковать из-за .git в exclude_dirs
     Collector::new(&root).exclude_dirs([".git"]);
 }
+
+#[tokio::test]
+async fn test_size_limits_skip_files() -> Result<(), PyLineError> {
+    let root = setup_test_dir().await;
+    std::fs::write(root.join("big.py"), "x = 1\n".repeat(100))?;
+
+    let files = Collector::new(&root)
+        .extensions(["py"])
+        .min_file_size(1)
+        .max_file_size(64)
+        .complete()
+        .await?;
+
+    assert_eq!(files.num_files(), 0);
+    assert_eq!(files.num_skipped(), 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_binary_files_skipped() -> Result<(), PyLineError> {
+    let root = setup_test_dir().await;
+    std::fs::write(root.join("blob.py"), b"\x00\x01\x02binary")?;
+
+    let files = Collector::new(&root).extensions(["py"]).complete().await?;
+
+    assert_eq!(files.num_files(), 1);
+    assert!(files.skipped()[0].path.ends_with("blob.py"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_cp1251_files_collected() -> Result<(), PyLineError> {
+    let root = setup_test_dir().await;
+    // Cyrillic comments and names in CP1251, mostly invalid UTF-8.
+    let source = b"# \xca\xee\xec\xec\xe5\xed\xf2\xe0\xf0\xe8\xe9 \xed\xe0 \xf0\xf3\xf1\xf1\xea\xee\xec \xff\xe7\xfb\xea\xe5\ndef \xef\xf0\xe8\xe2\xe5\xf2():\n    # \xe5\xf9\xb8 \xee\xe4\xe8\xed \xea\xee\xec\xec\xe5\xed\xf2\xe0\xf0\xe8\xe9\n    return 1\n";
+    std::fs::write(root.join("ru.py"), source)?;
+    let mut declared = b"# -*- coding: cp1251 -*-\n".to_vec();
+    declared.extend_from_slice(source);
+    std::fs::write(root.join("declared.py"), declared)?;
+
+    let files = Collector::new(&root).extensions(["py"]).complete().await?;
+
+    assert!(!files.has_skipped(), "{:?}", files.skipped());
+    assert_eq!(files.num_files(), 3);
+
+    let mut stats = Python::new();
+    stats.parse(files.files()).await?;
+    assert!(stats.failures().is_empty());
+    let encodings: Vec<_> = stats
+        .metrics()
+        .files
+        .iter()
+        .filter(|file| !file.path.ends_with("example.py"))
+        .map(|file| file.encoding.name.as_str())
+        .collect();
+    assert_eq!(encodings, ["windows-1251", "windows-1251"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_origin_classification() -> Result<(), PyLineError> {
+    let root = setup_test_dir().await;
+    std::fs::write(root.join("msg_pb2.py"), "x = 1\n")?;
+    std::fs::create_dir_all(root.join("third_party"))?;
+    std::fs::write(root.join("third_party").join("six.py"), "x = 1\n")?;
+
+    let files = Collector::new(&root).extensions(["py"]).complete().await?;
+
+    let origin_of = |name: &str| {
+        files
+            .files()
+            .iter()
+            .find(|f| f.path.ends_with(name))
+            .map(|f| f.origin())
+    };
+    assert_eq!(origin_of("example.py"), Some(Origin::Authored));
+    assert_eq!(origin_of("msg_pb2.py"), Some(Origin::Generated));
+    assert_eq!(origin_of("six.py"), Some(Origin::Vendored));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_depth_limits() -> Result<(), PyLineError> {
+    let root = setup_test_dir().await;
+    let nested = root.join("pkg").join("sub");
+    fs::create_dir_all(&nested).await?;
+    File::create(root.join("pkg").join("mod.py"))?;
+    File::create(nested.join("deep.py"))?;
+
+    let shallow = Collector::new(&root)
+        .extensions(["py"])
+        .max_depth(1)
+        .complete()
+        .await?;
+    assert_eq!(shallow.num_files(), 1);
+
+    let deep = Collector::new(&root)
+        .extensions(["py"])
+        .min_depth(2)
+        .complete()
+        .await?;
+    assert_eq!(deep.num_files(), 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_overlapping_roots_are_deduplicated() -> Result<(), PyLineError> {
+    let root = setup_test_dir().await;
+    let pkg = root.join("pkg");
+    fs::create_dir_all(&pkg).await?;
+    File::create(pkg.join("mod.py"))?;
+
+    let files = Collector::new(&pkg)
+        .add_roots([&root, &pkg])
+        .extensions(["py"])
+        .complete()
+        .await?;
+
+    assert_eq!(files.num_files(), 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_files_from_list() -> Result<(), PyLineError> {
+    let root = setup_test_dir().await;
+    let listed = root.join("example.py");
+
+    let files = Collector::new(&root)
+        .extensions(["py"])
+        .files_from([listed.clone(), listed, root.join("README.md")])
+        .complete()
+        .await?;
+
+    assert_eq!(files.num_files(), 1);
+
+    // Directory exclusions apply to listed files too.
+    let hidden = root.join(".git").join("hidden.py");
+    let files = Collector::new(&root)
+        .extensions(["py"])
+        .ignore_dot_dirs(true)
+        .files_from([hidden])
+        .complete()
+        .await?;
+    assert_eq!(files.num_files(), 0);
+
+    fs::create_dir_all(root.join("build")).await?;
+    let built = root.join("build").join("gen.py");
+    File::create(&built)?;
+    let files = Collector::new(&root)
+        .extensions(["py"])
+        .exclude_dirs(["build"])
+        .files_from([built])
+        .complete()
+        .await?;
+    assert_eq!(files.num_files(), 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_dedupe_identical_files() -> Result<(), PyLineError> {
+    let root = setup_test_dir().await;
+    fs::create_dir_all(root.join("a")).await?;
+    fs::create_dir_all(root.join("b")).await?;
+    std::fs::write(root.join("a/helpers.py"), "def helper():\n    pass\n")?;
+    std::fs::write(root.join("b/helpers.py"), "def helper():\n    pass\n")?;
+    std::fs::write(root.join("b/other.py"), "def other():\n    pass\n")?;
+    std::fs::write(root.join("b/sizes.py"), "def hepler():\n    pass\n")?;
+    std::fs::hard_link(root.join("a/helpers.py"), root.join("linked.py"))?;
+
+    let files = Collector::new(&root).extensions(["py"]).complete().await?;
+    assert_eq!(files.num_files(), 6);
+    assert!(!files.has_duplicates());
+
+    let files = Collector::new(&root)
+        .extensions(["py"])
+        .dedupe(true)
+        .complete()
+        .await?;
+    // Same size, different bytes: kept.
+    assert_eq!(files.num_files(), 4);
+    assert_eq!(files.num_duplicates(), 2);
+
+    let group = &files.duplicates()[0];
+    assert!(group.kept.ends_with("a/helpers.py"));
+    assert_eq!(group.copies.len(), 2);
+    assert!(group.copies[0].0.ends_with("b/helpers.py"));
+    assert_eq!(group.copies[0].1, DuplicateKind::SameContent);
+    assert!(group.copies[1].0.ends_with("linked.py"));
+    assert_eq!(group.copies[1].1, DuplicateKind::SameFile);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_errors_carry_path_and_kind() -> Result<(), PyLineError> {
+    let root = std::env::temp_dir().join(format!("collector_test_{}", Uuid::new_v4()));
+
+    let files = Collector::new(&root)
+        .extensions(["py"])
+        .skip_errors(true)
+        .complete()
+        .await?;
+
+    let [error] = files.errors().as_slice() else {
+        panic!("expected one error");
+    };
+    assert_eq!(error.kind(), ErrorKind::NotFound);
+    assert_eq!(error.operation(), Some(Operation::ReadDir));
+    assert_eq!(error.path(), Some(root.as_path()));
+    // The source is not repeated in the message of the error itself.
+    let source = std::error::Error::source(error).expect("I/O error source");
+    assert!(!error.to_string().contains(&source.to_string()));
+    assert_eq!(error.clone().to_string(), error.to_string());
+    assert_eq!(files.error_summary()[&ErrorKind::NotFound], 1);
+
+    let err = Collector::new(&root)
+        .extensions(["py"])
+        .skip_errors(false)
+        .complete()
+        .await
+        .err()
+        .expect("missing root fails without skip_errors");
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+
+    // Sniffing tells a file that cannot be opened from one that cannot be
+    // read (a directory opens, but fails to read).
+    let missing = read_head(&root.join("missing.py")).await.unwrap_err();
+    assert_eq!(missing.operation(), Some(Operation::Open));
+    let dir = read_head(&std::env::temp_dir()).await.unwrap_err();
+    assert_eq!(dir.operation(), Some(Operation::Read));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_check_file_matches_listed_paths() -> Result<(), PyLineError> {
+    let root = setup_test_dir().await;
+    fs::create_dir_all(root.join("sub")).await?;
+    let changed = root.join("example.py");
+    // Listed as typed, not in canonical form.
+    let listed = root.join("sub").join("..").join("example.py");
+
+    let collector = Collector::new(&root)
+        .extensions(["py"])
+        .files_from([listed]);
+    let file = collector.check_file(&changed).await?;
+    assert!(file.is_some());
+    assert!(
+        collector
+            .check_file(&root.join(".git").join("hidden.py"))
+            .await?
+            .is_none()
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_listed_files_under_dot_parent() -> Result<(), PyLineError> {
+    // Only the directories below the root are checked, not its parents.
+    let parent = std::env::temp_dir().join(format!(".work_{}", Uuid::new_v4()));
+    let project = parent.join("proj");
+    fs::create_dir_all(project.join(".cache")).await?;
+    let source = project.join("a.py");
+    File::create(&source)?;
+    let hidden = project.join(".cache").join("b.py");
+    File::create(&hidden)?;
+
+    let collector = Collector::new(&project)
+        .extensions(["py"])
+        .ignore_dot_dirs(true)
+        .files_from([source.clone(), hidden.clone()]);
+    let files = collector.complete().await?;
+    assert_eq!(files.num_files(), 1);
+    assert_eq!(files.files()[0].path, source);
+    assert!(collector.check_file(&source).await?.is_some());
+    assert!(collector.check_file(&hidden).await?.is_none());
+
+    let walked = Collector::new(&project).extensions(["py"]);
+    assert!(walked.check_file(&source).await?.is_some());
+    assert!(walked.check_file(&hidden).await?.is_none());
+
+    fs::remove_dir_all(&parent).await?;
+    Ok(())
+}
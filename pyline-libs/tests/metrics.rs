@@ -0,0 +1,979 @@
+use pyline_libs::collector::FileData;
+use pyline_libs::errors::PyLineError;
+use pyline_libs::metrics::FileMetrics;
+use pyline_libs::metrics::comments::CommentReport;
+use pyline_libs::metrics::complexity::ComplexityReport;
+use pyline_libs::metrics::duplicates::DuplicateReport;
+use pyline_libs::metrics::encodings::EncodingReport;
+use pyline_libs::metrics::gates::Gates;
+use pyline_libs::metrics::graph::DependencyGraph;
+use pyline_libs::metrics::halstead::{Halstead, HalsteadReport, maintainability_index};
+use pyline_libs::metrics::line_length::LineLengthReport;
+use pyline_libs::metrics::macros::MacroReport;
+use pyline_libs::metrics::nesting::NestingReport;
+use pyline_libs::metrics::panics::{PanicKind, PanicReport};
+use pyline_libs::metrics::symbols::{SymbolKind, SymbolReport, Visibility};
+use pyline_libs::metrics::tasks::TaskReport;
+use pyline_libs::metrics::type_hints::TypeHintReport;
+use pyline_libs::metrics::unsafety::{UnsafeKind, UnsafeReport};
+use pyline_libs::metrics::usage::{Import, ImportOrigin, UsageReport};
+use pyline_libs::metrics::whitespace::WhitespaceReport;
+use pyline_libs::parser::{Python, Rust};
+use pyline_libs::source::{Detection, LineEnding};
+use pyline_libs::traits::CodeParsers;
+use tokio::fs;
+use uuid::Uuid;
+
+/// Writes `content` to a temporary file and parses it.
+async fn parse_source<C: CodeParsers<Code = C>>(
+    name: &str,
+    content: &str,
+) -> Result<C, PyLineError> {
+    let root = std::env::temp_dir().join(format!("metrics_test_{}", Uuid::new_v4()));
+    fs::create_dir_all(&root).await?;
+    let path = root.join(name);
+    fs::write(&path, content).await?;
+
+    let result = C::parse_one(&FileData::new(path, content.len() as u64)).await;
+    fs::remove_dir_all(&root).await?;
+    result
+}
+
+const PYTHON_SYMBOLS: &str = r#"import os
+
+@dataclass
+class Point:
+    """A point.
+
+    def not_a_method(self):
+    """
+    x: int
+
+    @property
+    def norm(self):
+        return (self.x ** 2 +
+    1)
+
+    async def fetch(self):
+        def helper():
+            return 1
+        return helper()
+
+
+def main(key=lambda v: v):
+    return sorted([], key=key)
+"#;
+
+#[tokio::test]
+async fn test_python_symbol_inventory() -> Result<(), PyLineError> {
+    let result: Python = parse_source("point.py", PYTHON_SYMBOLS).await?;
+    let symbols = &result.metrics().symbols;
+
+    let spans: Vec<_> = symbols
+        .iter()
+        .map(|s| (s.name.as_str(), s.kind, s.start_line, s.end_line))
+        .collect();
+    assert_eq!(
+        spans,
+        vec![
+            ("Point", SymbolKind::Class, 4, 19),
+            ("norm", SymbolKind::Method, 12, 14),
+            ("fetch", SymbolKind::Method, 16, 19),
+            ("helper", SymbolKind::NestedFunction, 17, 18),
+            ("main", SymbolKind::Function, 22, 23),
+            ("<lambda>", SymbolKind::Lambda, 22, 22),
+        ]
+    );
+    assert_eq!(symbols[0].decorators, vec!["dataclass"]);
+    assert_eq!(symbols[1].decorators, vec!["property"]);
+    assert!(symbols[2].is_async);
+
+    let report = SymbolReport::new(symbols, 2);
+    assert_eq!(report.functions, 4);
+    assert_eq!(report.counts.get(&SymbolKind::Lambda), Some(&1));
+    assert_eq!(report.longest[0].name, "fetch");
+    assert_eq!(report.longest.len(), 2);
+
+    Ok(())
+}
+
+const RUST_ITEMS: &str = r#"//! Items.
+use std::fmt;
+
+#[derive(Debug)]
+pub struct Point {
+    x: i32, // { not a brace
+}
+
+pub(crate) enum Shape { Dot(Point) }
+
+pub trait Area {
+    fn area(&self) -> f64;
+}
+
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{{{}", self.x)
+    }
+}
+
+impl<T> Wrapper<T> where T: Clone {
+    pub async fn get(&self) -> impl Iterator<Item = u8> {
+        fn helper() {}
+        std::iter::empty()
+    }
+}
+
+macro_rules! square {
+    ($x:expr) => { $x * $x };
+}
+
+pub(super) mod inner;
+type Id = u64;
+"#;
+
+#[tokio::test]
+async fn test_rust_item_inventory() -> Result<(), PyLineError> {
+    let result: Rust = parse_source("items.rs", RUST_ITEMS).await?;
+    let symbols = &result.metrics().symbols;
+
+    let items: Vec<_> = symbols
+        .iter()
+        .map(|s| {
+            (
+                s.name.as_str(),
+                s.kind,
+                s.visibility.unwrap(),
+                s.start_line,
+                s.end_line,
+            )
+        })
+        .collect();
+    assert_eq!(
+        items,
+        vec![
+            ("Point", SymbolKind::Struct, Visibility::Public, 5, 7),
+            ("Shape", SymbolKind::Enum, Visibility::Crate, 9, 9),
+            ("Area", SymbolKind::Trait, Visibility::Public, 11, 13),
+            ("area", SymbolKind::Method, Visibility::Private, 12, 12),
+            (
+                "fmt::Display for Point",
+                SymbolKind::TraitImpl,
+                Visibility::Private,
+                15,
+                19
+            ),
+            ("fmt", SymbolKind::Method, Visibility::Private, 16, 18),
+            (
+                "Wrapper<T>",
+                SymbolKind::InherentImpl,
+                Visibility::Private,
+                21,
+                26
+            ),
+            ("get", SymbolKind::Method, Visibility::Public, 22, 25),
+            (
+                "helper",
+                SymbolKind::NestedFunction,
+                Visibility::Private,
+                23,
+                23
+            ),
+            (
+                "square",
+                SymbolKind::MacroRules,
+                Visibility::Private,
+                28,
+                30
+            ),
+            ("inner", SymbolKind::Module, Visibility::Restricted, 32, 32),
+            ("Id", SymbolKind::TypeAlias, Visibility::Private, 33, 33),
+        ]
+    );
+    assert!(symbols[7].is_async);
+
+    let report = SymbolReport::new(symbols, 10);
+    let api: usize = report.api_surface.values().map(|api| api.items).sum();
+    assert_eq!(api, 3);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_python_complexity() -> Result<(), PyLineError> {
+    let source = "def check(x, items):\n    if x and items:\n        return [i for i in items if i]\n    match x:\n        case 1:\n            return 'if and or'\n";
+    let result: Python = parse_source("check.py", source).await?;
+    let symbols = &result.metrics().symbols;
+
+    // 1 + if, and, for, if, case.
+    assert_eq!(symbols[0].complexity, 6);
+
+    let gates = Gates {
+        max_complexity: Some(5),
+        ..Default::default()
+    };
+    let violations = gates.check(result.metrics());
+    assert_eq!(violations.len(), 1);
+    assert_eq!(
+        violations[0].location.as_deref(),
+        Some(symbols[0].location().as_str())
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_rust_complexity() -> Result<(), PyLineError> {
+    let source = r#"fn run(a: &&str, items: &[u8]) -> Result<(), Error> {
+    let f = || a.is_empty();
+    for x in items {
+        if *x > 0 && f() || x.is_ascii() {
+            read()?;
+        }
+    }
+    match a.len() {
+        0 => Ok(()),
+        _ => Err(Error),
+    }
+}
+"#;
+    let result: Rust = parse_source("run.rs", source).await?;
+
+    // 1 + for, if, &&, ||, ?, two match arms.
+    assert_eq!(result.metrics().symbols[0].complexity, 8);
+
+    let report = ComplexityReport::new(&result.metrics().symbols, 10);
+    assert_eq!(report.max_complexity, 8);
+    assert_eq!(report.histogram[1].functions, 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_nesting_depth() -> Result<(), PyLineError> {
+    let source = "def f(x):\n    if x:\n\tfor i in x:\n            pass\n    return (1,\n  2)\n";
+    let python: Python = parse_source("nested.py", source).await?;
+    let file = &python.metrics().files[0];
+    assert_eq!(file.nesting.max, 3);
+    assert_eq!(file.nesting.deepest_line, 4);
+    assert_eq!(file.mixed_indentation, vec![3]);
+    assert_eq!(python.metrics().symbols[0].max_nesting, 3);
+
+    let source =
+        "fn f(x: bool) {\n    if x {\n        loop {\n            break;\n        }\n    }\n}\n";
+    let rust: Rust = parse_source("nested.rs", source).await?;
+    let file = &rust.metrics().files[0];
+    assert_eq!(file.nesting.max, 3);
+    assert_eq!(file.nesting.deepest_line, 4);
+    assert_eq!(rust.metrics().symbols[0].max_nesting, 3);
+
+    let report = NestingReport::new(&rust.metrics().files, &rust.metrics().symbols, 5);
+    assert_eq!(report.max_depth, 3);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_task_markers() -> Result<(), PyLineError> {
+    let source = "def f():\n    \"\"\"FIXME: handle errors.\"\"\"\n    x = '# TODO: not a comment'\n    return x  # TODO(alice): cache\n# NOTE: kept\nSQL = \"\"\"TODO: not a docstring\"\"\"\n";
+    let python: Python = parse_source("tasks.py", source).await?;
+    let tasks = &python.metrics().tasks;
+    assert_eq!(tasks.len(), 3);
+    assert_eq!(tasks[1].author.as_deref(), Some("alice"));
+
+    let tags = vec!["TODO".to_string(), "FIXME".to_string()];
+    let report = TaskReport::new(tasks, &tags);
+    assert_eq!(report.markers.len(), 2);
+    assert_eq!(report.counts["FIXME"], 1);
+    assert_eq!(report.sarif_results()[0].level, "warning");
+
+    let gates = Gates {
+        max_fixmes: Some(0),
+        ..Default::default()
+    };
+    assert_eq!(gates.check(python.metrics()).len(), 1);
+
+    let rust: Rust = parse_source("tasks.rs", "fn f() {} /* XXX: remove */\n").await?;
+    assert_eq!(rust.metrics().tasks[0].tag, "XXX");
+    assert_eq!(rust.metrics().tasks[0].text, "remove");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_unsafe_sites() -> Result<(), PyLineError> {
+    let source = r#"pub unsafe fn raw(p: *const u8) -> u8 {
+    // SAFETY: the caller guarantees that `p` is valid.
+    unsafe { *p }
+}
+
+unsafe impl Send for Handle {}
+pub unsafe trait Zeroable {}
+
+unsafe extern "C" {
+    fn abs(x: i32) -> i32;
+}
+
+fn call(f: unsafe fn()) {
+    let s = "unsafe { }";
+    unsafe {
+        f();
+    }
+}
+"#;
+    let rust: Rust = parse_source("unsafe.rs", source).await?;
+    let report = UnsafeReport::new(&rust.metrics().unsafe_sites);
+    assert_eq!(report.sites.len(), 6);
+    assert_eq!(report.counts[&UnsafeKind::Block], 2);
+    assert_eq!(report.counts[&UnsafeKind::Function], 1);
+    assert_eq!(report.counts[&UnsafeKind::Extern], 1);
+    assert_eq!(report.block_lines, 4);
+    assert_eq!(report.undocumented_blocks, 1);
+    assert_eq!(report.sarif_results()[0].line, 15);
+
+    let gates = Gates {
+        max_unsafe: Some(6),
+        ..Default::default()
+    };
+    assert!(gates.check(rust.metrics()).is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_panic_sites() -> Result<(), PyLineError> {
+    let source = r#"fn load(path: &str) -> Config {
+    let text = read(path).unwrap();
+    let first = text.lines().next().expect("empty file");
+    if first.is_empty() {
+        panic!("no header");
+    }
+    let values: [u8; 4] = [0; 4];
+    let _ = "x.unwrap()";
+    parse(&first[1..], values[0]).unwrap_or_default()
+}
+
+fn later() -> u8 {
+    todo!()
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn loads() {
+        load("a").unwrap();
+        unreachable!();
+    }
+}
+"#;
+    let rust: Rust = parse_source("panics.rs", source).await?;
+    let sites = &rust.metrics().panic_sites;
+
+    let report = PanicReport::new(sites, &rust.metrics().files, false, 10);
+    assert_eq!(report.total.code, 4);
+    assert_eq!(report.total.test, 2);
+    assert_eq!(report.counts[&PanicKind::Unwrap].code, 1);
+    assert_eq!(report.counts[&PanicKind::Unwrap].test, 1);
+    assert_eq!(report.counts[&PanicKind::Todo].code, 1);
+    assert!(!report.counts.contains_key(&PanicKind::Index));
+    assert_eq!(rust.metrics().files[0].test_lines, 7);
+
+    let robustness = report.crates.values().next().unwrap();
+    assert_eq!(robustness.panic_sites, 4);
+    assert!(robustness.score < 100.0);
+
+    let report = PanicReport::new(sites, &rust.metrics().files, true, 10);
+    assert_eq!(report.counts[&PanicKind::Index].code, 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_macros_and_attributes() -> Result<(), PyLineError> {
+    let source = r##"#![allow(dead_code)]
+
+#[derive(Debug, Clone, serde::Serialize)]
+#[allow(clippy::too_many_arguments, unused)]
+struct Config {
+    values: Vec<u8>,
+}
+
+#[tokio::main]
+async fn main() {
+    let values = vec![1, 2];
+    println!("{:?}", values);
+    if !(values.is_empty()) {
+        std::println!("#[derive(Copy)] {}", format!("x"));
+    }
+}
+"##;
+    let rust: Rust = parse_source("macros.rs", source).await?;
+    let counts = &rust.metrics().macros;
+    assert_eq!(counts.macros["println"], 2);
+    assert_eq!(counts.macros["vec"], 1);
+    assert_eq!(counts.macros["format"], 1);
+    assert!(!counts.macros.contains_key("if"));
+    assert_eq!(counts.attributes["allow"], 2);
+    assert_eq!(counts.attributes["tokio::main"], 1);
+    assert_eq!(counts.derives["serde::Serialize"], 1);
+    assert!(!counts.derives.contains_key("Copy"));
+    assert_eq!(counts.allows["clippy::too_many_arguments"], 1);
+
+    let report = MacroReport::new(counts, 2);
+    assert_eq!(report.macro_calls, 4);
+    assert_eq!(report.macros[0], ("println".to_string(), 2));
+    assert_eq!(report.macros.len(), 2);
+    assert_eq!(report.suppressions, 3);
+    assert_eq!(report.allows.len(), 3);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_python_usage() -> Result<(), PyLineError> {
+    let source = r#"import os.path, sys
+import requests as rq
+from .models import *
+from shapes import Circle
+from django.db import (
+    models,
+)
+import json; from . import utils, helpers
+
+class Shape:
+    @property
+    def area(self):
+        return 0
+
+    @functools.lru_cache(maxsize=None)
+    def __eq__(self, other):
+        return True
+
+    def __repr__(self):
+        return "import json"
+
+@pytest.fixture
+def shape():
+    return Shape()
+"#;
+    let result: Python = parse_source("shapes.py", source).await?;
+    let metrics = result.metrics();
+    assert_eq!(metrics.imports.len(), 8);
+    assert_eq!(metrics.imports[5].names, vec!["models"]);
+    assert_eq!(metrics.imports[7].line, 8);
+
+    let report = UsageReport::new(&metrics.imports, &metrics.symbols, &metrics.files, 10);
+    assert_eq!(report.import_counts[&ImportOrigin::Stdlib], 3);
+    assert_eq!(report.import_counts[&ImportOrigin::ThirdParty], 2);
+    assert_eq!(report.import_counts[&ImportOrigin::Local], 3);
+    assert!(report.imports[&ImportOrigin::Local].contains(&(".utils".to_string(), 1)));
+    assert_eq!(report.decorators.len(), 3);
+    assert!(
+        report
+            .decorators
+            .contains(&("functools.lru_cache".to_string(), 1))
+    );
+    assert_eq!(
+        report.dunders,
+        vec![("__eq__".to_string(), 1), ("__repr__".to_string(), 1)]
+    );
+    assert_eq!(report.star_imports.len(), 1);
+    assert_eq!(report.relative_imports[0].line, 3);
+
+    let dependencies: Vec<&str> = report
+        .dependencies
+        .values()
+        .flatten()
+        .map(String::as_str)
+        .collect();
+    assert_eq!(dependencies, vec!["django", "requests"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_rust_imports() -> Result<(), PyLineError> {
+    let source = r#"extern crate alloc;
+mod cli;
+pub(crate) mod tools { }
+use crate::metrics::{self, gates::Gates, usage::*};
+use std::io::{
+    Read as _,
+    Write,
+};
+fn f(x: impl Sized + use<>) {}
+"#;
+    let rust: Rust = parse_source("main.rs", source).await?;
+    let modules: Vec<(&str, bool)> = rust
+        .metrics()
+        .imports
+        .iter()
+        .map(|i| (i.module.as_str(), i.star))
+        .collect();
+    assert_eq!(
+        modules,
+        vec![
+            ("alloc", false),
+            ("self::cli", false),
+            ("crate::metrics", false),
+            ("crate::metrics::gates::Gates", false),
+            ("crate::metrics::usage", true),
+            ("std::io::Read", false),
+            ("std::io::Write", false),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_python_dependency_graph() {
+    let files: Vec<FileMetrics> = [
+        "app/__init__.py",
+        "app/models.py",
+        "app/views.py",
+        "app/api/__init__.py",
+        "app/api/routes.py",
+        "manage.py",
+    ]
+    .into_iter()
+    .map(FileMetrics::new)
+    .collect();
+    let import = |path: &str, module: &str| Import {
+        path: path.into(),
+        line: 1,
+        module: module.to_string(),
+        names: Vec::new(),
+        star: false,
+    };
+    let imports = vec![
+        import("app/views.py", ".models"),
+        import("app/views.py", "os"),
+        import("app/models.py", "app.views.helper"),
+        import("app/api/routes.py", "..models"),
+        import("app/api/__init__.py", ".routes"),
+        import("manage.py", "app.api"),
+    ];
+
+    let graph = DependencyGraph::new(&imports, &files, 10);
+    assert_eq!(graph.modules.len(), 6);
+    assert_eq!(graph.edge_count(), 5);
+    assert!(graph.modules["app.api.routes"].contains("app.models"));
+    assert!(graph.modules["app.api"].contains("app.api.routes"));
+    assert!(graph.modules["manage"].contains("app.api"));
+    assert_eq!(graph.cycles, vec![vec!["app.models", "app.views"]]);
+    assert_eq!(graph.fan_in[0], ("app.models".to_string(), 2));
+
+    assert!(graph.to_dot().contains("\"app.views\" -> \"app.models\";"));
+    assert!(graph.to_mermaid().starts_with("graph LR\n    m0[\"app\"]"));
+}
+
+#[test]
+fn test_python_graph_relative_from_imports() {
+    let files: Vec<FileMetrics> = ["pkg/__init__.py", "pkg/a.py", "pkg/b.py"]
+        .into_iter()
+        .map(FileMetrics::new)
+        .collect();
+    let import = |path: &str, module: &str, names: &[&str]| Import {
+        path: path.into(),
+        line: 1,
+        module: module.to_string(),
+        names: names.iter().map(|n| n.to_string()).collect(),
+        star: false,
+    };
+    let imports = vec![
+        import("pkg/__init__.py", ".a", &["A"]),
+        import("pkg/a.py", ".", &["b"]),
+    ];
+
+    let graph = DependencyGraph::new(&imports, &files, 10);
+    assert_eq!(graph.edge_count(), 2);
+    assert!(graph.modules["pkg"].contains("pkg.a"));
+    assert!(graph.modules["pkg.a"].contains("pkg.b"));
+    assert!(graph.cycles.is_empty());
+    assert!(graph.to_dot().contains("\"pkg.a\" -> \"pkg.b\""));
+}
+
+#[tokio::test]
+async fn test_type_hints() -> Result<(), PyLineError> {
+    let source = r#"from typing import Any, cast
+
+class Repo:
+    def get(self, key: str, default=None) -> Any:
+        return cast(Any, self.data.get(key))  # type: ignore[no-any-return]
+
+    @classmethod
+    async def load(
+        cls,
+        path: str,
+        *,
+        strict: bool = lambda x: x,
+        **options,
+    ):
+        return typing.cast("Repo", None)
+
+def broadcast(x, *args: int) -> None:
+    """def fake(a, b):"""
+"#;
+    let result: Python = parse_source("repo.py", source).await?;
+    let hints = result.metrics().files[0].type_hints;
+    assert_eq!(hints.functions, 3);
+    assert_eq!(hints.annotated_returns, 2);
+    assert_eq!(hints.params, 7);
+    assert_eq!(hints.annotated_params, 4);
+    assert_eq!(hints.any, 2);
+    assert_eq!(hints.casts, 2);
+    assert_eq!(hints.type_ignores, 1);
+
+    // Imports of `Any` are not uses.
+    let source = "import typing; from typing import (\n    Any,\n)\n\ndef f(x: Any): pass\n";
+    let imports: Python = parse_source("any.py", source).await?;
+    assert_eq!(imports.metrics().files[0].type_hints.any, 1);
+
+    let report = TypeHintReport::new(&result.metrics().files, 10);
+    assert_eq!(report.packages.len(), 1);
+    assert!((report.total.coverage() - 60.0).abs() < 1e-9);
+
+    let gates = Gates {
+        min_type_coverage: Some(75.0),
+        ..Default::default()
+    };
+    assert_eq!(gates.check(result.metrics()).len(), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_duplicate_code() -> Result<(), PyLineError> {
+    let source = r#"def first(items):
+    total = 0
+    for item in items:
+        if item > 10:
+            total += item
+        else:
+            total -= 1
+    return total
+
+
+def second(items):
+    total = 0
+    for item in items:  # same code
+        if item > 10:
+            total += item
+        else:
+            total -= 1
+    return total
+
+
+def third(values):
+    acc = 1
+    for value in values:
+        if value > 20:
+            acc += value
+        else:
+            acc -= 2
+    return acc
+"#;
+    let result: Python = parse_source("dup.py", source).await?;
+    let files = &result.metrics().files;
+
+    let report = DuplicateReport::new(files, 6, false, 10);
+    assert_eq!(report.groups.len(), 1);
+    let group = &report.groups[0];
+    assert_eq!(group.lines, 7);
+    assert_eq!(group.locations.len(), 2);
+    assert_eq!(group.locations[0].start_line, 2);
+    assert_eq!(group.locations[0].end_line, 8);
+    assert_eq!(group.locations[1].start_line, 12);
+    assert_eq!(report.duplicated_lines, 14);
+    assert!((report.duplicated_percent - 14.0 * 100.0 / 24.0).abs() < 1e-9);
+
+    let report = DuplicateReport::new(files, 6, true, 10);
+    assert_eq!(report.groups[0].locations.len(), 3);
+    assert_eq!(report.groups[0].lines, 8);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_halstead_metrics() -> Result<(), PyLineError> {
+    let result: Python = parse_source("calc.py", "def add(a, b):\n    return a + b\n").await?;
+    let metrics = result.metrics();
+    let expected = Halstead {
+        distinct_operators: 6,
+        distinct_operands: 3,
+        operators: 6,
+        operands: 5,
+    };
+    assert_eq!(metrics.symbols[0].halstead, expected);
+    assert_eq!(metrics.files[0].halstead, expected);
+    assert!((expected.volume() - 11.0 * 9f64.log2()).abs() < 1e-9);
+    assert!((expected.difficulty() - 5.0).abs() < 1e-9);
+
+    let report = HalsteadReport::new(&metrics.files, &metrics.symbols, 10);
+    let function = &report.least_maintainable_functions[0];
+    assert_eq!(function.name.as_deref(), Some("add"));
+    assert!(
+        (function.maintainability - maintainability_index(expected.volume(), 1, 2)).abs() < 1e-9
+    );
+    assert!((function.maintainability - 82.5).abs() < 0.1);
+
+    let source = "fn twice(x: u32) -> u32 {\n    let s = \"a\";\n    x * 2\n}\n";
+    let result: Rust = parse_source("lib.rs", source).await?;
+    let halstead = result.metrics().symbols[0].halstead;
+    // fn ( : -> { let = ; *
+    assert_eq!(halstead.distinct_operators, 9);
+    // twice x u32 s "" 2
+    assert_eq!(halstead.distinct_operands, 6);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_comment_ratios() -> Result<(), PyLineError> {
+    let source = r#"# Helpers.
+import os
+
+
+def load(path):
+    """Load a file."""
+    return open(path)  # no context manager
+
+
+def save(path, data):
+    # TODO: atomic write
+    pass
+
+
+def _private():
+    pass
+"#;
+    let result: Python = parse_source("io.py", source).await?;
+    let stats = result.stats();
+    assert_eq!(stats.comment_lines, 3);
+    assert_eq!(stats.doc_lines, 1);
+    assert_eq!((stats.public_items, stats.documented_items), (2, 1));
+    assert_eq!(stats.doc_coverage(), Some(50.0));
+
+    // Only the first statement of a module, class or function body is a
+    // docstring.
+    let source = r#""""Module docs."""
+QUERY = """
+SELECT 1
+"""
+
+
+def join(parts):
+    "".join(parts)
+
+
+def render(page):
+    page.load()
+    """Not a docstring."""
+    return page
+"#;
+    let result: Python = parse_source("render.py", source).await?;
+    let stats = result.stats();
+    assert_eq!(stats.doc_lines, 1);
+    assert_eq!((stats.public_items, stats.documented_items), (2, 0));
+
+    let source = r#"//! Crate docs.
+
+/// Adds one.
+#[inline]
+pub fn inc(x: u32) -> u32 {
+    // overflow is fine here
+    x + 1
+}
+
+pub struct Undocumented;
+
+fn private() {}
+"#;
+    let result: Rust = parse_source("lib.rs", source).await?;
+    let docs = result.metrics().files[0].documentation;
+    assert_eq!(docs.code_lines, 9);
+    assert_eq!((docs.comment_lines, docs.doc_lines), (1, 2));
+    assert_eq!((docs.public_items, docs.documented_items), (2, 1));
+
+    let report = CommentReport::new(&result.metrics().files, 10);
+    assert_eq!(report.least_commented.len(), 1);
+    assert!((report.total.comment_ratio() - 1.0 / 9.0).abs() < 1e-9);
+    let dir = report.directories.values().next().unwrap();
+    assert_eq!(dir.documented_percent, Some(50.0));
+
+    // A directory without public items has no documented share.
+    let helpers: Rust = parse_source("helpers.rs", "fn helper() {}\n").await?;
+    let report = CommentReport::new(&helpers.metrics().files, 10);
+    let dir = report.directories.values().next().unwrap();
+    assert_eq!(dir.documented_percent, None);
+    assert!(report.to_string().contains("   n/a  "), "{}", report);
+
+    let gates = Gates {
+        min_comment_ratio: Some(0.2),
+        ..Default::default()
+    };
+    assert_eq!(gates.check(result.metrics()).len(), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_line_lengths() -> Result<(), PyLineError> {
+    let long = format!("x = '{}'", "a".repeat(100));
+    let source = format!("def f():\n\treturn 1\n\n{long}\n{long}  # noqa: E501\ns = '中文'\n");
+    let result: Python = parse_source("long.py", &source).await?;
+    let files = &result.metrics().files;
+    assert_eq!(files[0].line_widths.len(), 5);
+
+    let report = LineLengthReport::new(files, 4, Some(100), 10);
+    assert_eq!((report.lines, report.exempt, report.over_limit), (5, 1, 1));
+    assert_eq!(report.histogram[&100], 1);
+    assert_eq!((report.longest[0].line, report.longest[0].width), (4, 106));
+    // The tab advances to column 4, the CJK characters are two columns wide.
+    let widths: Vec<usize> = report.longest.iter().map(|l| l.width).collect();
+    assert_eq!(widths, [106, 12, 10, 8]);
+    assert_eq!(
+        LineLengthReport::new(files, 8, None, 10).longest[1].width,
+        16
+    );
+
+    let source = format!(
+        "#[rustfmt::skip]\nconst T: &[&str] = &[\n    \"{}\",\n];\n",
+        "a".repeat(120)
+    );
+    let result: Rust = parse_source("table.rs", &source).await?;
+    let report = LineLengthReport::new(&result.metrics().files, 4, Some(100), 10);
+    assert_eq!((report.lines, report.exempt, report.over_limit), (4, 4, 0));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_whitespace_hygiene() -> Result<(), PyLineError> {
+    let source = "\u{feff}import os\r\ndef f():  \n\treturn '\t'\r\n    \n    pass";
+    let result: Python = parse_source("ws.py", source).await?;
+    assert_eq!(result.stats().lines_total, 5);
+    let whitespace = &result.metrics().files[0].whitespace;
+    assert_eq!(whitespace.trailing_whitespace, [2, 4]);
+    assert_eq!(whitespace.line_endings[&LineEnding::CrLf], 2);
+    assert_eq!(whitespace.line_endings[&LineEnding::Lf], 2);
+    assert!(whitespace.has_mixed_line_endings());
+    assert!(whitespace.missing_final_newline);
+    assert!(whitespace.bom);
+    assert_eq!(whitespace.tabs, [3]);
+    assert_eq!(result.metrics().imports[0].module, "os");
+
+    let result: Rust = parse_source("clean.rs", "fn main() {\n\tlet x = 1;\n}\n").await?;
+    let report = WhitespaceReport::new(&result.metrics().files);
+    assert_eq!((report.files_checked, report.files.len()), (1, 0));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_source_encodings() -> Result<(), PyLineError> {
+    let root = std::env::temp_dir().join(format!("metrics_test_{}", Uuid::new_v4()));
+    fs::create_dir_all(&root).await?;
+    let mut utf16 = vec![0xff, 0xfe];
+    utf16.extend(
+        "def f():\n    return 1\n"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes),
+    );
+    let mut broken = "# привет\n".as_bytes().to_vec();
+    broken.extend(b"x = '\xff'\n");
+    let sources: [(&str, &[u8]); 3] = [
+        ("utf16.py", &utf16),
+        (
+            "cp1251.py",
+            b"# \xcf\xf0\xe8\xe2\xe5\xf2 \xec\xe8\xf0\ndef g():\n    pass\n",
+        ),
+        ("broken.py", &broken),
+    ];
+    let mut files = Vec::new();
+    for (name, content) in sources {
+        let path = root.join(name);
+        fs::write(&path, content).await?;
+        files.push(FileData::new(path, content.len() as u64));
+    }
+
+    let utf16: Python = Python::parse_one(&files[0]).await?;
+    assert_eq!(utf16.stats().lines_total, 2);
+    assert_eq!(utf16.metrics().symbols[0].name, "f");
+    let cp1251: Python = Python::parse_one(&files[1]).await?;
+    let encoding = &cp1251.metrics().files[0].encoding;
+    assert_eq!(encoding.name, "windows-1251");
+    assert_eq!(encoding.detection, Detection::Heuristic);
+
+    assert!(Python::parse_one(&files[2]).await.is_err());
+    let broken = files.pop().map(|file| file.with_lossy(true)).unwrap();
+    let lossy: Python = Python::parse_one(&broken).await?;
+    assert_eq!(lossy.stats().code_lines, 1);
+
+    let mut metrics = utf16.metrics().clone();
+    metrics.merge_ref(cp1251.metrics());
+    metrics.merge_ref(lossy.metrics());
+    let report = EncodingReport::new(&metrics.files);
+    assert_eq!(report.encodings["UTF-8"], 1);
+    assert_eq!(report.encodings["UTF-16LE"], 1);
+    assert_eq!(report.detected.len(), 2);
+    assert_eq!(report.lossy, std::slice::from_ref(&broken.path));
+
+    fs::remove_dir_all(&root).await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_parse_failures() -> Result<(), PyLineError> {
+    let root = std::env::temp_dir().join(format!("metrics_test_{}", Uuid::new_v4()));
+    fs::create_dir_all(&root).await?;
+    let sources: [(&str, &[u8]); 3] = [
+        ("ok.py", b"x = 1\n"),
+        ("broken.py", b"x = 1\ny = '\xd0\x9f\xff'\n"),
+        ("bom.py", b"\xef\xbb\xbfx = 1\n\xd0\x9f\xff\n"),
+    ];
+    let mut files = Vec::new();
+    for (name, content) in sources {
+        let path = root.join(name);
+        fs::write(&path, content).await?;
+        files.push(FileData::new(path, content.len() as u64));
+    }
+
+    let mut stats = Python::new();
+    stats.parse(&files).await?;
+    assert_eq!(stats.stats().num_files_not_valid, 2);
+    let failure = |file: &FileData| {
+        stats
+            .failures()
+            .iter()
+            .find(|(path, _)| path == &file.path)
+            .map(|(_, error)| error)
+            .expect("expected a failure")
+    };
+    let error = failure(&files[1]);
+    assert_eq!(error.line(), Some(2));
+    assert!(matches!(error, PyLineError::ParseError { .. }));
+    // The offset counts the byte order mark, like the line.
+    let error = failure(&files[2]);
+    assert_eq!(error.line(), Some(2));
+    assert_eq!(error.message(), "invalid UTF-8 at byte 11");
+
+    fs::remove_dir_all(&root).await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_rust_impl_header_stray_angle_brackets() -> Result<(), PyLineError> {
+    let source = "impl<F: Fn() -> u8> Run for F {}\n\nimpl > Broken {}\n";
+    let result: Rust = parse_source("impls.rs", source).await?;
+    let names: Vec<_> = result
+        .metrics()
+        .symbols
+        .iter()
+        .map(|s| s.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["Run for F", "Broken"]);
+
+    Ok(())
+}
@@ -0,0 +1,71 @@
+use pyline_libs::collector::Collector;
+use pyline_libs::errors::PyLineError;
+use pyline_libs::parser::Python;
+use pyline_libs::traits::CodeParsers;
+use pyline_libs::watch::WatchState;
+use tokio::fs;
+use uuid::Uuid;
+
+#[tokio::test]
+async fn test_watch_state_reports_deltas() -> Result<(), PyLineError> {
+    let root = std::env::temp_dir().join(format!("watch_test_{}", Uuid::new_v4()));
+    fs::create_dir_all(&root).await?;
+    let source = root.join("main.py");
+    fs::write(&source, "def main():\n    return 1\n").await?;
+
+    let collector = Collector::new(&root).extensions(["py"]);
+    let files = collector.complete().await?;
+    let mut state = WatchState::<Python>::new(files.files()).await;
+    assert_eq!(state.totals().stats().code_lines, 2);
+
+    fs::write(&source, "def main():\n    pass\ndef other():\n    pass\n").await?;
+    let ignored = root.join("notes.txt");
+    fs::write(&ignored, "def").await?;
+
+    let update = state.update(&collector, &[source.clone(), ignored]).await?;
+    assert_eq!(update.changed, vec![source.clone()]);
+    assert_eq!(update.code_lines_delta, 2);
+    assert_eq!(update.keyword_deltas.get("def"), Some(&1));
+    assert_eq!(update.keyword_deltas.get("return"), Some(&-1));
+
+    fs::remove_file(&source).await?;
+    let update = state.update(&collector, &[source]).await?;
+    assert_eq!(update.files_delta, -1);
+    assert_eq!(state.totals().stats().num_files_total, 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_watch_state_keeps_failures() -> Result<(), PyLineError> {
+    let root = std::env::temp_dir().join(format!("watch_test_{}", Uuid::new_v4()));
+    fs::create_dir_all(&root).await?;
+    let source = root.join("main.py");
+    fs::write(&source, b"x = '\xd0\x9f\xff'\n").await?;
+
+    let collector = Collector::new(&root).extensions(["py"]);
+    let files = collector.complete().await?;
+    let mut state = WatchState::<Python>::new(files.files()).await;
+    assert_eq!(state.totals().stats().num_files_not_valid, 1);
+    assert_eq!(state.totals().failures()[0].0, source);
+
+    fs::write(&source, "x = 1\n").await?;
+    let update = state
+        .update(&collector, std::slice::from_ref(&source))
+        .await?;
+    assert!(update.failed.is_empty());
+    assert!(state.totals().failures().is_empty());
+    assert_eq!(state.totals().stats().code_lines, 1);
+
+    fs::write(&source, b"x = '\xd0\x9f\xff'\n").await?;
+    let update = state
+        .update(&collector, std::slice::from_ref(&source))
+        .await?;
+    assert_eq!(update.changed, vec![source.clone()]);
+    assert_eq!(update.failed, vec![source.clone()]);
+    assert_eq!(update.code_lines_delta, -1);
+    assert_eq!(state.totals().failures().len(), 1);
+
+    fs::remove_dir_all(&root).await?;
+    Ok(())
+}